@@ -1,13 +1,66 @@
-use wasm_bindgen::prelude::*;
-
 use serde::{Deserialize, Serialize};
 
-use crate::angle_fmt::AngleSerializeFmt;
-
 use super::color::ColorRGB;
 
-#[wasm_bindgen]
-#[derive(Deserialize, Serialize)]
+/// The shape of the markers drawn for each source
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Shape {
+    Circle,
+    #[default]
+    Square,
+    Plus,
+    Cross,
+    Rhomb,
+    Triangle,
+}
+
+/// Linear mapping of the values of a column to marker sizes in pixels
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeMapping {
+    /// Column value mapped to `min_size`
+    pub min: f32,
+    /// Column value mapped to `max_size`
+    pub max: f32,
+    pub min_size: f32,
+    pub max_size: f32,
+}
+
+impl SizeMapping {
+    pub fn size(&self, value: f32) -> f32 {
+        let t = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+        self.min_size + t * (self.max_size - self.min_size)
+    }
+}
+
+/// Mapping of the values of a column to the colors of a colormap
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorMapping {
+    pub colormap: String,
+    /// Column value mapped to the first color of the colormap
+    pub min: f32,
+    /// Column value mapped to the last color of the colormap
+    pub max: f32,
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+impl ColorMapping {
+    /// Normalize a column value between 0 and 1
+    pub fn normalize(&self, value: f32) -> f32 {
+        let t = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+        if self.reversed {
+            1.0 - t
+        } else {
+            t
+        }
+    }
+}
+
+/// Options for rendering a catalog with markers
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Catalog {
     #[serde(default = "default_color")]
@@ -15,9 +68,16 @@ pub struct Catalog {
     #[serde(default = "default_opacity")]
     pub opacity: Option<f32>,
     #[serde(default = "default_shape")]
-    pub shape: Option<&'static str>,
+    pub shape: Option<Shape>,
+    /// Size of the markers in pixels
     #[serde(default = "default_size")]
     pub size: Option<f32>,
+    /// Mapping of the size column values to marker sizes
+    #[serde(default = "default_size_mapping")]
+    pub size_mapping: Option<SizeMapping>,
+    /// Mapping of the color column values to a colormap
+    #[serde(default = "default_color_mapping")]
+    pub color_mapping: Option<ColorMapping>,
 }
 
 fn default_color() -> Option<ColorRGB> {
@@ -28,10 +88,61 @@ fn default_opacity() -> Option<f32> {
     None
 }
 
-fn default_shape() -> Option<&'static str> {
+fn default_shape() -> Option<Shape> {
+    None
+}
+
+fn default_size() -> Option<f32> {
+    None
+}
+
+fn default_size_mapping() -> Option<SizeMapping> {
     None
 }
 
-fn default_size() -> Option<AngleSerializeFmt> {
+fn default_color_mapping() -> Option<ColorMapping> {
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorMapping, SizeMapping};
+
+    #[test]
+    fn size_mapping_interpolates_and_clamps() {
+        let mapping = SizeMapping {
+            min: 10.0,
+            max: 20.0,
+            min_size: 2.0,
+            max_size: 12.0,
+        };
+
+        assert_eq!(mapping.size(10.0), 2.0);
+        assert_eq!(mapping.size(15.0), 7.0);
+        assert_eq!(mapping.size(20.0), 12.0);
+        // values out of the range get the extreme sizes
+        assert_eq!(mapping.size(0.0), 2.0);
+        assert_eq!(mapping.size(100.0), 12.0);
+    }
+
+    #[test]
+    fn color_mapping_normalizes() {
+        let mut mapping = ColorMapping {
+            colormap: "viridis".to_string(),
+            min: -1.0,
+            max: 1.0,
+            reversed: false,
+        };
+
+        assert_eq!(mapping.normalize(-1.0), 0.0);
+        assert_eq!(mapping.normalize(0.0), 0.5);
+        assert_eq!(mapping.normalize(0.5), 0.75);
+        assert_eq!(mapping.normalize(-5.0), 0.0);
+        assert_eq!(mapping.normalize(5.0), 1.0);
+
+        mapping.reversed = true;
+        assert_eq!(mapping.normalize(-1.0), 1.0);
+        assert_eq!(mapping.normalize(0.5), 0.25);
+        assert_eq!(mapping.normalize(5.0), 0.0);
+    }
+}
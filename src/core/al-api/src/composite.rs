@@ -0,0 +1,73 @@
+use serde::Deserialize;
+
+use crate::hips::ImageMetadata;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RGBCompositeCfg {
+    /// Layer name
+    pub layer: String,
+
+    /// The layers (HiPS or FITS images) giving the red, green and blue channels
+    pub red: ChannelCfg,
+    pub green: ChannelCfg,
+    pub blue: ChannelCfg,
+
+    /// The stretch applied to the intensity of the channels
+    #[serde(default)]
+    pub stretch: LuptonStretch,
+
+    /// Its opacity, blending and tonal corrections
+    pub meta: ImageMetadata,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelCfg {
+    /// The layer drawn in this channel
+    pub layer: String,
+    /// Cuts of the pixel values, the ones of the layer if not given
+    ///
+    /// The values of the channel are normalized between 0 and 1 before the stretch,
+    /// the cuts giving the background level and the relative weight of the channel
+    #[serde(default)]
+    pub min_cut: Option<f32>,
+    #[serde(default)]
+    pub max_cut: Option<f32>,
+}
+
+/// The asinh stretch of Lupton et al. (2004)
+///
+/// The intensity I of a pixel being the mean of its normalized channel values,
+/// each channel is multiplied by F(I)/I with F(I) = asinh(alpha * Q * I) / asinh(Q).
+/// The colors are therefore preserved, the saturated pixels being scaled down so that
+/// their brightest channel equals 1.
+///
+/// F is the Lupton et al. function normalized so that F(1) = 1 when alpha = 1.
+/// The stretch tends to be linear when Q tends to 0.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LuptonStretch {
+    /// Softening parameter
+    #[serde(rename = "Q", alias = "q", default = "default_q")]
+    pub q: f32,
+    /// Linear stretch of the intensity
+    #[serde(default = "default_alpha")]
+    pub alpha: f32,
+}
+
+impl Default for LuptonStretch {
+    fn default() -> Self {
+        LuptonStretch {
+            q: default_q(),
+            alpha: default_alpha(),
+        }
+    }
+}
+
+fn default_q() -> f32 {
+    8.0
+}
+fn default_alpha() -> f32 {
+    1.0
+}
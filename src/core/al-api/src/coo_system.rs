@@ -2,6 +2,9 @@ use cgmath::Matrix4;
 pub trait CooBaseFloat: Sized + 'static {
     const GALACTIC_TO_J2000: &'static Matrix4<Self>;
     const J2000_TO_GALACTIC: &'static Matrix4<Self>;
+    const ECLIPTIC_TO_J2000: &'static Matrix4<Self>;
+    const SUPERGALACTIC_TO_J2000: &'static Matrix4<Self>;
+    const FK4_B1950_TO_J2000: &'static Matrix4<Self>;
     const ID: &'static Matrix4<Self>;
 }
 
@@ -44,6 +47,63 @@ impl CooBaseFloat for f32 {
         1.0,
     );
 
+    const ECLIPTIC_TO_J2000: &'static Matrix4<Self> = &Matrix4::new(
+        0.9174821,
+        0.39777717,
+        0.0,
+        0.0,
+        -0.39777717,
+        0.9174821,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
+    const SUPERGALACTIC_TO_J2000: &'static Matrix4<Self> = &Matrix4::new(
+        -0.09572714,
+        0.42878512,
+        -0.89832044,
+        0.0,
+        -0.93504566,
+        0.27075058,
+        0.22887497,
+        0.0,
+        0.34135896,
+        0.8618802,
+        0.37501547,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
+    const FK4_B1950_TO_J2000: &'static Matrix4<Self> = &Matrix4::new(
+        0.9999375,
+        -0.0000271474,
+        -0.011182061,
+        0.0,
+        -0.0000271765,
+        0.9999882,
+        -0.0048579476,
+        0.0,
+        0.011182061,
+        0.004857948,
+        0.9999257,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
     const ID: &'static Matrix4<Self> = &Matrix4::new(
         1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
     );
@@ -87,12 +147,69 @@ impl CooBaseFloat for f64 {
         1.0,
     );
 
+    const ECLIPTIC_TO_J2000: &'static Matrix4<Self> = &Matrix4::new(
+        0.9174820620691818,
+        0.3977771559319137,
+        0.0,
+        0.0,
+        -0.3977771559319137,
+        0.9174820620691818,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
+    const SUPERGALACTIC_TO_J2000: &'static Matrix4<Self> = &Matrix4::new(
+        -0.09572714072498745,
+        0.4287851134887343,
+        -0.8983204556165199,
+        0.0,
+        -0.9350456532745491,
+        0.27075057870087615,
+        0.22887496679521832,
+        0.0,
+        0.34135896182902536,
+        0.861880183426672,
+        0.3750154778078048,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
+    const FK4_B1950_TO_J2000: &'static Matrix4<Self> = &Matrix4::new(
+        0.9999374784,
+        -0.0000271474,
+        -0.0111820611,
+        0.0,
+        -0.0000271765,
+        0.9999881997,
+        -0.0048579477,
+        0.0,
+        0.011182061,
+        0.0048579479,
+        0.9999256782,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+    );
+
     const ID: &'static Matrix4<Self> = &Matrix4::new(
         1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
     );
 }
 
-use cgmath::BaseFloat;
+use cgmath::{BaseFloat, Matrix};
 use serde::Deserialize;
 use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
@@ -100,20 +217,139 @@ use wasm_bindgen::prelude::*;
 pub enum CooSystem {
     ICRS = 0,
     GAL = 1,
+    /// Ecliptic frame defined by the mean ecliptic and equinox of J2000
+    ECL = 2,
+    /// Ecliptic frame defined by the mean ecliptic and equinox of the current date
+    ECLD = 3,
+    /// Supergalactic frame (de Vaucouleurs)
+    SGAL = 4,
+    /// FK4 frame, equinox and epoch B1950. The E-terms of aberration are neglected
+    FK4 = 5,
 }
 
-pub const NUM_COOSYSTEM: usize = 2;
+pub const NUM_COOSYSTEM: usize = 6;
 
 impl CooSystem {
+    /// Matrix converting a position given in `self` to `coo_system`
     #[inline]
-    pub fn to<S>(&self, coo_system: Self) -> &Matrix4<S>
+    pub fn to<S>(&self, coo_system: Self) -> Matrix4<S>
     where
         S: BaseFloat + CooBaseFloat,
     {
         match (self, coo_system) {
-            (CooSystem::GAL, CooSystem::ICRS) => S::GALACTIC_TO_J2000,
-            (CooSystem::ICRS, CooSystem::GAL) => S::J2000_TO_GALACTIC,
-            (_, _) => S::ID,
+            (CooSystem::GAL, CooSystem::ICRS) => *S::GALACTIC_TO_J2000,
+            (CooSystem::ICRS, CooSystem::GAL) => *S::J2000_TO_GALACTIC,
+            (c1, c2) if *c1 == c2 => *S::ID,
+            // All the other conversions go through ICRS.
+            // The matrices being rotations, their inverse is their transpose
+            (c1, c2) => c2.to_icrs::<S>().transpose() * c1.to_icrs::<S>(),
+        }
+    }
+
+    /// Tell whether the longitude of the frame is a right ascension
+    ///
+    /// Equatorial frames usually have their longitudes displayed in hours
+    #[inline]
+    pub fn is_equatorial(&self) -> bool {
+        matches!(self, CooSystem::ICRS | CooSystem::FK4)
+    }
+
+    fn to_icrs<S>(self) -> Matrix4<S>
+    where
+        S: BaseFloat + CooBaseFloat,
+    {
+        match self {
+            CooSystem::ICRS => *S::ID,
+            CooSystem::GAL => *S::GALACTIC_TO_J2000,
+            CooSystem::ECL => *S::ECLIPTIC_TO_J2000,
+            CooSystem::SGAL => *S::SUPERGALACTIC_TO_J2000,
+            CooSystem::FK4 => *S::FK4_B1950_TO_J2000,
+            CooSystem::ECLD => ECLIPTIC_OF_DATE_TO_J2000
+                .with(|ecl2icrs| ecl2icrs.cast::<S>())
+                .unwrap_or(*S::ID),
         }
     }
 }
+
+thread_local! {
+    // Computed once at its first use, the precession during a session being negligible
+    static ECLIPTIC_OF_DATE_TO_J2000: Matrix4<f64> =
+        ecliptic_of_date_to_j2000(current_julian_epoch());
+}
+
+/// Julian epoch (e.g. 2024.5) of the current date
+fn current_julian_epoch() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    let unix_ms = js_sys::Date::now();
+    #[cfg(not(target_arch = "wasm32"))]
+    let unix_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as f64)
+        .unwrap_or(0.0);
+
+    // 946728000000 ms is J2000.0 (2000-01-01T12:00:00) in unix time
+    2000.0 + (unix_ms - 946_728_000_000.0) / (86_400_000.0 * 365.25)
+}
+
+/// Matrix converting from the mean ecliptic of `epoch` to ICRS (J2000).
+fn ecliptic_of_date_to_j2000(epoch: f64) -> Matrix4<f64> {
+    // J2000 equator -> mean equator and equinox of date -> mean ecliptic of date
+    let j2000_to_ecl_date = rot_x(mean_obliquity(epoch)) * fk5_precession(2000.0, epoch);
+    j2000_to_ecl_date.transpose()
+}
+
+/// Matrix precessing a position from the mean equator and equinox of `from_equinox`
+/// to the mean equator and equinox of `to_equinox`.
+///
+/// Equinoxes are given as julian epochs (e.g. 2000.0 for J2000).
+/// Uses the IAU 1976 precession angles (Lieske et al. 1977) as done for FK5.
+/// The matrix applies to positions given in model space.
+pub fn fk5_precession(from_equinox: f64, to_equinox: f64) -> Matrix4<f64> {
+    let arcsec = std::f64::consts::PI / (180.0 * 3600.0);
+    // Julian centuries from J2000 to the starting equinox
+    let t0 = (from_equinox - 2000.0) / 100.0;
+    // Julian centuries between the two equinoxes
+    let t = (to_equinox - from_equinox) / 100.0;
+
+    let w = 2306.2181 + (1.39656 - 0.000139 * t0) * t0;
+    let zeta = (w + ((0.30188 - 0.000344 * t0) + 0.017998 * t) * t) * t * arcsec;
+    let z = (w + ((1.09468 + 0.000066 * t0) + 0.018203 * t) * t) * t * arcsec;
+    let theta = ((2004.3109 + (-0.85330 - 0.000217 * t0) * t0)
+        + ((-0.42665 - 0.000217 * t0) - 0.041833 * t) * t)
+        * t
+        * arcsec;
+
+    rot_z(-z) * rot_y(theta) * rot_z(-zeta)
+}
+
+/// Mean obliquity of the ecliptic in radians at a julian epoch (IAU 1976)
+pub fn mean_obliquity(epoch: f64) -> f64 {
+    let arcsec = std::f64::consts::PI / (180.0 * 3600.0);
+    let t = (epoch - 2000.0) / 100.0;
+
+    (((0.001813 * t - 0.00059) * t - 46.8150) * t + 84381.448) * arcsec
+}
+
+// Rotations around the X, Y and Z axis of the usual equatorial cartesian frame
+// (X pointing towards (0, 0), Z towards the north pole), expressed in model space
+// where a position is stored as (Y, Z, X)
+fn rot_x(a: f64) -> Matrix4<f64> {
+    let (s, c) = a.sin_cos();
+    Matrix4::new(
+        c, -s, 0.0, 0.0, s, c, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+fn rot_y(a: f64) -> Matrix4<f64> {
+    let (s, c) = a.sin_cos();
+    Matrix4::new(
+        1.0, 0.0, 0.0, 0.0, 0.0, c, -s, 0.0, 0.0, s, c, 0.0, 0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+fn rot_z(a: f64) -> Matrix4<f64> {
+    let (s, c) = a.sin_cos();
+    Matrix4::new(
+        c, 0.0, s, 0.0, 0.0, 1.0, 0.0, 0.0, -s, 0.0, c, 0.0, 0.0, 0.0, 0.0, 1.0,
+    )
+}
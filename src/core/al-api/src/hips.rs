@@ -2,6 +2,7 @@ use wasm_bindgen::JsValue;
 
 use super::blend::BlendCfg;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -13,6 +14,35 @@ pub struct HiPSCfg {
     pub properties: HiPSProperties,
     /// Its color
     pub meta: ImageMetadata,
+    /// The options of its HTTP requests
+    #[serde(default)]
+    pub request_options: RequestOptions,
+}
+
+/// The options of the HTTP requests of a HiPS (tiles, allsky, MOC and metadata)
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    /// Extra headers e.g. `{"Authorization": "Bearer <token>"}`
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Whether the cookies and the HTTP authentication are sent
+    #[serde(default)]
+    pub credentials: Credentials,
+    /// Called when a request is answered with HTTP 401, it returns the new
+    /// bearer token or a promise of it. A function cannot be deserialized so it is set apart.
+    #[serde(skip)]
+    pub refresh_token: Option<js_sys::Function>,
+}
+
+/// The credentials mode of a request, see the `credentials` option of `fetch`
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Credentials {
+    Omit,
+    #[default]
+    SameOrigin,
+    Include,
 }
 
 impl HiPSCfg {
@@ -48,13 +78,23 @@ pub struct HiPSProperties {
     hips_initial_ra: Option<f64>,
     hips_initial_dec: Option<f64>,
 
-    // Parametrable by the user
-    #[allow(unused)]
+    // Number of frames of a HiPS cube
+    hips_cube_depth: Option<u32>,
+    // Frame displayed first for a HiPS cube
+    hips_cube_firstframe: Option<u32>,
+
+    // Whether the FITS tiles are tile-compressed (fits.fz)
+    fits_compressed: Option<bool>,
+
+    // Default cuts of the FITS tiles (hips_pixel_cut or hips_data_range properties)
     min_cutout: Option<f32>,
-    #[allow(unused)]
     max_cutout: Option<f32>,
 
     creator_did: String,
+
+    // The other urls serving the HiPS, e.g. given by the hips_service_url_n properties
+    #[serde(default)]
+    mirrors: Vec<String>,
 }
 
 impl HiPSProperties {
@@ -63,6 +103,11 @@ impl HiPSProperties {
         &self.url
     }
 
+    #[inline(always)]
+    pub fn get_mirrors(&self) -> &[String] {
+        &self.mirrors
+    }
+
     #[inline(always)]
     pub fn get_creator_did(&self) -> &str {
         &self.creator_did
@@ -117,6 +162,26 @@ impl HiPSProperties {
     pub fn get_initial_dec(&self) -> Option<f64> {
         self.hips_initial_dec
     }
+
+    #[inline(always)]
+    pub fn get_cube_depth(&self) -> Option<u32> {
+        self.hips_cube_depth
+    }
+
+    #[inline(always)]
+    pub fn get_cube_first_frame(&self) -> Option<u32> {
+        self.hips_cube_firstframe
+    }
+
+    #[inline(always)]
+    pub fn is_fits_compressed(&self) -> bool {
+        self.fits_compressed.unwrap_or(false)
+    }
+
+    #[inline(always)]
+    pub fn get_cutouts(&self) -> Option<(f32, f32)> {
+        self.min_cutout.zip(self.max_cutout)
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -142,31 +207,160 @@ impl std::fmt::Display for ImageExt {
 
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
-#[wasm_bindgen]
+/// The stretch applied to the pixel values normalized between the cuts
+///
+/// It is given as a name, optionally followed by its parameter
+/// e.g. "linear", "asinh(20)", "power(2.5)" or "histeq".
 #[derive(Clone, Copy, PartialEq, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(try_from = "String", into = "String")]
 pub enum TransferFunction {
     Linear,
     Sqrt,
-    Log,
-    Asinh,
+    /// log(a*x + 1) / log(a + 1)
+    Log {
+        a: f32,
+    },
+    /// asinh(a*x) / asinh(a)
+    Asinh {
+        a: f32,
+    },
     Pow2,
+    /// sinh(a*x) / sinh(a)
+    Sinh {
+        a: f32,
+    },
+    /// x^exponent
+    Power {
+        exponent: f32,
+    },
+    /// Histogram equalization, given by the cumulative distribution of the pixel values
+    HistEq,
 }
 
+const DEFAULT_LOG_A: f32 = 1000.0;
+const DEFAULT_ASINH_A: f32 = 10.0;
+const DEFAULT_SINH_A: f32 = 3.0;
+// Beyond, sinh(a) overflows the single precision floats of the shaders
+const MAX_SINH_A: f32 = 80.0;
+
 impl TransferFunction {
-    pub fn new(id: &str) -> Self {
-        if id.contains("linear") {
-            TransferFunction::Linear
-        } else if id.contains("pow2") {
-            TransferFunction::Pow2
-        } else if id.contains("log") {
-            TransferFunction::Log
-        } else if id.contains("sqrt") {
-            TransferFunction::Sqrt
-        } else {
-            TransferFunction::Asinh
+    pub fn new(id: &str) -> Result<Self, String> {
+        let id = id.trim().to_lowercase();
+        // Split the name from its optional parameter
+        let (name, param) = match id.find('(') {
+            Some(idx) => {
+                let param = id[idx + 1..]
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("Missing closing parenthesis in stretch \"{}\"", id))?
+                    .trim();
+                let param = param
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|p| p.is_finite())
+                    .ok_or_else(|| {
+                        format!("Invalid parameter \"{}\" for stretch \"{}\"", param, id)
+                    })?;
+
+                (id[..idx].trim(), Some(param))
+            }
+            None => (id.as_str(), None),
+        };
+
+        let no_param = |f: TransferFunction| {
+            if param.is_some() {
+                Err(format!("The stretch \"{}\" takes no parameter", name))
+            } else {
+                Ok(f)
+            }
+        };
+        let positive = |p: f32| {
+            if p > 0.0 {
+                Ok(p)
+            } else {
+                Err(format!(
+                    "The parameter of the stretch \"{}\" must be positive",
+                    name
+                ))
+            }
+        };
+
+        match name {
+            "linear" => no_param(TransferFunction::Linear),
+            "sqrt" => no_param(TransferFunction::Sqrt),
+            "pow2" => no_param(TransferFunction::Pow2),
+            "histeq" => no_param(TransferFunction::HistEq),
+            "log" => Ok(TransferFunction::Log {
+                a: positive(param.unwrap_or(DEFAULT_LOG_A))?,
+            }),
+            "asinh" => Ok(TransferFunction::Asinh {
+                a: positive(param.unwrap_or(DEFAULT_ASINH_A))?,
+            }),
+            "sinh" => {
+                let a = positive(param.unwrap_or(DEFAULT_SINH_A))?;
+                if a > MAX_SINH_A {
+                    Err(format!(
+                        "The parameter of the stretch \"sinh\" must not exceed {}",
+                        MAX_SINH_A
+                    ))
+                } else {
+                    Ok(TransferFunction::Sinh { a })
+                }
+            }
+            "power" => Ok(TransferFunction::Power {
+                exponent: positive(param.ok_or_else(|| {
+                    "The stretch \"power\" needs an exponent e.g. \"power(2.5)\"".to_string()
+                })?)?,
+            }),
+            _ => Err(format!(
+                "Unknown stretch \"{}\". Possible values are \"linear\", \"sqrt\", \"log\", \"asinh\", \"pow2\", \"sinh\", \"power(n)\" or \"histeq\"",
+                name
+            )),
         }
     }
+
+    /// The id of the function given to the shaders
+    pub fn id(&self) -> i32 {
+        match self {
+            TransferFunction::Linear => 0,
+            TransferFunction::Sqrt => 1,
+            TransferFunction::Log { .. } => 2,
+            TransferFunction::Asinh { .. } => 3,
+            TransferFunction::Pow2 => 4,
+            TransferFunction::Sinh { .. } => 5,
+            TransferFunction::Power { .. } => 6,
+            TransferFunction::HistEq => 7,
+        }
+    }
+
+    /// The parameter of the function, 0 if it does not have any
+    pub fn param(&self) -> f32 {
+        match self {
+            TransferFunction::Log { a }
+            | TransferFunction::Asinh { a }
+            | TransferFunction::Sinh { a } => *a,
+            TransferFunction::Power { exponent } => *exponent,
+            _ => 0.0,
+        }
+    }
+
+    /// Stretch a value normalized between the cuts as the shaders do
+    ///
+    /// Returns `None` for the histogram equalization as it depends on the pixel values
+    pub fn eval(&self, x: f32) -> Option<f32> {
+        let x = x.clamp(0.0, 1.0);
+        let y = match *self {
+            TransferFunction::Linear => x,
+            TransferFunction::Sqrt => x.sqrt(),
+            TransferFunction::Log { a } => (a * x).ln_1p() / a.ln_1p(),
+            TransferFunction::Asinh { a } => (a * x).asinh() / a.asinh(),
+            TransferFunction::Pow2 => x * x,
+            TransferFunction::Sinh { a } => (a * x).sinh() / a.sinh(),
+            TransferFunction::Power { exponent } => x.powf(exponent),
+            TransferFunction::HistEq => return None,
+        };
+
+        Some(y)
+    }
 }
 
 impl Default for TransferFunction {
@@ -175,12 +369,46 @@ impl Default for TransferFunction {
     }
 }
 
-impl From<String> for TransferFunction {
-    fn from(id: String) -> Self {
+impl std::str::FromStr for TransferFunction {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        TransferFunction::new(id)
+    }
+}
+
+impl std::convert::TryFrom<String> for TransferFunction {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
         TransferFunction::new(&id)
     }
 }
 
+impl std::fmt::Display for TransferFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            TransferFunction::Linear => write!(f, "linear"),
+            TransferFunction::Sqrt => write!(f, "sqrt"),
+            TransferFunction::Pow2 => write!(f, "pow2"),
+            TransferFunction::HistEq => write!(f, "histeq"),
+            TransferFunction::Log { a } if a == DEFAULT_LOG_A => write!(f, "log"),
+            TransferFunction::Log { a } => write!(f, "log({})", a),
+            TransferFunction::Asinh { a } if a == DEFAULT_ASINH_A => write!(f, "asinh"),
+            TransferFunction::Asinh { a } => write!(f, "asinh({})", a),
+            TransferFunction::Sinh { a } if a == DEFAULT_SINH_A => write!(f, "sinh"),
+            TransferFunction::Sinh { a } => write!(f, "sinh({})", a),
+            TransferFunction::Power { exponent } => write!(f, "power({})", exponent),
+        }
+    }
+}
+
+impl From<TransferFunction> for String {
+    fn from(f: TransferFunction) -> Self {
+        f.to_string()
+    }
+}
+
 use crate::colormap::CmapLabel;
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -320,3 +548,72 @@ impl ImageMetadata {
         self.opacity > 0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TransferFunction;
+
+    #[test]
+    fn stretches_map_the_cuts_to_the_unit_interval() {
+        for stretch in [
+            "linear",
+            "sqrt",
+            "pow2",
+            "log",
+            "log(0.01)",
+            "log(0.5)",
+            "log(1)",
+            "log(1e6)",
+            "asinh",
+            "asinh(0.01)",
+            "asinh(1)",
+            "asinh(1000)",
+            "sinh",
+            "sinh(0.01)",
+            "sinh(1)",
+            "sinh(80)",
+            "power(0.1)",
+            "power(1)",
+            "power(4)",
+        ] {
+            let f = TransferFunction::new(stretch).unwrap();
+
+            assert!(f.eval(0.0).unwrap().abs() < 1e-6, "{}", stretch);
+            assert!((f.eval(1.0).unwrap() - 1.0).abs() < 1e-5, "{}", stretch);
+
+            // Increasing between the cuts and clamped outside
+            let mut prev = f.eval(-1.0).unwrap();
+            for i in 0..=100 {
+                let y = f.eval(i as f32 / 100.0).unwrap();
+                assert!(y.is_finite() && y >= prev, "{}", stretch);
+                prev = y;
+            }
+            assert_eq!(f.eval(2.0), f.eval(1.0), "{}", stretch);
+        }
+
+        assert_eq!(TransferFunction::HistEq.eval(0.5), None);
+    }
+
+    #[test]
+    fn stretch_parameters_must_be_positive() {
+        for stretch in [
+            "log(0)",
+            "log(-1)",
+            "asinh(0)",
+            "sinh(-2)",
+            "sinh(100)",
+            "power(0)",
+            "power",
+        ] {
+            assert!(TransferFunction::new(stretch).is_err(), "{}", stretch);
+        }
+        assert!(TransferFunction::new("linear(2)").is_err());
+        assert!(TransferFunction::new("cubic").is_err());
+
+        assert_eq!(
+            TransferFunction::new(" Log(0.5) "),
+            Ok(TransferFunction::Log { a: 0.5 })
+        );
+        assert_eq!(TransferFunction::new("asinh").unwrap().to_string(), "asinh");
+    }
+}
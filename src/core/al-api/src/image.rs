@@ -10,3 +10,131 @@ pub struct ImageParams {
     pub min_cut: f32,
     pub max_cut: f32,
 }
+
+/// The algorithm computing the cuts of a FITS image from its pixel values
+///
+/// Given from javascript as an object whose `algorithm` field is either `percentile`,
+/// `zscale` or `sigmaClip`, the missing parameters taking their default value
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "algorithm")]
+pub enum CutAlgorithm {
+    /// Cuts at two percentiles of the pixel values
+    #[serde(rename = "percentile")]
+    Percentile {
+        #[serde(default = "default_low_percent")]
+        low: f32,
+        #[serde(default = "default_high_percent")]
+        high: f32,
+    },
+    /// The IRAF zscale algorithm
+    ///
+    /// A line is fitted to the sorted samples with an iterative k-sigma rejection,
+    /// its slope divided by the contrast giving the range around the median
+    #[serde(rename = "zscale", rename_all = "camelCase")]
+    ZScale {
+        #[serde(default = "default_contrast")]
+        contrast: f32,
+        #[serde(default = "default_num_samples")]
+        num_samples: u32,
+        #[serde(default = "default_max_reject")]
+        max_reject: f32,
+        #[serde(default = "default_min_npixels")]
+        min_npixels: u32,
+        #[serde(default = "default_krej")]
+        krej: f32,
+        #[serde(default = "default_max_iterations")]
+        max_iterations: u32,
+    },
+    /// Iterative sigma clipping around the median, sigma being estimated
+    /// from the median absolute deviation
+    ///
+    /// The cuts are the extrema of the values that have not been clipped
+    #[serde(rename = "sigmaClip", rename_all = "camelCase")]
+    SigmaClip {
+        #[serde(default = "default_sigma")]
+        sigma: f32,
+        #[serde(default = "default_max_iterations")]
+        max_iterations: u32,
+    },
+}
+
+impl Default for CutAlgorithm {
+    fn default() -> Self {
+        CutAlgorithm::Percentile {
+            low: default_low_percent(),
+            high: default_high_percent(),
+        }
+    }
+}
+
+fn default_low_percent() -> f32 {
+    1.0
+}
+fn default_high_percent() -> f32 {
+    99.0
+}
+fn default_contrast() -> f32 {
+    0.25
+}
+fn default_num_samples() -> u32 {
+    1000
+}
+fn default_max_reject() -> f32 {
+    0.5
+}
+fn default_min_npixels() -> u32 {
+    5
+}
+fn default_krej() -> f32 {
+    2.5
+}
+fn default_max_iterations() -> u32 {
+    5
+}
+fn default_sigma() -> f32 {
+    3.0
+}
+
+/// Parameters of the histogram of the pixel values of a layer
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramParams {
+    /// Number of bins
+    #[serde(default = "default_num_bins")]
+    pub num_bins: u32,
+    /// Logarithmically spaced bins, the values lower or equal to 0 being excluded
+    #[serde(default)]
+    pub log: bool,
+    /// Range of the bins, the extrema of the values if not given
+    #[serde(default)]
+    pub min: Option<f32>,
+    #[serde(default)]
+    pub max: Option<f32>,
+}
+
+impl Default for HistogramParams {
+    fn default() -> Self {
+        HistogramParams {
+            num_bins: default_num_bins(),
+            log: false,
+            min: None,
+            max: None,
+        }
+    }
+}
+
+fn default_num_bins() -> u32 {
+    256
+}
+
+// This struct is intended to be returned to the javascript
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram {
+    /// Number of values in each bin
+    pub counts: Vec<u32>,
+    /// The `num_bins + 1` edges of the bins
+    pub edges: Vec<f32>,
+    /// Number of values in the bins
+    pub num_values: u32,
+}
@@ -5,6 +5,7 @@
 pub mod blend;
 pub mod color;
 pub mod colormap;
+pub mod composite;
 pub mod coo_system;
 pub mod grid;
 pub mod hips;
@@ -14,6 +15,7 @@ pub mod cell;
 pub mod fov;
 pub mod image;
 pub mod angle_fmt;
+pub mod catalog;
 
 pub trait Abort {
     type Item;
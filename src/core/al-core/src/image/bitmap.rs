@@ -18,6 +18,11 @@ where
             format: std::marker::PhantomData,
         }
     }
+
+    /// Size of the decoded image in bytes
+    pub fn byte_size(&self) -> usize {
+        (self.image.width() * self.image.height()) as usize * F::NUM_CHANNELS
+    }
 }
 use wasm_bindgen::JsValue;
 use crate::texture::Texture2DArray;
@@ -26,10 +31,8 @@ where
     F: ImageFormat + Clone,
 {
     fn tex_sub_image_3d(&self, textures: &Texture2DArray, offset: &Vector3<i32>) -> Result<(), JsValue> {
-        textures[offset.z as usize]
+        textures
             .bind()
-            .tex_sub_image_2d_with_u32_and_u32_and_image_bitmap(offset.x, offset.y, &self.image);
-
-        Ok(())
+            .tex_sub_image_3d_with_image_bitmap(offset.x, offset.y, offset.z, &self.image)
     }
 }
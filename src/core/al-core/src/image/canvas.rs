@@ -15,6 +15,11 @@ where
             format: std::marker::PhantomData,
         }
     }
+
+    /// Size of the pixels of the canvas in bytes
+    pub fn byte_size(&self) -> usize {
+        (self.canvas.width() * self.canvas.height()) as usize * F::NUM_CHANNELS
+    }
 }
 
 use cgmath::Vector3;
@@ -33,14 +38,11 @@ where
         // An offset to write the image in the texture array
         offset: &Vector3<i32>,
     ) -> Result<(), JsValue> {
-        textures[offset.z as usize]
-            .bind()
-            .tex_sub_image_2d_with_u32_and_u32_and_html_canvas_element(
-                offset.x,
-                offset.y,
-                &self.canvas,
-            );
-
-        Ok(())
+        textures.bind().tex_sub_image_3d_with_html_canvas_element(
+            offset.x,
+            offset.y,
+            offset.z,
+            &self.canvas,
+        )
     }
 }
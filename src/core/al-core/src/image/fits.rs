@@ -129,56 +129,71 @@ impl Image for Fits<'_> {
         match &self.data {
             Data::U8(data) => {
                 let view = unsafe { R8UI::view(&data) };
-                textures[offset.z as usize]
+                textures
                     .bind()
-                    .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                    .tex_sub_image_3d_with_opt_array_buffer_view(
                         offset.x,
                         offset.y,
+                        offset.z,
                         self.size.x,
                         self.size.y,
                         Some(view.as_ref()),
-                    );
+                    )?;
             }
             Data::I16(data) => {
                 let view = unsafe { R16I::view(&data) };
-                textures[offset.z as usize]
+                textures
                     .bind()
-                    .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                    .tex_sub_image_3d_with_opt_array_buffer_view(
                         offset.x,
                         offset.y,
+                        offset.z,
                         self.size.x,
                         self.size.y,
                         Some(view.as_ref()),
-                    );
+                    )?;
             }
             Data::I32(data) => {
                 let view = unsafe { R32I::view(&data) };
-                textures[offset.z as usize]
+                textures
                     .bind()
-                    .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                    .tex_sub_image_3d_with_opt_array_buffer_view(
                         offset.x,
                         offset.y,
+                        offset.z,
                         self.size.x,
                         self.size.y,
                         Some(view.as_ref()),
-                    );
+                    )?;
             }
             Data::F32(data) => {
                 let view = unsafe { R32F::view(&data) };
-                textures[offset.z as usize]
+                textures
                     .bind()
-                    .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                    .tex_sub_image_3d_with_opt_array_buffer_view(
                         offset.x,
                         offset.y,
+                        offset.z,
                         self.size.x,
                         self.size.y,
                         Some(view.as_ref()),
-                    );
+                    )?;
             }
         }
 
         Ok(())
     }
+
+    fn sample_values(&self, step: usize) -> Option<Vec<f32>> {
+        let values = match &self.data {
+            Data::U8(data) => data.iter().step_by(step).map(|&v| v as f32).collect(),
+            Data::I16(data) => data.iter().step_by(step).map(|&v| v as f32).collect(),
+            Data::I32(data) => data.iter().step_by(step).map(|&v| v as f32).collect(),
+            Data::F32(data) => data.iter().step_by(step).copied().collect(),
+        };
+
+        Some(values)
+    }
 }
 
 use crate::image::format::ImageFormat;
@@ -309,6 +309,28 @@ pub enum ChannelType {
     R32I,
 }
 
+impl ChannelType {
+    /// Size in bytes of a pixel stored in a texture
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            ChannelType::RGBA32F => 16,
+            ChannelType::RGB32F => 12,
+            ChannelType::RGBA8U => 4,
+            ChannelType::RGB8U => 3,
+            ChannelType::R32F => 4,
+            // f64 values are stored as f32 in the textures
+            #[cfg(feature = "webgl2")]
+            ChannelType::R64F => 4,
+            #[cfg(feature = "webgl2")]
+            ChannelType::R8UI => 1,
+            #[cfg(feature = "webgl2")]
+            ChannelType::R16I => 2,
+            #[cfg(feature = "webgl2")]
+            ChannelType::R32I => 4,
+        }
+    }
+}
+
 pub const NUM_CHANNELS: usize = 9;
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
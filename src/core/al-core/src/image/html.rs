@@ -15,6 +15,11 @@ where
             format: std::marker::PhantomData,
         }
     }
+
+    /// Size of the decoded image in bytes
+    pub fn byte_size(&self) -> usize {
+        (self.image.natural_width() * self.image.natural_height()) as usize * F::NUM_CHANNELS
+    }
 }
 
 use cgmath::Vector3;
@@ -33,14 +38,11 @@ where
         // An offset to write the image in the texture array
         offset: &Vector3<i32>,
     ) -> Result<(), JsValue> {
-        textures[offset.z as usize]
-            .bind()
-            .tex_sub_image_2d_with_u32_and_u32_and_html_image_element(
-                offset.x,
-                offset.y,
-                &self.image,
-            );
-
-        Ok(())
+        textures.bind().tex_sub_image_3d_with_html_image_element(
+            offset.x,
+            offset.y,
+            offset.z,
+            &self.image,
+        )
     }
 }
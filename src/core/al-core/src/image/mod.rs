@@ -191,6 +191,27 @@ pub trait Image {
         // An offset to write the image in the texture array
         offset: &Vector3<i32>,
     ) -> Result<(), JsValue>;
+
+    /// Get the pixel values taken every `step` pixels
+    ///
+    /// Only single channel images have values, `None` is returned for color images
+    fn sample_values(&self, _step: usize) -> Option<Vec<f32>> {
+        None
+    }
+
+    /// Write the image into the texture array and get its pixel values taken every `step` pixels
+    ///
+    /// The images parsed to be written override it so that they are only parsed once
+    fn tex_sub_image_3d_and_sample(
+        &self,
+        textures: &Texture2DArray,
+        offset: &Vector3<i32>,
+        step: usize,
+    ) -> Result<Option<Vec<f32>>, JsValue> {
+        self.tex_sub_image_3d(textures, offset)?;
+
+        Ok(self.sample_values(step))
+    }
 }
 
 impl<'a, I> Image for &'a I
@@ -209,6 +230,19 @@ where
 
         Ok(())
     }
+
+    fn sample_values(&self, step: usize) -> Option<Vec<f32>> {
+        (**self).sample_values(step)
+    }
+
+    fn tex_sub_image_3d_and_sample(
+        &self,
+        textures: &Texture2DArray,
+        offset: &Vector3<i32>,
+        step: usize,
+    ) -> Result<Option<Vec<f32>>, JsValue> {
+        (**self).tex_sub_image_3d_and_sample(textures, offset, step)
+    }
 }
 
 use std::{io::Cursor, rc::Rc};
@@ -228,6 +262,19 @@ where
 
         Ok(())
     }
+
+    fn sample_values(&self, step: usize) -> Option<Vec<f32>> {
+        (**self).sample_values(step)
+    }
+
+    fn tex_sub_image_3d_and_sample(
+        &self,
+        textures: &Texture2DArray,
+        offset: &Vector3<i32>,
+        step: usize,
+    ) -> Result<Option<Vec<f32>>, JsValue> {
+        (**self).tex_sub_image_3d_and_sample(textures, offset, step)
+    }
 }
 use crate::Abort;
 
@@ -288,6 +335,26 @@ pub enum ImageType {
     RawR32f { image: ImageBuffer<R32F> },
 }
 
+impl ImageType {
+    /// An estimation of the memory size of the image in bytes
+    pub fn byte_size(&self) -> usize {
+        match self {
+            ImageType::FitsImage { raw_bytes } => raw_bytes.length() as usize,
+            ImageType::Canvas { canvas } => canvas.byte_size(),
+            ImageType::ImageRgba8u { image } => image.byte_size(),
+            ImageType::ImageRgb8u { image } => image.byte_size(),
+            ImageType::HTMLImageRgba8u { image } => image.byte_size(),
+            ImageType::HTMLImageRgb8u { image } => image.byte_size(),
+            ImageType::RawRgb8u { image } => image.byte_size(),
+            ImageType::RawRgba8u { image } => image.byte_size(),
+            ImageType::RawR32f { image } => image.byte_size(),
+            ImageType::RawR32i { image } => image.byte_size(),
+            ImageType::RawR16i { image } => image.byte_size(),
+            ImageType::RawR8ui { image } => image.byte_size(),
+        }
+    }
+}
+
 use cgmath::Vector3;
 impl Image for ImageType {
     fn tex_sub_image_3d(
@@ -324,4 +391,40 @@ impl Image for ImageType {
 
         Ok(())
     }
+
+    // The pixels of a FITS image are only parsed when it is written, see
+    // `tex_sub_image_3d_and_sample`
+    fn sample_values(&self, step: usize) -> Option<Vec<f32>> {
+        match self {
+            ImageType::RawR32f { image } => image.sample_values(step),
+            ImageType::RawR32i { image } => image.sample_values(step),
+            ImageType::RawR16i { image } => image.sample_values(step),
+            ImageType::RawR8ui { image } => image.sample_values(step),
+            _ => None,
+        }
+    }
+
+    fn tex_sub_image_3d_and_sample(
+        &self,
+        textures: &Texture2DArray,
+        offset: &Vector3<i32>,
+        step: usize,
+    ) -> Result<Option<Vec<f32>>, JsValue> {
+        match self {
+            ImageType::FitsImage {
+                raw_bytes: raw_bytes_buf,
+            } => {
+                let raw_bytes = raw_bytes_buf.to_vec();
+
+                let mut bytes_reader = Cursor::new(raw_bytes.as_slice());
+                let fits_img = Fits::from_byte_slice(&mut bytes_reader)?;
+                fits_img.tex_sub_image_3d_and_sample(textures, offset, step)
+            }
+            _ => {
+                self.tex_sub_image_3d(textures, offset)?;
+
+                Ok(self.sample_values(step))
+            }
+        }
+    }
 }
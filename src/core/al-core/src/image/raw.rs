@@ -125,6 +125,11 @@ where
     pub fn height(&self) -> i32 {
         self.size.y
     }
+
+    /// Size of the pixels in bytes
+    pub fn byte_size(&self) -> usize {
+        self.data.len() * std::mem::size_of::<<<T as ImageFormat>::P as Pixel>::Item>()
+    }
 }
 
 use crate::image::format::{R16I, R32F, R32I, R8UI, RGB8U, RGBA8U};
@@ -153,17 +158,29 @@ where
     ) -> Result<(), JsValue> {
         let js_array =
             <<<I as ImageFormat>::P as Pixel>::Container as ArrayBuffer>::new(&self.data);
-        textures[offset.z as usize]
-            .bind()
-            .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
-                offset.x,
-                offset.y,
-                self.size.x,
-                self.size.y,
-                Some(js_array.as_ref()),
-            );
+        textures.bind().tex_sub_image_3d_with_opt_array_buffer_view(
+            offset.x,
+            offset.y,
+            offset.z,
+            self.size.x,
+            self.size.y,
+            Some(js_array.as_ref()),
+        )
+    }
 
-        Ok(())
+    fn sample_values(&self, step: usize) -> Option<Vec<f32>> {
+        if I::NUM_CHANNELS == 1 {
+            let values = self
+                .data
+                .iter()
+                .step_by(step)
+                .map(|&v| crate::convert::Cast::<f32>::cast(v))
+                .collect();
+
+            Some(values)
+        } else {
+            None
+        }
     }
 
     // The size of the image
@@ -250,6 +250,22 @@ impl UniformType for Texture2D {
     }
 }
 
+use super::texture::Texture2DArray;
+impl UniformType for Texture2DArray {
+    fn uniform(gl: &WebGlContext, location: Option<&WebGlUniformLocation>, tex: &Self) {
+        unsafe {
+            let _ = tex
+                // 1. Active the texture unit of the texture array
+                .active_texture(CUR_IDX_TEX_UNIT)
+                // 2. Bind the texture array to that texture unit
+                .bind();
+
+            gl.uniform1i(location, CUR_IDX_TEX_UNIT as i32);
+            CUR_IDX_TEX_UNIT += 1;
+        };
+    }
+}
+
 use al_api::color::ColorRGB;
 impl UniformType for ColorRGB {
     fn uniform(gl: &WebGlContext, location: Option<&WebGlUniformLocation>, value: &Self) {
@@ -277,7 +293,9 @@ impl<'a> UniformType for &'a ColorRGBA {
 use al_api::hips::TransferFunction;
 impl SendUniforms for TransferFunction {
     fn attach_uniforms<'a>(&self, shader: &'a ShaderBound<'a>) -> &'a ShaderBound<'a> {
-        shader.attach_uniform("H", self);
+        shader
+            .attach_uniform("H", self)
+            .attach_uniform("stretch_param", &self.param());
 
         shader
     }
@@ -285,7 +303,7 @@ impl SendUniforms for TransferFunction {
 
 impl UniformType for TransferFunction {
     fn uniform(gl: &WebGlContext, location: Option<&WebGlUniformLocation>, value: &Self) {
-        gl.uniform1i(location, *value as i32);
+        gl.uniform1i(location, value.id());
     }
 }
 
@@ -307,7 +325,7 @@ impl SendUniforms for HiPSColor {
         let reversed = self.reversed as u8 as f32;
 
         shader
-            .attach_uniform("H", &self.stretch)
+            .attach_uniforms_from(&self.stretch)
             .attach_uniform("min_value", &self.min_cut.unwrap_or(0.0))
             .attach_uniform("max_value", &self.max_cut.unwrap_or(1.0))
             .attach_uniform("k_gamma", &self.k_gamma)
@@ -331,7 +349,7 @@ impl SendUniformsWithParams<Colormaps> for HiPSColor {
         let cmap = cmaps.get(&self.cmap_name.as_ref());
         shader
             .attach_uniforms_with_params_from(cmap, cmaps)
-            .attach_uniform("H", &self.stretch)
+            .attach_uniforms_from(&self.stretch)
             .attach_uniform("min_value", &self.min_cut.unwrap_or(0.0))
             .attach_uniform("max_value", &self.max_cut.unwrap_or(1.0))
             .attach_uniform("k_gamma", &self.k_gamma)
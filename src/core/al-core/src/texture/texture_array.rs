@@ -1,20 +1,35 @@
 use crate::image::format::ImageFormat;
 
 use crate::webgl_ctx::WebGlContext;
-pub struct Texture2DArray {
-    pub textures: Vec<Texture2D>,
+use crate::webgl_ctx::WebGlRenderingCtx;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlCanvasElement;
+use web_sys::HtmlImageElement;
+use web_sys::WebGlTexture;
+
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+struct Texture2DArrayMeta {
+    pub format: u32,
+    pub internal_format: i32,
+    pub type_: u32,
+
+    pub width: i32,
+    pub height: i32,
+    pub num_slices: i32,
 }
 
-use std::ops::Index;
-impl Index<usize> for Texture2DArray {
-    type Output = Texture2D;
+/// A GPU array of 2D textures of the same size and format
+///
+/// The textures (i.e. the slices) are sampled in the shaders with a `sampler2DArray`
+pub struct Texture2DArray {
+    texture: Option<WebGlTexture>,
 
-    fn index(&self, idx: usize) -> &Self::Output {
-        &self.textures[idx]
-    }
+    gl: WebGlContext,
+
+    metadata: Texture2DArrayMeta,
 }
-use super::Texture2D;
-use wasm_bindgen::prelude::*;
+
 impl Texture2DArray {
     pub fn create_empty<F: ImageFormat>(
         gl: &WebGlContext,
@@ -26,28 +41,242 @@ impl Texture2DArray {
         num_slices: i32,
         tex_params: &'static [(u32, u32)],
     ) -> Result<Texture2DArray, JsValue> {
-        let textures: Result<Vec<_>, _> = (0..num_slices)
-            .map(|_| Texture2D::create_empty_with_format::<F>(gl, width, height, tex_params))
-            .collect();
+        let texture = gl.create_texture();
+
+        gl.bind_texture(WebGlRenderingCtx::TEXTURE_2D_ARRAY, texture.as_ref());
+
+        for (pname, param) in tex_params.iter() {
+            gl.tex_parameteri(WebGlRenderingCtx::TEXTURE_2D_ARRAY, *pname, *param as i32);
+        }
+
+        gl.tex_image_3d_with_opt_u8_array(
+            WebGlRenderingCtx::TEXTURE_2D_ARRAY,
+            0,
+            F::INTERNAL_FORMAT,
+            width,
+            height,
+            num_slices,
+            0,
+            F::FORMAT,
+            F::TYPE,
+            None,
+        )?;
+
+        let gl = gl.clone();
+        let metadata = Texture2DArrayMeta {
+            width,
+            height,
+            num_slices,
+            internal_format: F::INTERNAL_FORMAT,
+            format: F::FORMAT,
+            type_: F::TYPE,
+        };
 
         Ok(Texture2DArray {
-            textures: textures?,
+            texture,
+
+            gl,
+
+            metadata,
         })
     }
+
+    pub fn num_slices(&self) -> i32 {
+        self.metadata.num_slices
+    }
+
+    /// Copy the slices of `src` into the first slices of the array
+    ///
+    /// Both arrays must have the same format and the size of their slices.
+    /// The slices are copied through a framebuffer, the format must therefore be renderable
+    pub fn copy_slices_from(&self, src: &Texture2DArray) -> Result<(), JsValue> {
+        let num_slices = self.metadata.num_slices.min(src.metadata.num_slices);
+        let width = self.metadata.width.min(src.metadata.width);
+        let height = self.metadata.height.min(src.metadata.height);
+
+        let reader = self.gl.create_framebuffer();
+        self.gl
+            .bind_framebuffer(WebGlRenderingCtx::READ_FRAMEBUFFER, reader.as_ref());
+        self.gl
+            .bind_texture(WebGlRenderingCtx::TEXTURE_2D_ARRAY, self.texture.as_ref());
+
+        let mut result = Ok(());
+        for slice in 0..num_slices {
+            // Read from the slice of the source array
+            self.gl.framebuffer_texture_layer(
+                WebGlRenderingCtx::READ_FRAMEBUFFER,
+                WebGlRenderingCtx::COLOR_ATTACHMENT0,
+                src.texture.as_ref(),
+                0,
+                slice,
+            );
+
+            let status = self
+                .gl
+                .check_framebuffer_status(WebGlRenderingCtx::READ_FRAMEBUFFER);
+            if status != WebGlRenderingCtx::FRAMEBUFFER_COMPLETE {
+                result = Err(JsValue::from_str("incomplete framebuffer"));
+                break;
+            }
+
+            self.gl.copy_tex_sub_image_3d(
+                WebGlRenderingCtx::TEXTURE_2D_ARRAY,
+                0,
+                0,
+                0,
+                slice,
+                0,
+                0,
+                width,
+                height,
+            );
+        }
+
+        // Unbind the framebuffer
+        self.gl
+            .bind_framebuffer(WebGlRenderingCtx::READ_FRAMEBUFFER, None);
+        // Delete the framebuffer
+        self.gl.delete_framebuffer(reader.as_ref());
+
+        result
+    }
+
+    pub fn active_texture(&self, idx_tex_unit: u8) -> &Self {
+        self.gl
+            .active_texture(WebGlRenderingCtx::TEXTURE0 + idx_tex_unit as u32);
+        self
+    }
+
+    pub fn bind(&self) -> Texture2DArrayBound {
+        self.gl
+            .bind_texture(WebGlRenderingCtx::TEXTURE_2D_ARRAY, self.texture.as_ref());
+
+        Texture2DArrayBound {
+            texture_array: self,
+        }
+    }
 }
 
-const TEX_UNIFORMS_NAME: &[&str] = &["tex1", "tex2", "tex3", "tex4", "tex5"];
+impl Drop for Texture2DArray {
+    fn drop(&mut self) {
+        self.gl.delete_texture(self.texture.as_ref());
+    }
+}
+
+pub struct Texture2DArrayBound<'a> {
+    texture_array: &'a Texture2DArray,
+}
+
+impl<'a> Texture2DArrayBound<'a> {
+    pub fn tex_sub_image_3d_with_html_image_element(
+        &self,
+        dx: i32,
+        dy: i32,
+        dz: i32,
+        image: &HtmlImageElement,
+    ) -> Result<(), JsValue> {
+        let metadata = &self.texture_array.metadata;
+
+        self.texture_array
+            .gl
+            .tex_sub_image_3d_with_html_image_element(
+                WebGlRenderingCtx::TEXTURE_2D_ARRAY,
+                0,
+                dx,
+                dy,
+                dz,
+                image.width() as i32,
+                image.height() as i32,
+                1,
+                metadata.format,
+                metadata.type_,
+                image,
+            )
+    }
+
+    pub fn tex_sub_image_3d_with_html_canvas_element(
+        &self,
+        dx: i32,
+        dy: i32,
+        dz: i32,
+        canvas: &HtmlCanvasElement,
+    ) -> Result<(), JsValue> {
+        let metadata = &self.texture_array.metadata;
+
+        self.texture_array
+            .gl
+            .tex_sub_image_3d_with_html_canvas_element(
+                WebGlRenderingCtx::TEXTURE_2D_ARRAY,
+                0,
+                dx,
+                dy,
+                dz,
+                canvas.width() as i32,
+                canvas.height() as i32,
+                1,
+                metadata.format,
+                metadata.type_,
+                canvas,
+            )
+    }
+
+    pub fn tex_sub_image_3d_with_image_bitmap(
+        &self,
+        dx: i32,
+        dy: i32,
+        dz: i32,
+        image: &web_sys::ImageBitmap,
+    ) -> Result<(), JsValue> {
+        let metadata = &self.texture_array.metadata;
+
+        self.texture_array.gl.tex_sub_image_3d_with_image_bitmap(
+            WebGlRenderingCtx::TEXTURE_2D_ARRAY,
+            0,
+            dx,
+            dy,
+            dz,
+            image.width() as i32,
+            image.height() as i32,
+            1,
+            metadata.format,
+            metadata.type_,
+            image,
+        )
+    }
+
+    pub fn tex_sub_image_3d_with_opt_array_buffer_view(
+        &self,
+        dx: i32,
+        dy: i32,
+        dz: i32,
+        width: i32,  // Width of the image
+        height: i32, // Height of the image
+        image: Option<&js_sys::Object>,
+    ) -> Result<(), JsValue> {
+        let metadata = &self.texture_array.metadata;
+
+        self.texture_array
+            .gl
+            .tex_sub_image_3d_with_opt_array_buffer_view(
+                WebGlRenderingCtx::TEXTURE_2D_ARRAY,
+                0,
+                dx,
+                dy,
+                dz,
+                width,
+                height,
+                1,
+                metadata.format,
+                metadata.type_,
+                image,
+            )
+    }
+}
 
 use crate::shader::{SendUniforms, ShaderBound};
 impl SendUniforms for Texture2DArray {
     fn attach_uniforms<'a>(&self, shader: &'a ShaderBound<'a>) -> &'a ShaderBound<'a> {
-        let num_tex = self.textures.len();
-
-        for (idx, tex) in self.textures.iter().enumerate() {
-            let loc = TEX_UNIFORMS_NAME[idx];
-            shader.attach_uniform(loc, tex);
-        }
-        shader.attach_uniform("num_tex", &(num_tex as i32));
+        shader.attach_uniform("tex", self);
 
         shader
     }
@@ -1,11 +1,14 @@
+use crate::renderable::image::cube::CubeAxis;
 use crate::renderable::ImageLayer;
 use crate::tile_fetcher::HiPSLocalFiles;
 use crate::{
     //async_task::{BuildCatalogIndex, ParseTableTask, TaskExecutor, TaskResult, TaskType},
     camera::CameraViewPort,
-    downloader::Downloader,
+    downloader::{mirror::MirrorState, Downloader},
     healpix::coverage::HEALPixCoverage,
+    healpix::st_coverage::{SpaceTimeCoverage, TimeCoverage},
     inertia::Inertia,
+    lru_cache::CacheStats,
     math::{
         self,
         angle::{Angle, ArcDeg},
@@ -13,7 +16,10 @@ use crate::{
     },
     renderable::grid::ProjetedGrid,
     renderable::Layers,
-    renderable::{catalog::Manager, moc::MOCRenderer},
+    renderable::{
+        catalog::{Footprints, Manager, Markers},
+        moc::MOCRenderer,
+    },
     shader::ShaderManager,
     tile_fetcher::TileFetcherQueue,
     time::DeltaTime,
@@ -28,9 +34,10 @@ use al_core::WebGlContext;
 use super::coosys;
 use crate::Abort;
 use al_api::{
+    composite::RGBCompositeCfg,
     coo_system::CooSystem,
     grid::GridCfg,
-    hips::{HiPSCfg, ImageMetadata},
+    hips::{HiPSCfg, ImageMetadata, TransferFunction},
 };
 use cgmath::Vector4;
 use fitsrs::{fits::AsyncFits, hdu::extension::AsyncXtensionHDU};
@@ -40,12 +47,13 @@ use web_sys::{HtmlElement, WebGl2RenderingContext};
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 use crate::renderable::final_pass::RenderPass;
 use al_core::FrameBufferObject;
 
-use al_api::image::ImageParams;
+use al_api::image::{CutAlgorithm, Histogram, HistogramParams, ImageParams};
 
 pub struct App {
     pub gl: WebGlContext,
@@ -66,6 +74,8 @@ pub struct App {
     grid: ProjetedGrid,
     // The moc renderable
     moc: MOCRenderer,
+    // The Space-Time MOCs whose spatial coverage is displayed as a MOC layer
+    stmocs: HashMap<String, SpaceTimeCoverage>,
     // Catalog manager
     manager: Manager,
 
@@ -191,6 +201,7 @@ impl App {
         let request_for_new_tiles = true;
 
         let moc = MOCRenderer::new(&gl)?;
+        let stmocs = HashMap::new();
         gl.clear_color(0.15, 0.15, 0.15, 1.0);
 
         let (img_send, img_recv) = async_channel::unbounded::<ImageLayer>();
@@ -223,6 +234,7 @@ impl App {
             grid,
             // MOCs renderable
             moc,
+            stmocs,
             // The catalog renderable
             manager,
             //exec,
@@ -261,6 +273,7 @@ impl App {
 
     fn look_for_new_tiles(&mut self) -> Result<(), JsValue> {
         // Move the views of the different active surveys
+        // The queued tiles are dropped, the queue being filled again with the tiles of the new view
         self.tile_fetcher.clear();
         // Loop over the surveys
         for survey in self.layers.values_mut_hips() {
@@ -283,16 +296,41 @@ impl App {
             let creator_did = survey.get_config().get_creator_did().to_string();
             let root_url = survey.get_config().get_root_url().to_string();
             let format = survey.get_config().get_format();
+            let ext = survey.get_config().get_tile_ext();
+            let frame = survey.get_config().get_cube_frame();
+            let options = survey.get_config().get_fetch_options().clone();
+            let hips_frame = survey.get_config().get_frame();
+            // The tiles near the center of the view are fetched first
+            let view_center = crate::coosys::apply_coo_system(
+                self.camera.get_coo_system(),
+                hips_frame,
+                self.camera.get_center(),
+            );
+
+            // Abort the requests of the tiles that left the view
+            let view_cov = self.camera.get_cov(hips_frame);
+            self.downloader
+                .borrow_mut()
+                .abort_tile_requests(&creator_did, |cell| {
+                    // The root tiles are kept as they are the base of the HiPS
+                    cell.is_root() || view_cov.intersects_cell(cell)
+                });
 
             if let Some(tiles_iter) = survey.look_for_new_tiles(&mut self.camera, &self.projection)
             {
                 for tile_cell in tiles_iter.into_iter() {
-                    self.tile_fetcher.append(query::Tile::new(
-                        &tile_cell,
-                        creator_did.clone(),
-                        root_url.clone(),
-                        format,
-                    ));
+                    self.tile_fetcher.append(
+                        query::Tile::new(
+                            &tile_cell,
+                            creator_did.clone(),
+                            root_url.clone(),
+                            format,
+                            &ext,
+                            frame,
+                            &options,
+                        ),
+                        &view_center,
+                    );
 
                     // check if we are starting aladin lite or not.
                     // If so we want to retrieve only the tiles in the view and access them
@@ -308,12 +346,18 @@ impl App {
             // Request for ancestor
             for ancestor in ancestors {
                 if !survey.update_priority_tile(&ancestor) {
-                    self.tile_fetcher.append(query::Tile::new(
-                        &ancestor,
-                        creator_did.clone(),
-                        root_url.clone(),
-                        format,
-                    ));
+                    self.tile_fetcher.append(
+                        query::Tile::new(
+                            &ancestor,
+                            creator_did.clone(),
+                            root_url.clone(),
+                            format,
+                            &ext,
+                            frame,
+                            &options,
+                        ),
+                        &view_center,
+                    );
                 }
             }
         }
@@ -505,12 +549,89 @@ impl App {
         self.moc
             .remove(cfg, &mut self.camera, &self.projection)
             .ok_or_else(|| JsValue::from_str("MOC not found"))?;
+        self.stmocs.remove(cfg.get_uuid());
+
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    /// Add a Space-Time MOC, its spatial coverage over the whole time range is displayed
+    pub(crate) fn add_stmoc(
+        &mut self,
+        cfg: al_api::moc::MOC,
+        stmoc: SpaceTimeCoverage,
+    ) -> Result<(), JsValue> {
+        let moc = stmoc.full_spatial_coverage();
+        self.stmocs.insert(cfg.get_uuid().clone(), stmoc);
+
+        self.add_moc(cfg, moc)
+    }
 
+    pub(crate) fn get_stmoc(&self, cfg: &al_api::moc::MOC) -> Option<&SpaceTimeCoverage> {
+        self.stmocs.get(cfg.get_uuid())
+    }
+
+    /// Display the spatial coverage of a Space-Time MOC observed during a time coverage
+    pub(crate) fn set_stmoc_time_coverage(
+        &mut self,
+        cfg: &al_api::moc::MOC,
+        time: &TimeCoverage,
+    ) -> Result<(), JsValue> {
+        let moc = self
+            .get_stmoc(cfg)
+            .ok_or_else(|| JsValue::from_str("Space-Time MOC not found"))?
+            .spatial_coverage(time);
+        self.moc
+            .set_hpx_coverage(cfg, moc)
+            .ok_or_else(|| JsValue::from_str("MOC not found"))?;
         self.request_redraw = true;
 
         Ok(())
     }
 
+    /// Compute the coverage of the footprint of the images of a layer
+    pub(crate) fn get_image_coverage(
+        &self,
+        layer: &str,
+        depth: u8,
+    ) -> Result<HEALPixCoverage, JsValue> {
+        let images = self
+            .layers
+            .get_image_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("Image layer not found"))?;
+
+        // A layer can contain several images (e.g. multi-extension FITS)
+        images
+            .iter()
+            .try_fold(HEALPixCoverage::empty(depth), |moc, image| {
+                Ok(moc.union(&image.get_coverage(depth)?))
+            })
+    }
+
+    /// Compute the coverage of the sources of a catalog
+    pub(crate) fn get_catalog_coverage(
+        &self,
+        name: &str,
+        depth: u8,
+    ) -> Result<HEALPixCoverage, JsValue> {
+        let moc = self.manager.get_coverage(name, depth)?;
+
+        Ok(moc)
+    }
+
+    /// Compute the ICRS coverage of the current field of view
+    pub(crate) fn get_fov_coverage(&self, depth: u8) -> HEALPixCoverage {
+        crate::camera::build_fov_coverage(
+            depth,
+            self.camera.get_field_of_view(),
+            self.camera.get_center(),
+            self.camera.get_coo_system(),
+            CooSystem::ICRS,
+            &self.projection,
+        )
+    }
+
     pub(crate) fn set_moc_cfg(&mut self, cfg: al_api::moc::MOC) -> Result<(), JsValue> {
         self.moc
             .set_cfg(cfg, &mut self.camera, &self.projection, &mut self.shaders)
@@ -585,9 +706,14 @@ impl App {
             .downloader
             .get_resolved_tiles(/*&available_tiles, */&mut self.surveys);*/
             let rscs_received = self.downloader.borrow_mut().get_received_resources();
+            // The tiles of a HiPS whose mirror is failing are requested from another one
+            self.tile_fetcher
+                .switch_failing_mirrors(self.layers.values_mut_hips(), &self.downloader);
 
             let _num_tile_handled = 0;
             let _tile_copied = false;
+            // The HiPS having received tiles, for their histogram equalization
+            let mut hips_with_new_tiles = HashSet::new();
             for rsc in rscs_received {
                 match rsc {
                     Resource::Tile(tile) => {
@@ -623,6 +749,7 @@ impl App {
                                         cell,
                                         image,
                                         time_req,
+                                        frame,
                                         ..
                                     } = tile;
 
@@ -688,15 +815,33 @@ impl App {
                                     };
                                     //}
 
-                                    match &*image.lock().unwrap_abort() {
-                                        Some(img) => {
-                                            survey.add_tile(&cell, img, time_req)?;
-                                            self.request_redraw = true;
+                                    if let Some(frame) = frame {
+                                        // The tile of a cube frame is kept even if it is not
+                                        // the current frame
+                                        survey.add_cube_tile(&cell, image, frame, time_req)?;
+                                        hips_with_new_tiles.insert(
+                                            survey.get_config().get_creator_did().to_string(),
+                                        );
+                                        self.request_redraw = true;
 
-                                            self.time_start_blending = Time::now();
-                                        }
-                                        None => (),
-                                    };
+                                        self.time_start_blending = Time::now();
+                                    } else {
+                                        match &*image.lock().unwrap_abort() {
+                                            Some(img) => {
+                                                survey.add_tile(&cell, img, time_req)?;
+                                                hips_with_new_tiles.insert(
+                                                    survey
+                                                        .get_config()
+                                                        .get_creator_did()
+                                                        .to_string(),
+                                                );
+                                                self.request_redraw = true;
+
+                                                self.time_start_blending = Time::now();
+                                            }
+                                            None => (),
+                                        };
+                                    }
                                 }
                             }
                         }
@@ -708,6 +853,11 @@ impl App {
                         let hips_cdid = allsky.get_hips_cdid();
 
                         if let Some(survey) = self.layers.get_mut_hips_from_cdid(hips_cdid) {
+                            if allsky.frame != survey.get_config().get_cube_frame() {
+                                // The allsky of a cube frame that is not displayed anymore
+                                continue;
+                            }
+
                             let is_missing = allsky.missing();
                             if is_missing {
                                 // The allsky image is missing so we donwload all the tiles contained into
@@ -720,6 +870,9 @@ impl App {
                                             cfg.get_creator_did().to_string(),
                                             cfg.get_root_url().to_string(),
                                             cfg.get_format(),
+                                            &cfg.get_tile_ext(),
+                                            allsky.frame,
+                                            cfg.get_fetch_options(),
                                         );
                                         self.tile_fetcher.append_base_tile(query);
                                     }
@@ -762,6 +915,20 @@ impl App {
                 }
             }
 
+            // The distribution of the values in the view changes with the new tiles
+            for layer in self.layers.get_histeq_layers() {
+                let has_new_tiles = self
+                    .layers
+                    .get_hips_from_layer(&layer)
+                    .map_or(false, |hips| {
+                        hips_with_new_tiles.contains(hips.get_config().get_creator_did())
+                    });
+
+                if has_new_tiles {
+                    self.update_layer_cdf(&layer)?;
+                }
+            }
+
             // Tiles are fetched if:
             let fetch_tiles = self.inertia.is_none() &&
             // * the user is not zooming
@@ -781,6 +948,10 @@ impl App {
 
         // The update from the camera
         //self.layers.update(&mut self.camera, &self.projection);
+        if has_camera_moved {
+            // Send the sources in the new view to the GPU
+            self.manager.update(&self.camera);
+        }
 
         if self.request_for_new_tiles
         //&& Time::now() - self.last_time_request_for_new_tiles > DeltaTime::from(200.0)
@@ -798,12 +969,14 @@ impl App {
         // Check for async retrieval
         if let Ok(img) = self.img_recv.try_recv() {
             let params = img.get_params();
+            let layer = img.layer.clone();
             self.layers.add_image(
                 img,
                 &mut self.camera,
                 &self.projection,
                 &mut self.tile_fetcher,
             )?;
+            self.update_layer_cdf(&layer)?;
             self.request_redraw = true;
 
             // Send the ack to the js promise so that she finished
@@ -825,8 +998,15 @@ impl App {
         if let Some(lonlat) = self.screen_to_world(pos) {
             if let Some(survey) = self.layers.get_hips_from_layer(layer) {
                 survey.read_pixel(&lonlat, &self.camera)
-            } else if let Some(_image) = self.layers.get_image_from_layer(layer) {
-                Err(JsValue::from_str("TODO: read pixel value"))
+            } else if let Some(images) = self.layers.get_image_from_layer(layer) {
+                // A layer can contain several images (e.g. multi-extension FITS)
+                for image in images {
+                    if let Some(pixel) = image.read_pixel(&lonlat, &self.camera)? {
+                        return Ok(pixel);
+                    }
+                }
+
+                Err(JsValue::from_str("position is out of the image"))
             } else {
                 Err(JsValue::from_str("Survey not found"))
             }
@@ -835,6 +1015,235 @@ impl App {
         }
     }
 
+    /// Display a slice of the data cubes of an image layer
+    pub(crate) fn set_image_slice(&mut self, layer: &str, slice: u64) -> Result<(), JsValue> {
+        let images = self
+            .layers
+            .get_mut_image_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("Image layer not found"))?;
+
+        let mut found_cube = false;
+        for image in images.iter_mut().filter(|image| image.get_cube().is_some()) {
+            image.set_slice(slice)?;
+            found_cube = true;
+        }
+
+        if !found_cube {
+            return Err(JsValue::from_str(
+                "The layer does not contain any data cube",
+            ));
+        }
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
+    /// Recompute the cuts of the images of a layer with a given algorithm
+    ///
+    /// Returns the cuts of the first image of the layer
+    pub(crate) fn set_image_cut_algorithm(
+        &mut self,
+        layer: &str,
+        cut_algorithm: CutAlgorithm,
+    ) -> Result<Range<f32>, JsValue> {
+        let images = self
+            .layers
+            .get_mut_image_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("Image layer not found"))?;
+
+        let mut cuts = None;
+        for image in images.iter_mut() {
+            let image_cuts = image.set_cut_algorithm(cut_algorithm).clone();
+            cuts.get_or_insert(image_cuts);
+        }
+
+        cuts.ok_or_else(|| JsValue::from_str("The layer does not contain any image"))
+    }
+
+    /// Set the cuts of a FITS HiPS layer from the pixel values of its tiles in the view
+    pub(crate) fn set_hips_cuts_from_view(
+        &mut self,
+        layer: &str,
+        cut_algorithm: CutAlgorithm,
+    ) -> Result<Range<f32>, JsValue> {
+        let hips = self
+            .layers
+            .get_hips_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("HiPS layer not found"))?;
+        if !hips.get_config().tex_storing_fits {
+            return Err(JsValue::from_str(
+                "The HiPS layer does not store FITS tiles",
+            ));
+        }
+
+        let mut values = hips.get_visible_pixel_values(&self.camera);
+        if values.is_empty() {
+            return Err(JsValue::from_str(
+                "No tile of the view has been received yet",
+            ));
+        }
+        let cuts = crate::renderable::image::cuts::compute(&cut_algorithm, &mut values);
+
+        let mut meta = self.layers.get_layer_cfg(layer)?;
+        meta.color.min_cut = Some(cuts.start);
+        meta.color.max_cut = Some(cuts.end);
+        self.layers
+            .set_layer_cfg(layer.to_string(), meta, &mut self.camera, &self.projection)?;
+
+        self.request_redraw = true;
+
+        Ok(cuts)
+    }
+
+    /// Compute the histogram of the pixel values of a FITS layer
+    ///
+    /// The values of an image layer are those sampled when it has been loaded, those of
+    /// a HiPS layer are sampled from its tiles in the view
+    pub(crate) fn get_layer_histogram(
+        &self,
+        layer: &str,
+        params: &HistogramParams,
+    ) -> Result<Histogram, JsValue> {
+        let values = self.get_layer_values(layer)?;
+        let histogram = crate::renderable::image::histogram::compute(&values, params);
+
+        Ok(histogram)
+    }
+
+    // The pixel values of an image layer or the ones in the view of a FITS HiPS layer
+    fn get_layer_values(&self, layer: &str) -> Result<Vec<f32>, JsValue> {
+        if let Some(images) = self.layers.get_image_from_layer(layer) {
+            Ok(images
+                .iter()
+                .flat_map(|image| image.get_sampled_values())
+                .collect::<Vec<_>>())
+        } else if let Some(hips) = self.layers.get_hips_from_layer(layer) {
+            if !hips.get_config().tex_storing_fits {
+                return Err(JsValue::from_str(
+                    "The HiPS layer does not store FITS tiles",
+                ));
+            }
+
+            Ok(hips.get_visible_pixel_values(&self.camera))
+        } else {
+            Err(JsValue::from_str("Layer not found"))
+        }
+    }
+
+    /// Update the cumulative distribution of the values of a layer
+    ///
+    /// It is only kept for the layers stretched with the histogram equalization.
+    /// The layers without values (e.g. a HiPS of JPEG tiles) keep a linear stretch.
+    fn update_layer_cdf(&mut self, layer: &str) -> Result<(), JsValue> {
+        use crate::renderable::image::histogram;
+
+        let color = self.layers.get_layer_cfg(layer)?.color;
+        if color.stretch == TransferFunction::HistEq {
+            let values = self.get_layer_values(layer).unwrap_or_default();
+            let cdf = histogram::cdf(
+                &values,
+                color.min_cut.unwrap_or(0.0),
+                color.max_cut.unwrap_or(1.0),
+                histogram::CDF_NUM_SAMPLES,
+            );
+
+            self.layers.set_layer_cdf(layer, Some(&cdf))
+        } else {
+            self.layers.set_layer_cdf(layer, None)
+        }
+    }
+
+    pub(crate) fn get_cache_stats(&self) -> CacheStats {
+        self.downloader.borrow().get_cache_stats()
+    }
+
+    pub(crate) fn get_hips_mirrors(&self, layer: &str) -> Result<Vec<MirrorState>, JsValue> {
+        let cfg = self
+            .layers
+            .get_hips_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("HiPS layer not found"))?
+            .get_config();
+
+        let downloader = self.downloader.borrow();
+        let mirrors = cfg
+            .get_mirrors()
+            .iter()
+            .map(|url| MirrorState {
+                url: url.clone(),
+                current: url == cfg.get_root_url(),
+                stats: downloader.get_mirror_stats(url),
+            })
+            .collect();
+
+        Ok(mirrors)
+    }
+
+    pub(crate) fn set_cache_budget(&mut self, budget: usize) {
+        self.downloader.borrow_mut().set_cache_budget(budget);
+    }
+
+    pub(crate) fn set_gpu_memory_budget(&mut self, budget: usize) -> Result<(), JsValue> {
+        self.layers.set_gpu_memory_budget(budget);
+
+        self.resize_hips_textures()
+    }
+
+    // Size the textures of the HiPS from the view and the GPU memory budget.
+    // The HiPS whose textures have been emptied are loaded again
+    fn resize_hips_textures(&mut self) -> Result<(), JsValue> {
+        let emptied = self.layers.resize_hips_textures(&self.camera)?;
+
+        for cdid in &emptied {
+            if let Some(hips) = self.layers.get_hips_from_cdid(cdid) {
+                self.tile_fetcher
+                    .launch_starting_hips_requests(hips, self.downloader.clone());
+            }
+        }
+
+        if !emptied.is_empty() {
+            self.request_for_new_tiles = true;
+            self.request_redraw = true;
+        }
+
+        Ok(())
+    }
+
+    /// Get the third axis of the data cube of an image layer
+    ///
+    /// Returns `None` if the layer does not contain any data cube. Otherwise the returned object contains:
+    /// * `numSlices` - The number of slices
+    /// * `slice` - The index of the displayed slice
+    /// * `ctype`, `cunit` - The type and unit of the axis, `null` if not given in the header
+    /// * `values` - The world coordinate of each slice
+    pub(crate) fn get_image_cube_axis(&self, layer: &str) -> Result<Option<JsValue>, JsValue> {
+        let images = self
+            .layers
+            .get_image_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("Image layer not found"))?;
+
+        if let Some(cube) = images.iter().find_map(|image| image.get_cube()) {
+            #[derive(serde::Serialize)]
+            #[serde(rename_all = "camelCase")]
+            struct CubeInfo<'a> {
+                #[serde(flatten)]
+                axis: &'a CubeAxis,
+                slice: u64,
+                values: Vec<f64>,
+            }
+
+            let axis = cube.get_axis();
+            let info = CubeInfo {
+                axis,
+                slice: cube.get_slice(),
+                values: (0..axis.num_slices).map(|k| axis.value(k)).collect(),
+            };
+
+            Ok(Some(serde_wasm_bindgen::to_value(&info)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub(crate) fn draw_grid_labels(&mut self) -> Result<(), JsValue> {
         self.grid.draw_labels()
     }
@@ -917,9 +1326,14 @@ impl App {
             )?;
 
             // Draw the catalog
-            //let fbo_view = &self.fbo_view;
-            //catalogs.draw(&gl, shaders, camera, colormaps, fbo_view)?;
-            //catalogs.draw(&gl, shaders, camera, colormaps, None, self.projection)?;
+            self.manager.draw(
+                &mut self.shaders,
+                &self.camera,
+                &self.colormaps,
+                None,
+                &self.projection,
+            )?;
+
             self.moc.draw(
                 &mut self.camera,
                 &self.projection,
@@ -955,6 +1369,11 @@ impl App {
     }
 
     pub(crate) fn remove_layer(&mut self, layer: &str) -> Result<(), JsValue> {
+        let hips_cdid = self
+            .layers
+            .get_hips_from_layer(layer)
+            .map(|hips| hips.get_config().get_creator_did().to_string());
+
         self.layers.remove_layer(
             layer,
             &mut self.camera,
@@ -962,6 +1381,23 @@ impl App {
             &mut self.tile_fetcher,
         )?;
 
+        // The cached resources of a HiPS not used anymore can be evicted
+        if let Some(hips_cdid) = hips_cdid {
+            let hips_still_used = self
+                .layers
+                .values_hips()
+                .any(|hips| hips.get_config().get_creator_did() == &hips_cdid);
+
+            if !hips_still_used {
+                self.downloader
+                    .borrow_mut()
+                    .release_hips_resources(&hips_cdid);
+
+                // The GPU memory is shared between the remaining HiPS
+                self.resize_hips_textures()?;
+            }
+        }
+
         self.request_redraw = true;
 
         Ok(())
@@ -1005,6 +1441,9 @@ impl App {
         self.tile_fetcher
             .launch_starting_hips_requests(hips, self.downloader.clone());
 
+        // The GPU memory is shared with the new HiPS
+        self.resize_hips_textures()?;
+
         // Once its added, request the tiles in the view (unless the viewer is at depth 0)
         self.request_for_new_tiles = true;
         self.request_redraw = true;
@@ -1012,6 +1451,32 @@ impl App {
         Ok(())
     }
 
+    pub(crate) fn add_rgb_composite(&mut self, cfg: RGBCompositeCfg) -> Result<(), JsValue> {
+        // The channels must be HiPS or FITS image layers
+        for channel in [&cfg.red, &cfg.green, &cfg.blue] {
+            let layer = channel.layer.as_str();
+            if self.layers.get_hips_from_layer(layer).is_none()
+                && self.layers.get_image_from_layer(layer).is_none()
+            {
+                return Err(JsValue::from_str(&format!(
+                    "Layer {:?} not found or is not a HiPS or an image layer",
+                    layer
+                )));
+            }
+        }
+
+        self.layers.add_rgb_composite(
+            cfg,
+            &mut self.camera,
+            &self.projection,
+            &mut self.tile_fetcher,
+        )?;
+
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
     pub(crate) fn add_image_from_blob_and_wcs(
         &mut self,
         layer: String,
@@ -1063,6 +1528,7 @@ impl App {
                 None,
                 None,
                 camera_coo_sys,
+                CutAlgorithm::default(),
             )
             .await
             {
@@ -1110,6 +1576,7 @@ impl App {
         stream: web_sys::ReadableStream,
         meta: ImageMetadata,
         layer: String,
+        cut_algorithm: CutAlgorithm,
     ) -> Result<js_sys::Promise, JsValue> {
         let gl = self.gl.clone();
 
@@ -1126,6 +1593,7 @@ impl App {
         let fut = async move {
             use crate::renderable::image::Image;
             use futures::future::Either;
+            use futures::io::{AsyncReadExt, Cursor};
             use futures::TryStreamExt;
             use js_sys::Uint8Array;
             use wasm_streams::ReadableStream;
@@ -1134,7 +1602,7 @@ impl App {
             let body = ReadableStream::from_raw(stream.dyn_into()?);
 
             // Convert the JS ReadableStream to a Rust stream
-            let bytes_reader = match body.try_into_async_read() {
+            let mut bytes_reader = match body.try_into_async_read() {
                 Ok(async_read) => Either::Left(async_read),
                 Err((_err, body)) => Either::Right(
                     body.into_stream()
@@ -1148,6 +1616,24 @@ impl App {
                 ),
             };
 
+            // Tile-compressed images are decompressed before being parsed
+            let read_error =
+                |e: std::io::Error| JsValue::from_str(&format!("Fits file reading: reason: {}", e));
+            let leading_bytes = crate::fits::read_leading_headers(&mut bytes_reader)
+                .await
+                .map_err(read_error)?;
+            let bytes_reader = if crate::fits::is_compressed(&leading_bytes) {
+                let mut bytes = leading_bytes;
+                bytes_reader
+                    .read_to_end(&mut bytes)
+                    .await
+                    .map_err(read_error)?;
+
+                Either::Left(Cursor::new(crate::fits::decompress(&bytes)?))
+            } else {
+                Either::Right(Cursor::new(leading_bytes).chain(bytes_reader))
+            };
+
             let mut reader = BufReader::new(bytes_reader);
 
             let AsyncFits { mut hdu } = AsyncFits::from_reader(&mut reader)
@@ -1157,7 +1643,7 @@ impl App {
             let mut hdu_ext_idx = 0;
             let mut images = vec![];
 
-            match Image::from_fits_hdu_async(&gl, &mut hdu.0, camera_coo_sys).await {
+            match Image::from_fits_hdu_async(&gl, &mut hdu.0, camera_coo_sys, cut_algorithm).await {
                 Ok(image) => {
                     images.push(image);
 
@@ -1167,8 +1653,13 @@ impl App {
                     while let Ok(Some(mut xhdu)) = hdu_ext {
                         match &mut xhdu {
                             AsyncXtensionHDU::Image(xhdu_img) => {
-                                match Image::from_fits_hdu_async(&gl, xhdu_img, camera_coo_sys)
-                                    .await
+                                match Image::from_fits_hdu_async(
+                                    &gl,
+                                    xhdu_img,
+                                    camera_coo_sys,
+                                    cut_algorithm,
+                                )
+                                .await
                                 {
                                     Ok(image) => {
                                         images.push(image);
@@ -1202,8 +1693,13 @@ impl App {
                     while let Ok(Some(mut xhdu)) = hdu_ext {
                         match &mut xhdu {
                             AsyncXtensionHDU::Image(xhdu_img) => {
-                                match Image::from_fits_hdu_async(&gl, xhdu_img, camera_coo_sys)
-                                    .await
+                                match Image::from_fits_hdu_async(
+                                    &gl,
+                                    xhdu_img,
+                                    camera_coo_sys,
+                                    cut_algorithm,
+                                )
+                                .await
                                 {
                                     Ok(image) => {
                                         images.push(image);
@@ -1295,8 +1791,16 @@ impl App {
         // Set the new meta
         // keep the old meta data
         let new_img_fmt = meta.img_format;
+        let (old_color, new_color) = (&old_meta.color, &meta.color);
+        let cdf_changed = old_color.stretch != new_color.stretch
+            || old_color.min_cut != new_color.min_cut
+            || old_color.max_cut != new_color.max_cut;
         self.layers
             .set_layer_cfg(layer.clone(), meta, &mut self.camera, &self.projection)?;
+        // The distribution only depends on the stretch and the cuts
+        if cdf_changed {
+            self.update_layer_cdf(&layer)?;
+        }
 
         if old_meta.img_format != new_img_fmt {
             // The image format has been changed
@@ -1309,6 +1813,8 @@ impl App {
             // Relaunch the base tiles for the survey to be ready with the new url
             self.tile_fetcher
                 .launch_starting_hips_requests(hips, self.downloader.clone());
+            // The size of the pixels in the GPU memory depends on the format
+            self.resize_hips_textures()?;
 
             // Once its added, request the tiles in the view (unless the viewer is at depth 0)
             self.request_for_new_tiles = true;
@@ -1319,6 +1825,29 @@ impl App {
         Ok(())
     }
 
+    pub(crate) fn set_hips_cube_frame(&mut self, layer: &str, frame: u32) -> Result<(), JsValue> {
+        let hips = self
+            .layers
+            .get_mut_hips_from_layer(layer)
+            .ok_or_else(|| JsValue::from_str("Layer not found"))?;
+        hips.set_cube_frame(frame)?;
+        self.downloader
+            .borrow_mut()
+            .set_cube_frame(hips.get_config().get_creator_did(), frame);
+
+        // The tiles of the frame already received have been restored, fetch the base tiles
+        // only if they are not among them
+        if !hips.is_base_loaded() {
+            self.tile_fetcher
+                .launch_starting_hips_requests(hips, self.downloader.clone());
+        }
+
+        self.request_for_new_tiles = true;
+        self.request_redraw = true;
+
+        Ok(())
+    }
+
     // Width and height given are in pixels
     pub(crate) fn set_projection(&mut self, projection: ProjectionType) -> Result<(), JsValue> {
         self.projection = projection;
@@ -1342,34 +1871,99 @@ impl App {
         self.camera.get_longitude_reversed()
     }
 
-    pub(crate) fn add_catalog(&mut self, _name: String, table: JsValue, _colormap: String) {
-        //let mut exec_ref = self.exec.borrow_mut();
-        let _table = table;
+    pub(crate) fn add_catalog(
+        &mut self,
+        name: String,
+        table: JsValue,
+        colormap: String,
+    ) -> Result<(), JsValue> {
+        // The sources are given as a flat list of (ra, dec) ICRS coordinates in degrees
+        let coo = js_sys::Float64Array::new(&table).to_vec();
+        self.manager
+            .add_catalog(name, &coo, colormap, &mut self.camera, &self.projection)?;
+        self.catalog_loaded = true;
+        self.request_redraw = true;
 
-        /*exec_ref
-        .spawner()
-        .spawn(TaskType::ParseTableTask, async move {
-            let mut stream = ParseTableTask::<[f32; 2]>::new(table);
-            let mut results: Vec<LonLatT<f32>> = vec![];
+        Ok(())
+    }
 
-            while let Some(item) = stream.next().await {
-                results.push(LonLatT::new(item[0].to_angle(), item[1].to_angle()));
-            }
+    pub(crate) fn add_catalog_markers(
+        &mut self,
+        name: String,
+        cfg: al_api::catalog::Catalog,
+        coo: &[f64],
+        size_column: Option<&[f32]>,
+        color_column: Option<&[f32]>,
+    ) -> Result<(), JsValue> {
+        let markers = Markers::new(&self.gl, cfg, coo, size_column, color_column)?;
 
-            let mut stream_sort = BuildCatalogIndex::new(results);
-            while stream_sort.next().await.is_some() {}
+        self.manager
+            .add_markers(name, markers, &mut self.camera, &self.projection);
+        self.catalog_loaded = true;
+        self.request_redraw = true;
 
-            // The stream is finished, we get the sorted sources
-            let results = stream_sort.sources;
+        Ok(())
+    }
 
-            TaskResult::TableParsed {
-                name,
-                sources: results.into_boxed_slice(),
-            }
-        });*/
+    pub(crate) fn remove_catalog(&mut self, name: String) -> Result<(), JsValue> {
+        self.manager
+            .remove_catalog(name, &mut self.camera, &self.projection)?;
+        self.request_redraw = true;
+
+        Ok(())
     }
 
-    pub(crate) fn resize(&mut self, width: f32, height: f32) {
+    pub(crate) fn pick_catalog_sources(
+        &self,
+        name: &str,
+        pos: &Vector2<f64>,
+        tolerance: f64,
+    ) -> Result<Vec<u32>, JsValue> {
+        let sources =
+            self.manager
+                .pick_sources(name, pos, tolerance, &self.camera, &self.projection)?;
+
+        Ok(sources)
+    }
+
+    pub(crate) fn set_catalog_footprints(
+        &mut self,
+        name: String,
+        coo: &[f64],
+        num_vertices: &[u32],
+    ) -> Result<(), JsValue> {
+        let footprints = Footprints::from_radec(coo, num_vertices)?;
+        self.manager.set_footprints(name, footprints)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn pick_catalog_footprints(
+        &self,
+        name: &str,
+        pos: &Vector2<f64>,
+        tolerance: f64,
+    ) -> Result<Vec<u32>, JsValue> {
+        let footprints =
+            self.manager
+                .pick_footprints(name, pos, tolerance, &self.camera, &self.projection)?;
+
+        Ok(footprints)
+    }
+
+    pub(crate) fn select_catalog_sources(
+        &self,
+        name: &str,
+        vertices: &[Vector2<f64>],
+    ) -> Result<Vec<u32>, JsValue> {
+        let sources =
+            self.manager
+                .select_sources(name, vertices, &self.camera, &self.projection)?;
+
+        Ok(sources)
+    }
+
+    pub(crate) fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
         self.camera.set_screen_size(width, height, &self.projection);
         self.camera
             .set_aperture(self.camera.get_aperture(), &self.projection);
@@ -1380,9 +1974,13 @@ impl App {
 
         // launch the new tile requests
         self.request_for_new_tiles = true;
-        //self.manager.set_kernel_size(&self.camera);
+        self.manager.set_kernel_size(&self.camera);
+        // The number of textures depends on the size of the view
+        self.resize_hips_textures()?;
 
         self.request_redraw = true;
+
+        Ok(())
     }
 
     pub(crate) fn set_survey_url(&mut self, cdid: &String, new_url: String) -> Result<(), JsValue> {
@@ -1394,11 +1992,15 @@ impl App {
         name: String,
         opacity: f32,
     ) -> Result<(), JsValue> {
-        let catalog = self.manager.get_mut_catalog(&name).map_err(|e| {
-            let err: JsValue = e.into();
-            err
-        })?;
-        catalog.set_alpha(opacity);
+        if let Ok(markers) = self.manager.get_mut_markers(&name) {
+            markers.set_opacity(opacity);
+        } else {
+            let catalog = self.manager.get_mut_catalog(&name).map_err(|e| {
+                let err: JsValue = e.into();
+                err
+            })?;
+            catalog.set_alpha(opacity);
+        }
 
         self.request_redraw = true;
 
@@ -18,6 +18,10 @@ impl ViewHpxCells {
         let hpx_cells = [
             HpxCells::new(CooSystem::ICRS),
             HpxCells::new(CooSystem::GAL),
+            HpxCells::new(CooSystem::ECL),
+            HpxCells::new(CooSystem::ECLD),
+            HpxCells::new(CooSystem::SGAL),
+            HpxCells::new(CooSystem::FK4),
         ];
 
         Self {
@@ -274,3 +278,31 @@ impl HpxCells {
         new_cells
     }*/
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HpxCells;
+    use crate::camera::FieldOfView;
+    use crate::math::projection::ProjectionType;
+    use crate::CooSystem;
+
+    use cgmath::{Matrix4, SquareMatrix, Vector2, Vector4};
+
+    #[test]
+    fn allsky_view_covers_the_whole_sky() {
+        // A view large enough for the HiPS to be rendered by the raytracer,
+        // the cells in view giving the values for the cuts and the histogram
+        let proj = ProjectionType::Ait(mapproj::pseudocyl::ait::Ait::new());
+        let fov = FieldOfView::new(&Vector2::new(1.0, 1.0), 4.0, &Matrix4::identity(), &proj);
+        assert!(fov.is_allsky());
+
+        let center = Vector4::new(0.0, 0.0, 1.0, 1.0);
+        for frame in [CooSystem::ICRS, CooSystem::GAL] {
+            let mut cells = HpxCells::new(frame);
+            cells.update(0, &fov, &center, CooSystem::ICRS, &proj);
+
+            assert_eq!(cells.get_cells(0).len(), 12);
+            assert_eq!(cells.get_cells(2).len(), 12 * 16);
+        }
+    }
+}
@@ -230,6 +230,17 @@ impl CameraViewPort {
             ProjectionType::Ait(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
             ProjectionType::Mol(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
             ProjectionType::Zea(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Feye(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Air(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Arc(_) => self.aperture >= 140.0_f64.to_radians().to_angle(),
+            ProjectionType::Ncp(_) => false,
+            ProjectionType::Par(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
+            ProjectionType::Sfl(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
+            ProjectionType::Car(_) => self.aperture >= 120.0_f64.to_radians().to_angle(),
+            ProjectionType::Cea(_) => self.aperture >= 120.0_f64.to_radians().to_angle(),
+            ProjectionType::Cyp(_) => self.aperture >= 120.0_f64.to_radians().to_angle(),
+            ProjectionType::Cod(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
+            ProjectionType::Hpx(_) => self.aperture >= 100.0_f64.to_radians().to_angle(),
         }
     }
 
@@ -340,12 +351,12 @@ impl CameraViewPort {
         let _can_unzoom_more = match proj {
             ProjectionType::Tan(_)
             | ProjectionType::Mer(_)
-            //| ProjectionType::Air(_)
-            | ProjectionType::Stg(_) => false,
-            //| ProjectionType::Car(_)
-            //| ProjectionType::Cea(_)
-            //| ProjectionType::Cyp(_)
-            //| ProjectionType::Hpx(_) => false,
+            | ProjectionType::Air(_)
+            | ProjectionType::Stg(_)
+            | ProjectionType::Car(_)
+            | ProjectionType::Cea(_)
+            | ProjectionType::Cyp(_)
+            | ProjectionType::Hpx(_) => false,
             _ => true,
         };
 
@@ -83,4 +83,89 @@ mod tests {
         assert_delta!(gal_lon_deg, 0.0, 1e-3);
         assert_delta!(gal_lat_deg, 0.0, 1e-3);
     }
+
+    #[test]
+    fn j2000_to_ecl() {
+        use super::CooSystem;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+
+        // The summer solstice point lies on the ecliptic
+        let lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(90.0).into(), ArcDeg(23.4392911).into());
+        let ecl_lonlat =
+            super::apply_coo_system(CooSystem::ICRS, CooSystem::ECL, &lonlat.vector()).lonlat();
+
+        let ecl_lon_deg = ecl_lonlat.lon().0.to_degrees();
+        let ecl_lat_deg = ecl_lonlat.lat().0.to_degrees();
+
+        assert!((ecl_lon_deg - 90.0).abs() < 1e-6);
+        assert!(ecl_lat_deg.abs() < 1e-6);
+    }
+
+    #[test]
+    fn sgal_to_j2000() {
+        use super::CooSystem;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+
+        let lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(0.0).into());
+        let j2000_lonlat =
+            super::apply_coo_system(CooSystem::SGAL, CooSystem::ICRS, &lonlat.vector()).lonlat();
+
+        let j2000_lon_deg = j2000_lonlat.lon().0.to_degrees();
+        let j2000_lat_deg = j2000_lonlat.lat().0.to_degrees();
+
+        assert!((j2000_lon_deg - 42.3101227).abs() < 1e-4);
+        assert!((j2000_lat_deg - 59.5283498).abs() < 1e-4);
+    }
+
+    #[test]
+    fn gal_to_fk4() {
+        use super::CooSystem;
+        use crate::math::lonlat::LonLat;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+
+        // Galactic center in B1950
+        let lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(0.0).into(), ArcDeg(0.0).into());
+        let b1950_lonlat =
+            super::apply_coo_system(CooSystem::GAL, CooSystem::FK4, &lonlat.vector()).lonlat();
+
+        let b1950_lon_deg = b1950_lonlat.lon().0.to_degrees();
+        let b1950_lat_deg = b1950_lonlat.lat().0.to_degrees();
+
+        assert!((b1950_lon_deg - 265.6108).abs() < 1e-3);
+        assert!((b1950_lat_deg + 28.9168).abs() < 1e-3);
+    }
+
+    #[test]
+    fn all_frames_roundtrip() {
+        use super::CooSystem;
+        use crate::ArcDeg;
+        use crate::LonLatT;
+        use cgmath::{InnerSpace, Vector4};
+
+        let frames = [
+            CooSystem::ICRS,
+            CooSystem::GAL,
+            CooSystem::ECL,
+            CooSystem::ECLD,
+            CooSystem::SGAL,
+            CooSystem::FK4,
+        ];
+
+        let lonlat: LonLatT<f64> = LonLatT::new(ArcDeg(123.4).into(), ArcDeg(-56.7).into());
+        let pos: Vector4<f64> = lonlat.vector();
+
+        for c1 in frames {
+            for c2 in frames {
+                let p2 = super::apply_coo_system(c1, c2, &pos);
+                let p1 = super::apply_coo_system(c2, c1, &p2);
+
+                assert!((p1 - pos).truncate().magnitude() < 1e-9);
+            }
+        }
+    }
 }
@@ -0,0 +1,251 @@
+use std::cell::{Cell, RefCell};
+
+use al_api::hips::{Credentials, RequestOptions};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AbortSignal, Headers, RequestCredentials, RequestInit, RequestMode, Response};
+
+use super::request::RequestError;
+use crate::Abort;
+
+/// The options of the HTTP requests of a HiPS
+///
+/// They are shared by all the requests of the HiPS so that a refreshed token
+/// is taken into account by the next ones.
+#[derive(Debug)]
+pub struct FetchOptions {
+    // Extra headers, the Authorization one being replaced when the token is refreshed
+    headers: RefCell<Vec<(String, String)>>,
+    credentials: Credentials,
+    refresh_token: Option<js_sys::Function>,
+
+    // Incremented each time the token is refreshed
+    token_generation: Cell<u32>,
+    // The refresh of the token in progress, awaited by all the requests answered 401 meanwhile
+    pending_refresh: RefCell<Option<js_sys::Promise>>,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        FetchOptions::new(&RequestOptions::default())
+    }
+}
+
+impl FetchOptions {
+    pub fn new(options: &RequestOptions) -> Self {
+        let headers = options
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        FetchOptions {
+            headers: RefCell::new(headers),
+            credentials: options.credentials,
+            refresh_token: options.refresh_token.clone(),
+
+            token_generation: Cell::new(0),
+            pending_refresh: RefCell::new(None),
+        }
+    }
+
+    // An image element can only be customized with its crossorigin attribute
+    fn can_use_image_element(&self) -> bool {
+        self.headers.borrow().is_empty()
+            && self.refresh_token.is_none()
+            && self.credentials != Credentials::Omit
+    }
+
+    fn cross_origin(&self) -> &'static str {
+        match self.credentials {
+            Credentials::Include => "use-credentials",
+            _ => "anonymous",
+        }
+    }
+
+    fn set_header(&self, name: &str, value: String) {
+        let mut headers = self.headers.borrow_mut();
+        if let Some(header) = headers
+            .iter_mut()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        {
+            header.1 = value;
+        } else {
+            headers.push((name.to_string(), value));
+        }
+    }
+
+    /// Fetch the resource at `url`
+    ///
+    /// If the server answers HTTP 401 and a token refresh callback is given,
+    /// the token is refreshed and the resource fetched again once.
+    /// The response is returned whatever its status code.
+    /// The request is cancelled when the optional `signal` is aborted.
+    pub async fn fetch(
+        &self,
+        url: &str,
+        signal: Option<&AbortSignal>,
+    ) -> Result<Response, RequestError> {
+        let generation = self.token_generation.get();
+        let resp = self.fetch_once(url, signal).await?;
+
+        match &self.refresh_token {
+            Some(refresh_token) if resp.status() == 401 => {
+                // The token may have already been refreshed by another request
+                if generation == self.token_generation.get() {
+                    self.refresh_token(refresh_token).await?;
+                }
+
+                self.fetch_once(url, signal).await
+            }
+            _ => Ok(resp),
+        }
+    }
+
+    async fn fetch_once(
+        &self,
+        url: &str,
+        signal: Option<&AbortSignal>,
+    ) -> Result<Response, RequestError> {
+        let opts = RequestInit::new();
+        opts.set_method("GET");
+        opts.set_mode(RequestMode::Cors);
+        opts.set_credentials(match self.credentials {
+            Credentials::Omit => RequestCredentials::Omit,
+            Credentials::SameOrigin => RequestCredentials::SameOrigin,
+            Credentials::Include => RequestCredentials::Include,
+        });
+
+        let headers = Headers::new()?;
+        for (name, value) in self.headers.borrow().iter() {
+            headers.append(name, value)?;
+        }
+        opts.set_headers(&headers);
+        if let Some(signal) = signal {
+            opts.set_signal(Some(signal));
+        }
+
+        let request = web_sys::Request::new_with_str_and_init(url, &opts)?;
+        let window = web_sys::window().unwrap_abort();
+        // The fetch promise is rejected on network errors
+        let resp_value = JsFuture::from(window.fetch_with_request(&request))
+            .await
+            .map_err(RequestError::Transient)?;
+        // `resp_value` is a `Response` object.
+        debug_assert!(resp_value.is_instance_of::<Response>());
+        let resp: Response = resp_value.dyn_into()?;
+
+        Ok(resp)
+    }
+
+    async fn refresh_token(&self, refresh_token: &js_sys::Function) -> Result<(), RequestError> {
+        let pending = self.pending_refresh.borrow().clone();
+        let promise = if let Some(promise) = pending {
+            promise
+        } else {
+            // The callback returns the token or a promise of it
+            let promise = js_sys::Promise::resolve(&refresh_token.call0(&JsValue::NULL)?);
+            *self.pending_refresh.borrow_mut() = Some(promise.clone());
+
+            promise
+        };
+
+        let token = JsFuture::from(promise).await;
+        self.pending_refresh.borrow_mut().take();
+
+        let token = token?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("The token refresh callback must give a string"))?;
+        self.set_header("Authorization", format!("Bearer {}", token));
+        self.token_generation.set(self.token_generation.get() + 1);
+
+        Ok(())
+    }
+
+    /// Load the image at `url`
+    ///
+    /// The image is fetched before being decoded by an image element if the request
+    /// has to be customized with headers.
+    /// The download is cancelled when the optional `signal` is aborted.
+    pub async fn fetch_html_image(
+        &self,
+        url: &str,
+        signal: Option<&AbortSignal>,
+    ) -> Result<web_sys::HtmlImageElement, RequestError> {
+        if self.can_use_image_element() {
+            match query_html_image(url, self.cross_origin(), signal).await {
+                Ok(image) => Ok(image),
+                Err(err) if signal.is_some_and(|signal| signal.aborted()) => {
+                    Err(RequestError::Other(err))
+                }
+                // An image element does not tell why it failed, the resource is fetched
+                // to get the status of the response
+                Err(err) => {
+                    let resp = self.fetch(url, signal).await?;
+                    if resp.ok() {
+                        // The image could not be decoded
+                        Err(RequestError::Other(err))
+                    } else {
+                        Err(RequestError::from_status(resp.status()))
+                    }
+                }
+            }
+        } else {
+            let resp = self.fetch(url, signal).await?;
+            if !resp.ok() {
+                return Err(RequestError::from_status(resp.status()));
+            }
+
+            let blob: web_sys::Blob = JsFuture::from(resp.blob()?)
+                .await
+                .map_err(RequestError::Transient)?
+                .dyn_into()?;
+            let blob_url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+            let image = query_html_image(&blob_url, "anonymous", None).await;
+            let _ = web_sys::Url::revoke_object_url(&blob_url);
+
+            Ok(image?)
+        }
+    }
+}
+
+async fn query_html_image(
+    url: &str,
+    cross_origin: &str,
+    signal: Option<&AbortSignal>,
+) -> Result<web_sys::HtmlImageElement, JsValue> {
+    let image = web_sys::HtmlImageElement::new().unwrap_abort();
+    let image_cloned = image.clone();
+    let cross_origin = cross_origin.to_string();
+    let signal = signal.cloned();
+
+    let promise = js_sys::Promise::new(
+        &mut (Box::new(move |resolve, reject| {
+            // Ask for CORS permissions
+            image_cloned.set_cross_origin(Some(&cross_origin));
+            image_cloned.set_onload(Some(&resolve));
+            image_cloned.set_onerror(Some(&reject));
+
+            if let Some(signal) = &signal {
+                let image = image_cloned.clone();
+                let reject = reject.clone();
+                let on_abort = Closure::once_into_js(move || {
+                    // Stop the download of the image
+                    image.set_src("");
+                    let _ = reject.call1(&JsValue::NULL, &JsValue::from_str("Aborted"));
+                });
+                // A listener does not replace the other handlers of the signal
+                let _ = signal.add_event_listener_with_callback("abort", on_abort.unchecked_ref());
+            }
+
+            image_cloned.set_src(&url);
+        }) as Box<dyn FnMut(js_sys::Function, js_sys::Function)>),
+    );
+
+    let _ = JsFuture::from(promise).await?;
+
+    Ok(image)
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::query::Url;
+use crate::time::{DeltaTime, Time};
+
+// Weight of the last request in the moving averages
+const SMOOTHING: f32 = 0.2;
+// Number of requests before a mirror can be considered as failing
+const MIN_NUM_REQUESTS: u32 = 5;
+// Ratio of failed requests above which a mirror is considered as failing
+const MAX_ERROR_RATE: f32 = 0.5;
+// Time during which a mirror that failed is not used again
+const FAILING_MIRROR_COOLDOWN: DeltaTime = DeltaTime::from_millis(60_000.0);
+
+/// Statistics of the tile requests sent to a mirror of a HiPS
+#[derive(Default, Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorStats {
+    /// Moving average of the duration in milliseconds of the successful requests
+    pub latency: Option<f32>,
+    /// Moving average of the ratio of failed requests
+    pub error_rate: f32,
+    /// Number of requests resolved
+    pub num_requests: u32,
+
+    // Time until which the mirror is not used because it failed
+    #[serde(skip)]
+    excluded_until: Option<Time>,
+}
+
+impl MirrorStats {
+    /// Record a request resolved in `duration`
+    pub fn add_success(&mut self, duration: DeltaTime) {
+        let duration = duration.as_millis();
+        self.latency = Some(match self.latency {
+            Some(latency) => latency + SMOOTHING * (duration - latency),
+            None => duration,
+        });
+
+        self.add_request(false);
+    }
+
+    /// Record a failed request
+    pub fn add_failure(&mut self) {
+        self.add_request(true);
+    }
+
+    fn add_request(&mut self, failed: bool) {
+        let error = if failed { 1.0 } else { 0.0 };
+        self.error_rate += SMOOTHING * (error - self.error_rate);
+        self.num_requests += 1;
+    }
+
+    pub fn is_failing(&self) -> bool {
+        self.num_requests >= MIN_NUM_REQUESTS && self.error_rate > MAX_ERROR_RATE
+    }
+
+    fn is_excluded(&self, now: Time) -> bool {
+        self.excluded_until.is_some_and(|until| now < until)
+    }
+}
+
+/// A mirror of a HiPS with the statistics of its tile requests
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorState {
+    pub url: Url,
+    /// Whether the tiles are currently requested from this mirror
+    pub current: bool,
+    pub stats: MirrorStats,
+}
+
+/// Choose the mirror to switch to if the `current` one is failing
+///
+/// The mirror with the lowest latency is chosen among the ones that are not failing,
+/// the mirrors not requested yet coming last. The current mirror is then excluded
+/// for a while. Returns `None` if the current mirror is fine or if no other is available.
+pub fn switch_mirror<'a>(
+    mirrors: &'a [Url],
+    current: &str,
+    stats: &mut HashMap<Url, MirrorStats>,
+    now: Time,
+) -> Option<&'a Url> {
+    if !stats.get(current).is_some_and(|s| s.is_failing()) {
+        return None;
+    }
+
+    // The mirrors excluded long enough are given a fresh start
+    for s in stats.values_mut() {
+        if s.excluded_until.is_some_and(|until| now >= until) {
+            *s = MirrorStats::default();
+        }
+    }
+
+    let latency = |url: &Url| {
+        stats
+            .get(url)
+            .and_then(|s| s.latency)
+            .unwrap_or(f32::INFINITY)
+    };
+    let mirror = mirrors
+        .iter()
+        .filter(|url| *url != current)
+        .filter(|url| {
+            !stats
+                .get(*url)
+                .is_some_and(|s| s.is_failing() || s.is_excluded(now))
+        })
+        .min_by(|u1, u2| latency(u1).total_cmp(&latency(u2)))?;
+
+    // Stop using the current mirror for a while
+    stats.entry(current.to_string()).or_default().excluded_until =
+        Some(now + FAILING_MIRROR_COOLDOWN);
+
+    Some(mirror)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{switch_mirror, MirrorStats};
+    use crate::time::{DeltaTime, Time};
+    use std::collections::HashMap;
+
+    fn failing() -> MirrorStats {
+        let mut stats = MirrorStats::default();
+        for _ in 0..5 {
+            stats.add_failure();
+        }
+
+        stats
+    }
+
+    fn with_latency(latency: f32) -> MirrorStats {
+        let mut stats = MirrorStats::default();
+        stats.add_success(DeltaTime::from_millis(latency));
+
+        stats
+    }
+
+    #[test]
+    fn stays_on_a_working_mirror() {
+        let mirrors = vec!["a".to_string(), "b".to_string()];
+        let mut stats = HashMap::new();
+        stats.insert("a".to_string(), with_latency(500.0));
+        stats.insert("b".to_string(), with_latency(100.0));
+
+        assert_eq!(switch_mirror(&mirrors, "a", &mut stats, Time(0.0)), None);
+    }
+
+    #[test]
+    fn switches_to_the_fastest_mirror() {
+        let mirrors = vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "d".to_string(),
+        ];
+        let mut stats = HashMap::new();
+        stats.insert("a".to_string(), failing());
+        stats.insert("b".to_string(), with_latency(300.0));
+        stats.insert("c".to_string(), with_latency(100.0));
+
+        let mirror = switch_mirror(&mirrors, "a", &mut stats, Time(0.0));
+        assert_eq!(mirror.map(|m| m.as_str()), Some("c"));
+
+        // The failing mirror is not chosen again before the cooldown
+        stats.insert("c".to_string(), failing());
+        let mirror = switch_mirror(&mirrors, "c", &mut stats, Time(1000.0));
+        assert_eq!(mirror.map(|m| m.as_str()), Some("b"));
+    }
+
+    #[test]
+    fn uses_again_a_mirror_after_its_cooldown() {
+        let mirrors = vec!["a".to_string(), "b".to_string()];
+        let mut stats = HashMap::new();
+        stats.insert("a".to_string(), failing());
+
+        let mirror = switch_mirror(&mirrors, "a", &mut stats, Time(0.0));
+        assert_eq!(mirror.map(|m| m.as_str()), Some("b"));
+
+        stats.insert("b".to_string(), failing());
+        assert_eq!(switch_mirror(&mirrors, "b", &mut stats, Time(1000.0)), None);
+        let mirror = switch_mirror(&mirrors, "b", &mut stats, Time(61_000.0));
+        assert_eq!(mirror.map(|m| m.as_str()), Some("a"));
+    }
+
+    #[test]
+    fn stays_if_all_mirrors_fail() {
+        let mirrors = vec!["a".to_string(), "b".to_string()];
+        let mut stats = HashMap::new();
+        stats.insert("a".to_string(), failing());
+        stats.insert("b".to_string(), failing());
+
+        assert_eq!(switch_mirror(&mirrors, "a", &mut stats, Time(0.0)), None);
+    }
+}
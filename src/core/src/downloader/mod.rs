@@ -1,7 +1,9 @@
+pub mod fetch;
+pub mod mirror;
 pub mod query;
 pub mod request;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use query::QueryId;
 
@@ -10,28 +12,96 @@ pub struct Downloader {
     requests: Vec<RequestType>,
     queried_list: HashSet<QueryId>,
 
+    // The received images, given back when queried again
     cache: Cache<QueryId, Resource>,
     queried_cached_ids: Vec<QueryId>,
+    // The resources pinned in the cache for each HiPS, with their frame if the HiPS is a cube
+    pinned: HashMap<CreatorDid, HashMap<QueryId, Option<u32>>>,
+    // The frame displayed of the HiPS cubes, only its base resources are pinned
+    cube_frames: HashMap<CreatorDid, u32>,
+
+    // Statistics of the tile requests sent to each mirror
+    mirror_stats: HashMap<Url, MirrorStats>,
 }
 
-use crate::fifo_cache::Cache;
+// Memory budget of the cache of the received images
+const DEFAULT_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+use crate::healpix::cell::HEALPixCell;
+use crate::lru_cache::{Cache, CacheStats};
+use crate::renderable::CreatorDid;
+use crate::time::Time;
+use mirror::MirrorStats;
+use query::Url;
 
 use query::Query;
-use request::{RequestType, Resource};
+use request::{RequestType, ResolvedStatus, Resource};
 
 impl Downloader {
     pub fn new() -> Downloader {
         let requests = Vec::with_capacity(32);
         let queried_list = HashSet::with_capacity(64);
-        let cache = Cache::new();
+        let cache = Cache::new(DEFAULT_CACHE_BUDGET);
         let queried_cached_ids = Vec::with_capacity(64);
         Self {
             requests,
             queried_list,
             cache,
             queried_cached_ids,
+            pinned: HashMap::new(),
+            cube_frames: HashMap::new(),
+            mirror_stats: HashMap::new(),
+        }
+    }
+
+    /// Set the memory budget of the cache in bytes
+    pub fn set_cache_budget(&mut self, budget: usize) {
+        self.cache.set_budget(budget);
+    }
+
+    pub fn get_cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Let the pinned resources of a HiPS be evicted from the cache
+    ///
+    /// To call when the HiPS is removed
+    pub fn release_hips_resources(&mut self, hips_cdid: &str) {
+        for id in self.pinned.remove(hips_cdid).unwrap_or_default().keys() {
+            self.cache.unpin(id);
+        }
+        self.cube_frames.remove(hips_cdid);
+    }
+
+    /// Set the frame displayed of a HiPS cube
+    ///
+    /// The allsky and the root tiles of the other frames can be evicted from the cache
+    pub fn set_cube_frame(&mut self, hips_cdid: &str, frame: u32) {
+        self.cube_frames.insert(hips_cdid.to_string(), frame);
+
+        if let Some(pinned) = self.pinned.get_mut(hips_cdid) {
+            let cache = &mut self.cache;
+            pinned.retain(|id, f| {
+                let keep = f.is_none() || *f == Some(frame);
+                if !keep {
+                    cache.unpin(id);
+                }
+
+                keep
+            });
         }
     }
+
+    /// Get the statistics of the tile requests sent to a mirror
+    pub fn get_mirror_stats(&self, mirror: &str) -> MirrorStats {
+        self.mirror_stats.get(mirror).copied().unwrap_or_default()
+    }
+
+    /// Choose another mirror for the tiles of a HiPS if its `current` one is failing
+    pub fn switch_mirror(&mut self, mirrors: &[Url], current: &str) -> Option<Url> {
+        mirror::switch_mirror(mirrors, current, &mut self.mirror_stats, Time::now()).cloned()
+    }
+
     // Returns true if the fetch has been done
     // Returns false if the query has already been done
     pub fn fetch<T>(&mut self, query: T) -> bool
@@ -39,34 +109,71 @@ impl Downloader {
         T: Query,
     {
         let id = query.id();
-        if self.cache.contains(id) {
-            //self.queried_cached_urls.push(url.clone());
+        if self.queried_list.contains(id) {
             false
-        } else {
-            let query_id = query.id();
-
-            let not_already_requested = !self.queried_list.contains(query_id);
+        } else if self.cache.get(id).is_some() {
+            // The resource is given back at the next call of get_received_resources
+            if !self.queried_cached_ids.contains(id) {
+                self.queried_cached_ids.push(id.clone());
+            }
 
+            false
+        } else {
             // The cell is not already requested
-            if not_already_requested {
-                self.queried_list.insert(query_id.to_string());
+            self.queried_list.insert(id.to_string());
 
-                let request = T::Request::from(query);
-                self.requests.push(request.into());
-            }
+            let request = T::Request::from(query);
+            self.requests.push(request.into());
 
-            not_already_requested
+            true
         }
     }
 
+    /// Abort the tile requests of a HiPS whose cell is not to be kept
+    pub fn abort_tile_requests<F>(&mut self, hips_cdid: &str, keep: F)
+    where
+        F: Fn(&HEALPixCell) -> bool,
+    {
+        let queried_list = &mut self.queried_list;
+        self.requests.retain(|request| match request {
+            RequestType::Tile(tile) if tile.get_hips_cdid() == hips_cdid && !keep(tile.cell()) => {
+                tile.abort();
+                queried_list.remove(&tile.id);
+
+                false
+            }
+            _ => true,
+        });
+    }
+
     pub fn get_received_resources(&mut self) -> Vec<Resource> {
         let mut rscs = vec![];
 
         let mut finished_query_list = vec![];
+        let mirror_stats = &mut self.mirror_stats;
         self.requests = self
             .requests
             .drain(..)
             .filter(|request| {
+                if let RequestType::Tile(tile) = request {
+                    if let Some(mirror) = tile.get_mirror() {
+                        match tile.resolve_status() {
+                            // A missing tile is a valid answer of the mirror
+                            ResolvedStatus::Found | ResolvedStatus::Missing => mirror_stats
+                                .entry(mirror.clone())
+                                .or_default()
+                                .add_success(tile.duration()),
+                            // Only the network and server errors tell that the mirror is failing
+                            ResolvedStatus::TransientFailure => mirror_stats
+                                .entry(mirror.clone())
+                                .or_default()
+                                .add_failure(),
+                            // e.g. a tile that cannot be decoded
+                            ResolvedStatus::Failed | ResolvedStatus::NotResolved => (),
+                        }
+                    }
+                }
+
                 // If the request resolves into a resource
                 if let Some(rsc) = request.into() {
                     rscs.push(rsc);
@@ -80,13 +187,45 @@ impl Downloader {
             })
             .collect();
 
+        // Keep the received images to give them back if they are queried again
+        for (query_id, rsc) in finished_query_list.iter().zip(rscs.iter()) {
+            // The allsky and the root tiles are the base of a HiPS, they are never evicted
+            // (only the ones of the frame displayed for a HiPS cube)
+            let base = match rsc {
+                Resource::Tile(tile) if !tile.missing() => {
+                    Some((tile.get_hips_cdid(), tile.frame)).filter(|_| tile.cell.is_root())
+                }
+                Resource::Allsky(allsky) if !allsky.missing() => {
+                    Some((allsky.get_hips_cdid(), allsky.frame))
+                }
+                _ => continue,
+            };
+
+            if let Some((hips_cdid, frame)) = base {
+                let current_frame = self.cube_frames.get(hips_cdid).copied();
+                let pin = match (frame, current_frame) {
+                    (Some(frame), Some(current_frame)) => frame == current_frame,
+                    _ => true,
+                };
+
+                if pin {
+                    self.cache.pin(query_id.clone());
+                    self.pinned
+                        .entry(hips_cdid.clone())
+                        .or_default()
+                        .insert(query_id.clone(), frame);
+                }
+            }
+            self.cache.insert(query_id.clone(), rsc.clone());
+        }
+
         for query_id in finished_query_list.into_iter() {
             self.queried_list.remove(&query_id);
         }
 
         while let Some(id) = self.queried_cached_ids.pop() {
-            if let Some(rsc) = self.cache.extract(&id) {
-                rscs.push(rsc);
+            if let Some(rsc) = self.cache.peek(&id) {
+                rscs.push(rsc.clone());
             }
         }
 
@@ -9,17 +9,34 @@ pub trait Query: Sized {
 
 pub type QueryId = String;
 
+use super::fetch::FetchOptions;
 use al_core::image::format::ImageFormatType;
+use std::rc::Rc;
 
-#[derive(Eq, Hash, PartialEq, Clone)]
+#[derive(Clone)]
 pub struct Tile {
     pub cell: HEALPixCell,
     pub format: ImageFormatType,
     // The root url of the HiPS
     pub hips_cdid: CreatorDid,
+    // The url of the mirror the tile is requested from
+    pub hips_url: Url,
     // The total url of the query
     pub url: Url,
     pub id: QueryId,
+    // The frame of the tile if the HiPS is a cube
+    pub frame: Option<u32>,
+    // The options of the HTTP request
+    pub options: Rc<FetchOptions>,
+}
+
+// Suffix of the files of a HiPS cube frame
+// The first frame of a cube is stored in the same files as a classical HiPS
+fn cube_frame_suffix(frame: Option<u32>) -> String {
+    match frame {
+        Some(frame) if frame > 0 => format!("_{}", frame),
+        _ => String::new(),
+    }
 }
 
 use crate::renderable::CreatorDid;
@@ -30,26 +47,43 @@ impl Tile {
         hips_cdid: String,
         hips_url: String,
         format: ImageFormatType,
+        ext: &str,
+        frame: Option<u32>,
+        options: &Rc<FetchOptions>,
     ) -> Self {
-        let ext = format.get_ext_file();
-
         let HEALPixCell(depth, idx) = *cell;
 
         let dir_idx = (idx / 10000) * 10000;
+        let suffix = cube_frame_suffix(frame);
 
         let url = format!(
-            "{}/Norder{}/Dir{}/Npix{}.{}",
-            hips_url, depth, dir_idx, idx, ext
+            "{}/Norder{}/Dir{}/Npix{}{}.{}",
+            hips_url, depth, dir_idx, idx, suffix, ext
         );
 
-        let id = format!("{}{}{}{}", hips_cdid, depth, idx, ext);
+        let id = format!("{}{}{}{}{}", hips_cdid, depth, idx, suffix, ext);
 
         Tile {
             hips_cdid,
+            hips_url,
             url,
             cell: *cell,
             format,
             id,
+            frame,
+            options: options.clone(),
+        }
+    }
+}
+
+impl Tile {
+    /// Request the tile from another mirror of the HiPS
+    ///
+    /// The tiles read from local files are not redirected
+    pub fn redirect(&mut self, hips_url: &str) {
+        if let Some(path) = self.url.strip_prefix(&self.hips_url) {
+            self.url = format!("{}{}", hips_url, path);
+            self.hips_url = hips_url.to_string();
         }
     }
 }
@@ -73,6 +107,11 @@ pub struct Allsky {
     // The total url of the query
     pub url: Url,
     pub id: QueryId,
+    // The frame of the allsky if the HiPS is a cube
+    pub frame: Option<u32>,
+    // The uncompressed allsky requested if the HiPS does not provide a tile-compressed one
+    pub fallback_url: Option<Url>,
+    pub options: Rc<FetchOptions>,
 }
 
 impl Allsky {
@@ -81,11 +120,16 @@ impl Allsky {
         let tile_size = cfg.get_tile_size();
         let texture_size = cfg.get_texture_size();
         let format = cfg.get_format();
-        let ext = format.get_ext_file();
+        let ext = cfg.get_tile_ext();
+        let frame = cfg.get_cube_frame();
+        let suffix = cube_frame_suffix(frame);
 
-        let url = format!("{}/Norder3/Allsky.{}", cfg.get_root_url(), ext);
+        let url = format!("{}/Norder3/Allsky{}.{}", cfg.get_root_url(), suffix, ext);
+        let fallback_url = ext
+            .strip_suffix(".fz")
+            .map(|ext| format!("{}/Norder3/Allsky{}.{}", cfg.get_root_url(), suffix, ext));
 
-        let id = format!("{}Allsky{}", cfg.get_creator_did(), ext);
+        let id = format!("{}Allsky{}{}", cfg.get_creator_did(), suffix, ext);
 
         Allsky {
             tile_size,
@@ -94,6 +138,9 @@ impl Allsky {
             url,
             format,
             id,
+            frame,
+            fallback_url,
+            options: cfg.get_fetch_options().clone(),
         }
     }
 }
@@ -115,13 +162,14 @@ pub struct PixelMetadata {
     // The total url of the query
     pub url: Url,
     pub id: QueryId,
+    pub options: Rc<FetchOptions>,
 }
 
 impl PixelMetadata {
     pub fn new(cfg: &HiPSConfig) -> Self {
         let hips_cdid = cfg.get_creator_did().to_string();
         let format = cfg.get_format();
-        let ext = format.get_ext_file();
+        let ext = cfg.get_tile_ext();
 
         let url = format!("{}/Norder3/Allsky.{}", cfg.get_root_url(), ext);
 
@@ -131,6 +179,7 @@ impl PixelMetadata {
             url,
             format,
             id,
+            options: cfg.get_fetch_options().clone(),
         }
     }
 }
@@ -150,13 +199,20 @@ pub struct Moc {
     pub url: Url,
     pub params: al_api::moc::MOC,
     pub hips_cdid: CreatorDid,
+    pub options: Rc<FetchOptions>,
 }
 impl Moc {
-    pub fn new(url: String, hips_cdid: CreatorDid, params: al_api::moc::MOC) -> Self {
+    pub fn new(
+        url: String,
+        hips_cdid: CreatorDid,
+        params: al_api::moc::MOC,
+        options: &Rc<FetchOptions>,
+    ) -> Self {
         Moc {
             url,
             params,
             hips_cdid,
+            options: options.clone(),
         }
     }
 }
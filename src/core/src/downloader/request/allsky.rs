@@ -1,5 +1,6 @@
 use std::io::Cursor;
 
+use crate::downloader::fetch::FetchOptions;
 use crate::downloader::query;
 use crate::renderable::CreatorDid;
 use al_core::image::format::ChannelType;
@@ -14,6 +15,7 @@ pub struct AllskyRequest {
     pub url: Url,
     pub depth_tile: u8,
     pub id: QueryId,
+    frame: Option<u32>,
 
     request: Request<Vec<ImageType>>,
 }
@@ -27,26 +29,13 @@ impl From<AllskyRequest> for RequestType {
 use super::Url;
 
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{RequestInit, RequestMode, Response};
 
 use al_core::{image::raw::ImageBuffer, texture::pixel::Pixel};
 use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 
-async fn query_image(url: &str) -> Result<ImageBuffer<RGBA8U>, JsValue> {
-    let image = web_sys::HtmlImageElement::new().unwrap_abort();
-    let image_cloned = image.clone();
-
-    let html_img_elt_promise = js_sys::Promise::new(
-        &mut (Box::new(move |resolve, reject| {
-            image_cloned.set_cross_origin(Some(""));
-            image_cloned.set_onload(Some(&resolve));
-            image_cloned.set_onerror(Some(&reject));
-            image_cloned.set_src(&url);
-        }) as Box<dyn FnMut(js_sys::Function, js_sys::Function)>),
-    );
-
-    let _ = JsFuture::from(html_img_elt_promise).await?;
+async fn query_image(url: &str, options: &FetchOptions) -> Result<ImageBuffer<RGBA8U>, JsValue> {
+    let image = options.fetch_html_image(url, None).await?;
 
     // The image has been received here
     let document = web_sys::window().unwrap_abort().document().unwrap_abort();
@@ -80,6 +69,9 @@ impl From<query::Allsky> for AllskyRequest {
             hips_cdid,
             texture_size,
             id,
+            frame,
+            fallback_url,
+            options,
         } = query;
 
         let depth_tile = crate::math::utils::log_2_unchecked(texture_size / tile_size) as u8;
@@ -90,7 +82,7 @@ impl From<query::Allsky> for AllskyRequest {
             match channel {
                 ChannelType::RGB8U => {
                     let allsky_tile_size = std::cmp::min(tile_size, 64);
-                    let allsky = query_image(&url_clone).await?;
+                    let allsky = query_image(&url_clone, &options).await?;
 
                     let allsky_tiles = handle_allsky_file::<RGBA8U>(
                         allsky,
@@ -117,7 +109,7 @@ impl From<query::Allsky> for AllskyRequest {
                 }
                 ChannelType::RGBA8U => {
                     let allsky_tile_size = std::cmp::min(tile_size, 64);
-                    let allsky = query_image(&url_clone).await?;
+                    let allsky = query_image(&url_clone, &options).await?;
 
                     let allsky_tiles =
                         handle_allsky_file(allsky, allsky_tile_size, texture_size, tile_size)?
@@ -128,16 +120,13 @@ impl From<query::Allsky> for AllskyRequest {
                     Ok(allsky_tiles)
                 }
                 _ => {
-                    let opts = RequestInit::new();
-                    opts.set_method("GET");
-                    opts.set_mode(RequestMode::Cors);
-                    let window = web_sys::window().unwrap_abort();
-
-                    let request = web_sys::Request::new_with_str_and_init(&url_clone, &opts)?;
-                    let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-                    // `resp_value` is a `Response` object.
-                    debug_assert!(resp_value.is_instance_of::<Response>());
-                    let resp: Response = resp_value.dyn_into()?;
+                    let mut resp = options.fetch(&url_clone, None).await?;
+                    // The allsky of a HiPS with tile-compressed tiles may not be compressed
+                    if !resp.ok() {
+                        if let Some(fallback_url) = &fallback_url {
+                            resp = options.fetch(fallback_url, None).await?;
+                        }
+                    }
                     // See https://github.com/MattiasBuelens/wasm-streams/blob/f6dacf58a8826dc67923ab4a3bae87635690ca64/examples/fetch_as_stream.rs#L25-L33
                     /*let raw_body = resp.body().ok_or(JsValue::from_str("Cannot extract readable stream"))?;
                     let body = ReadableStream::from_raw(raw_body.dyn_into()?);
@@ -154,6 +143,9 @@ impl From<query::Allsky> for AllskyRequest {
                         raw_bytes.set_len(num_bytes);
                     }
                     bytes_buffer.copy_to(&mut raw_bytes[..]);
+                    if crate::fits::is_compressed(&raw_bytes) {
+                        raw_bytes = crate::fits::decompress(&raw_bytes)?;
+                    }
                     let mut reader = Cursor::new(&raw_bytes[..]);
                     let Fits { hdu } = Fits::from_reader(&mut reader)
                         .map_err(|_| JsValue::from_str("Parsing fits error of allsky"))?;
@@ -211,6 +203,7 @@ impl From<query::Allsky> for AllskyRequest {
             hips_cdid,
             depth_tile,
             url,
+            frame,
             request,
         }
     }
@@ -305,10 +298,13 @@ use al_core::image::format::RGBA8U;
 
 use crate::time::Time;
 use std::sync::{Arc, Mutex};
+#[derive(Clone)]
 pub struct Allsky {
     pub image: Arc<Mutex<Option<Vec<ImageType>>>>,
     pub time_req: Time,
     pub depth_tile: u8,
+    // The frame of the allsky if the HiPS is a cube
+    pub frame: Option<u32>,
 
     pub hips_cdid: CreatorDid,
     url: Url,
@@ -337,6 +333,7 @@ impl<'a> From<&'a AllskyRequest> for Option<Allsky> {
             hips_cdid,
             depth_tile,
             url,
+            frame,
             ..
         } = request;
         if request.is_resolved() {
@@ -350,6 +347,7 @@ impl<'a> From<&'a AllskyRequest> for Option<Allsky> {
                 hips_cdid: hips_cdid.clone(),
                 url: url.clone(),
                 depth_tile: *depth_tile,
+                frame: *frame,
             })
         } else {
             None
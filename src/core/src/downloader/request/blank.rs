@@ -39,10 +39,8 @@ impl From<PixelMetadataRequest> for RequestType {
 }
 
 use super::Url;
-use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{RequestInit, RequestMode, Response};
 impl From<query::PixelMetadata> for PixelMetadataRequest {
     // Create a tile request associated to a HiPS
     fn from(query: query::PixelMetadata) -> Self {
@@ -51,26 +49,17 @@ impl From<query::PixelMetadata> for PixelMetadataRequest {
             url,
             hips_cdid,
             id,
+            options,
         } = query;
 
         let url_clone = url.clone();
 
         let channel = format.get_channel();
 
-        let window = web_sys::window().unwrap_abort();
         let request = match channel {
             ChannelType::R32F | ChannelType::R32I | ChannelType::R16I | ChannelType::R8UI => {
                 Request::new(async move {
-                    let opts = RequestInit::new();
-                    opts.set_method("GET");
-                    opts.set_mode(RequestMode::Cors);
-
-                    let request =
-                        web_sys::Request::new_with_str_and_init(&url_clone, &opts).unwrap_abort();
-                    let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-                    // `resp_value` is a `Response` object.
-                    debug_assert!(resp_value.is_instance_of::<Response>());
-                    let resp: Response = resp_value.dyn_into()?;
+                    let resp = options.fetch(&url_clone, None).await?;
                     // See https://github.com/MattiasBuelens/wasm-streams/blob/f6dacf58a8826dc67923ab4a3bae87635690ca64/examples/fetch_as_stream.rs#L25-L33
                     /*let raw_body = resp.body().ok_or(JsValue::from_str("Cannot extract readable stream"))?;
                     let body = ReadableStream::from_raw(raw_body.dyn_into()?);
@@ -88,6 +77,9 @@ impl From<query::PixelMetadata> for PixelMetadataRequest {
                         raw_bytes.set_len(num_bytes);
                     }
                     bytes_buffer.copy_to(&mut raw_bytes[..]);
+                    if crate::fits::is_compressed(&raw_bytes) {
+                        raw_bytes = crate::fits::decompress(&raw_bytes)?;
+                    }
 
                     let mut reader = Cursor::new(&raw_bytes[..]);
                     let Fits { hdu } = Fits::from_reader(&mut reader)
@@ -133,13 +125,12 @@ impl From<query::PixelMetadata> for PixelMetadataRequest {
 }
 
 use std::sync::{Arc, Mutex};
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PixelMetadata {
     pub value: Arc<Mutex<Option<Metadata>>>,
     pub hips_cdid: CreatorDid,
     pub url: String,
 }
-use crate::Abort;
 impl<'a> From<&'a PixelMetadataRequest> for Option<PixelMetadata> {
     fn from(request: &'a PixelMetadataRequest) -> Self {
         let PixelMetadataRequest {
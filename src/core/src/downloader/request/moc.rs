@@ -4,8 +4,9 @@ use crate::renderable::CreatorDid;
 use super::{Request, RequestType};
 
 use crate::healpix::coverage::Smoc;
+use crate::healpix::st_coverage::Tmoc;
 use moclib::deser::fits::MocType;
-use moclib::qty::Hpx;
+use moclib::qty::{Hpx, Time};
 
 pub struct MOCRequest {
     //pub id: QueryId,
@@ -22,9 +23,7 @@ impl From<MOCRequest> for RequestType {
 use super::Url;
 
 use moclib::deser::fits;
-use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{RequestInit, RequestMode, Response};
 
 use moclib::moc::range::op::convert::convert_to_u64;
 
@@ -39,8 +38,18 @@ pub fn from_fits_hpx<T: Idx>(moc: MocType<T, Hpx<T>, Cursor<&[u8]>>) -> Smoc {
     }
 }
 
+/// Convenient type for Time-MOCs
+pub fn from_fits_time<T: Idx>(moc: MocType<T, Time<T>, Cursor<&[u8]>>) -> Tmoc {
+    match moc {
+        MocType::Ranges(moc) => convert_to_u64::<T, Time<T>, _, Time<u64>>(moc).into_range_moc(),
+        MocType::Cells(moc) => {
+            convert_to_u64::<T, Time<T>, _, Time<u64>>(moc.into_cell_moc_iter().ranges())
+                .into_range_moc()
+        }
+    }
+}
+
 use crate::healpix::coverage::HEALPixCoverage;
-use crate::Abort;
 use moclib::deser::fits::MocIdxType;
 use moclib::deser::fits::MocQtyType;
 use moclib::idx::Idx;
@@ -54,21 +63,13 @@ impl From<query::Moc> for MOCRequest {
             url,
             params,
             hips_cdid,
+            options,
         } = query;
 
         let url_clone = url.clone();
 
-        let window = web_sys::window().unwrap_abort();
         let request = Request::new(async move {
-            let opts = RequestInit::new();
-            opts.set_method("GET");
-            opts.set_mode(RequestMode::Cors);
-
-            let request = web_sys::Request::new_with_str_and_init(&url_clone, &opts).unwrap_abort();
-            let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-            // `resp_value` is a `Response` object.
-            debug_assert!(resp_value.is_instance_of::<Response>());
-            let resp: Response = resp_value.dyn_into()?;
+            let resp = options.fetch(&url_clone, None).await?;
             let array_buffer = JsFuture::from(resp.array_buffer()?).await?;
 
             let bytes_buf = js_sys::Uint8Array::new(&array_buffer);
@@ -103,6 +104,7 @@ impl From<query::Moc> for MOCRequest {
 }
 
 use std::sync::{Arc, Mutex};
+#[derive(Clone)]
 pub struct Moc {
     pub moc: Arc<Mutex<Option<HEALPixCoverage>>>,
     pub params: al_api::moc::MOC,
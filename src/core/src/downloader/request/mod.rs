@@ -7,7 +7,7 @@ pub mod tile;
 
 /* ------------------------------------- */
 
-use crate::time::Time;
+use crate::time::{DeltaTime, Time};
 use std::cell::Cell;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
@@ -15,6 +15,8 @@ pub type Url = String;
 pub struct Request<R> {
     data: Arc<Mutex<Option<R>>>,
     time_request: Time,
+    // Time at which the request has been resolved
+    time_resolved: Rc<Cell<Time>>,
     // Flag telling if the tile has been copied so that
     // the HtmlImageElement can be reused to download another tile
     //ready: bool,
@@ -24,9 +26,50 @@ pub struct Request<R> {
 #[derive(Clone, Copy, PartialEq)]
 pub enum ResolvedStatus {
     NotResolved,
+    // The resource does not exist on the server
+    Missing,
+    // The request failed but may succeed if done again later
+    TransientFailure,
     Failed,
     Found,
 }
+
+/// The reason why a request failed
+pub enum RequestError {
+    /// The server answered that the resource does not exist (HTTP 404)
+    NotFound,
+    /// A network error, a timeout or a server error (HTTP 408, 429, 5xx)
+    Transient(JsValue),
+    /// Any other error
+    Other(JsValue),
+}
+
+impl From<JsValue> for RequestError {
+    fn from(err: JsValue) -> Self {
+        RequestError::Other(err)
+    }
+}
+
+impl From<RequestError> for JsValue {
+    fn from(err: RequestError) -> Self {
+        match err {
+            RequestError::NotFound => JsValue::from_str("Not found"),
+            RequestError::Transient(err) | RequestError::Other(err) => err,
+        }
+    }
+}
+
+impl RequestError {
+    /// Classify the failure of a HTTP request given its response status
+    pub fn from_status(status: u16) -> Self {
+        let err = JsValue::from_str(&format!("Response status code {}", status));
+        match status {
+            404 => RequestError::NotFound,
+            408 | 429 | 500..=599 => RequestError::Transient(err),
+            _ => RequestError::Other(err),
+        }
+    }
+}
 use crate::Abort;
 use std::future::Future;
 use wasm_bindgen::JsValue;
@@ -37,25 +80,41 @@ where
     pub fn new<F>(f: F) -> Self
     where
         F: Future<Output = Result<R, JsValue>> + 'static,
+    {
+        Self::new_classified(async move { f.await.map_err(RequestError::Other) })
+    }
+
+    /// Create a request whose failures are classified
+    ///
+    /// Contrary to `new`, it tells apart missing resources and transient errors
+    pub fn new_classified<F>(f: F) -> Self
+    where
+        F: Future<Output = Result<R, RequestError>> + 'static,
     {
         // By default, we say the tile is available to be reused
         let resolved = Rc::new(Cell::new(ResolvedStatus::NotResolved));
         let time_request = Time::now();
+        let time_resolved = Rc::new(Cell::new(time_request));
 
         let data = Arc::new(Mutex::new(None));
 
         {
             let data_cloned = data.clone();
             let resolved_cloned = resolved.clone();
+            let time_resolved_cloned = time_resolved.clone();
 
             let fut = async move {
-                let resp = f.await;
-                if let Ok(resp) = resp {
-                    *(data_cloned.lock().unwrap_abort()) = Some(resp);
-                    resolved_cloned.set(ResolvedStatus::Found);
-                } else {
-                    resolved_cloned.set(ResolvedStatus::Failed);
-                }
+                let status = match f.await {
+                    Ok(resp) => {
+                        *(data_cloned.lock().unwrap_abort()) = Some(resp);
+                        ResolvedStatus::Found
+                    }
+                    Err(RequestError::NotFound) => ResolvedStatus::Missing,
+                    Err(RequestError::Transient(_)) => ResolvedStatus::TransientFailure,
+                    Err(RequestError::Other(_)) => ResolvedStatus::Failed,
+                };
+                time_resolved_cloned.set(Time::now());
+                resolved_cloned.set(status);
 
                 Ok(JsValue::from_bool(true))
             };
@@ -67,12 +126,17 @@ where
             data,
             resolved,
             time_request,
+            time_resolved,
         }
     }
 
+    /// The time taken by the request to be resolved
+    pub fn duration(&self) -> DeltaTime {
+        self.time_resolved.get() - self.time_request
+    }
+
     pub fn is_resolved(&self) -> bool {
-        let resolved = self.resolve_status();
-        resolved == ResolvedStatus::Found || resolved == ResolvedStatus::Failed
+        self.resolve_status() != ResolvedStatus::NotResolved
     }
 
     pub fn resolve_status(&self) -> ResolvedStatus {
@@ -120,6 +184,7 @@ use allsky::Allsky;
 use blank::PixelMetadata;
 use moc::Moc;
 use tile::Tile;
+#[derive(Clone)]
 pub enum Resource {
     Tile(Tile),
     Allsky(Allsky),
@@ -127,6 +192,30 @@ pub enum Resource {
     Moc(Moc),
 }
 
+use crate::lru_cache::ByteSize;
+impl ByteSize for Resource {
+    fn byte_size(&self) -> usize {
+        match self {
+            Resource::Tile(tile) => tile
+                .image
+                .lock()
+                .unwrap_abort()
+                .as_ref()
+                .map_or(0, |image| image.byte_size()),
+            Resource::Allsky(allsky) => allsky
+                .image
+                .lock()
+                .unwrap_abort()
+                .as_ref()
+                .map_or(0, |images| {
+                    images.iter().map(|image| image.byte_size()).sum()
+                }),
+            // Small compared to the images
+            Resource::PixelMetadata(_) | Resource::Moc(_) => 0,
+        }
+    }
+}
+
 impl Resource {
     pub fn id(&self) -> &String {
         match self {
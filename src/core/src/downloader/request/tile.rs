@@ -6,7 +6,7 @@ use crate::downloader::query;
 use al_core::image::ImageType;
 
 use super::Url;
-use super::{Request, RequestType};
+use super::{Request, RequestError, RequestType, ResolvedStatus};
 use crate::downloader::QueryId;
 
 pub struct TileRequest {
@@ -14,8 +14,13 @@ pub struct TileRequest {
 
     cell: HEALPixCell,
     hips_cdid: CreatorDid,
+    // The mirror the tile is requested from
+    hips_url: Url,
     url: Url,
     format: ImageFormatType,
+    frame: Option<u32>,
+    // Cancels the request when the tile leaves the view
+    abort_controller: Option<AbortController>,
 
     request: Request<ImageType>,
 }
@@ -26,30 +31,9 @@ impl From<TileRequest> for RequestType {
     }
 }
 
-async fn query_html_image(url: &str) -> Result<web_sys::HtmlImageElement, JsValue> {
-    let image = web_sys::HtmlImageElement::new().unwrap_abort();
-    let image_cloned = image.clone();
-
-    let promise = js_sys::Promise::new(
-        &mut (Box::new(move |resolve, reject| {
-            // Ask for CORS permissions
-            image_cloned.set_cross_origin(Some(""));
-            image_cloned.set_onload(Some(&resolve));
-            image_cloned.set_onerror(Some(&reject));
-            image_cloned.set_src(&url);
-        }) as Box<dyn FnMut(js_sys::Function, js_sys::Function)>),
-    );
-
-    let _ = JsFuture::from(promise).await?;
-
-    Ok(image)
-}
-
 use al_core::image::html::HTMLImage;
-use wasm_bindgen::JsCast;
-use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{RequestInit, RequestMode, Response};
+use web_sys::AbortController;
 impl From<query::Tile> for TileRequest {
     // Create a tile request associated to a HiPS
     fn from(query: query::Tile) -> Self {
@@ -58,15 +42,21 @@ impl From<query::Tile> for TileRequest {
             cell,
             url,
             hips_cdid,
+            hips_url,
             id,
+            frame,
+            options,
         } = query;
 
         let url_clone = url.clone();
         let channel = format.get_channel();
 
-        let window = web_sys::window().unwrap_abort();
+        let abort_controller = AbortController::new().ok();
+        let signal = abort_controller
+            .as_ref()
+            .map(|controller| controller.signal());
         let request = match channel {
-            ChannelType::RGB8U => Request::new(async move {
+            ChannelType::RGB8U => Request::new_classified(async move {
                 /*let mut opts = RequestInit::new();
                 opts.method("GET");
                 opts.mode(RequestMode::Cors);
@@ -95,13 +85,15 @@ impl From<query::Tile> for TileRequest {
                 Ok(ImageType::RawRgb8u { image })
                 */
                 // HTMLImageElement
-                let image = query_html_image(&url_clone).await?;
+                let image = options
+                    .fetch_html_image(&url_clone, signal.as_ref())
+                    .await?;
                 // The image has been resolved
-                Ok(ImageType::HTMLImageRgb8u {
+                Ok::<_, RequestError>(ImageType::HTMLImageRgb8u {
                     image: HTMLImage::<RGB8U>::new(image),
                 })
             }),
-            ChannelType::RGBA8U => Request::new(async move {
+            ChannelType::RGBA8U => Request::new_classified(async move {
                 /*let mut opts = RequestInit::new();
                 opts.method("GET");
                 opts.mode(RequestMode::Cors);
@@ -130,9 +122,11 @@ impl From<query::Tile> for TileRequest {
                 Ok(ImageType::RawRgba8u { image })
                 */
                 // HTMLImageElement
-                let image = query_html_image(&url_clone).await?;
+                let image = options
+                    .fetch_html_image(&url_clone, signal.as_ref())
+                    .await?;
                 // The image has been resolved
-                Ok(ImageType::HTMLImageRgba8u {
+                Ok::<_, RequestError>(ImageType::HTMLImageRgba8u {
                     image: HTMLImage::<RGBA8U>::new(image),
                 })
             }),
@@ -140,17 +134,8 @@ impl From<query::Tile> for TileRequest {
             | ChannelType::R64F
             | ChannelType::R32I
             | ChannelType::R16I
-            | ChannelType::R8UI => Request::new(async move {
-                let opts = RequestInit::new();
-                opts.set_method("GET");
-                opts.set_mode(RequestMode::Cors);
-
-                let request =
-                    web_sys::Request::new_with_str_and_init(&url_clone, &opts).unwrap_abort();
-                let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-                // `resp_value` is a `Response` object.
-                debug_assert!(resp_value.is_instance_of::<Response>());
-                let resp: Response = resp_value.dyn_into()?;
+            | ChannelType::R8UI => Request::new_classified(async move {
+                let resp = options.fetch(&url_clone, signal.as_ref()).await?;
                 // See https://github.com/MattiasBuelens/wasm-streams/blob/f6dacf58a8826dc67923ab4a3bae87635690ca64/examples/fetch_as_stream.rs#L25-L33
                 /*let raw_body = resp.body().ok_or(JsValue::from_str("Cannot extract readable stream"))?;
                 let body = ReadableStream::from_raw(raw_body.dyn_into()?);
@@ -160,14 +145,21 @@ impl From<query::Tile> for TileRequest {
                 let image = Fits::new(reader).await?;
                 */
                 if resp.ok() {
-                    let array_buffer = JsFuture::from(resp.array_buffer()?).await?;
-                    let raw_bytes = js_sys::Uint8Array::new(&array_buffer);
+                    let array_buffer = JsFuture::from(resp.array_buffer()?)
+                        .await
+                        .map_err(RequestError::Transient)?;
+                    let mut raw_bytes = js_sys::Uint8Array::new(&array_buffer);
+
+                    // Tiles in the fits.fz format are tile-compressed FITS files
+                    let leading_bytes = raw_bytes.subarray(0, raw_bytes.length().min(8 * 2880));
+                    if crate::fits::is_compressed(&leading_bytes.to_vec()) {
+                        let bytes = crate::fits::decompress(&raw_bytes.to_vec())?;
+                        raw_bytes = js_sys::Uint8Array::from(&bytes[..]);
+                    }
 
                     Ok(ImageType::FitsImage { raw_bytes })
                 } else {
-                    Err(JsValue::from_str(
-                        "Response status code not between 200-299.",
-                    ))
+                    Err(RequestError::from_status(resp.status()))
                 }
             }),
             _ => todo!(),
@@ -178,19 +170,62 @@ impl From<query::Tile> for TileRequest {
             format,
             id,
             hips_cdid,
+            hips_url,
             url,
+            frame,
+            abort_controller,
             request,
         }
     }
 }
 
-use crate::time::Time;
+impl TileRequest {
+    pub fn resolve_status(&self) -> ResolvedStatus {
+        self.request.resolve_status()
+    }
+
+    /// Cancel the request
+    pub fn abort(&self) {
+        if let Some(controller) = &self.abort_controller {
+            controller.abort();
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_hips_cdid(&self) -> &CreatorDid {
+        &self.hips_cdid
+    }
+
+    #[inline(always)]
+    pub fn cell(&self) -> &HEALPixCell {
+        &self.cell
+    }
+
+    /// The mirror the tile is requested from, `None` for a tile read from a local file
+    pub fn get_mirror(&self) -> Option<&Url> {
+        if self.url.starts_with(&self.hips_url) {
+            Some(&self.hips_url)
+        } else {
+            None
+        }
+    }
+
+    /// The time taken by the request to be resolved
+    pub fn duration(&self) -> DeltaTime {
+        self.request.duration()
+    }
+}
+
+use crate::time::{DeltaTime, Time};
 use std::sync::{Arc, Mutex};
+#[derive(Clone)]
 pub struct Tile {
     pub image: Arc<Mutex<Option<ImageType>>>,
     pub time_req: Time,
     pub cell: HEALPixCell,
     pub format: ImageFormatType,
+    // The frame of the tile if the HiPS is a cube
+    pub frame: Option<u32>,
     hips_cdid: CreatorDid,
     url: Url,
 }
@@ -231,6 +266,7 @@ impl<'a> From<&'a TileRequest> for Option<Tile> {
             hips_cdid,
             url,
             format,
+            frame,
             ..
         } = request;
         if request.is_resolved() {
@@ -245,6 +281,7 @@ impl<'a> From<&'a TileRequest> for Option<Tile> {
                 hips_cdid: hips_cdid.clone(),
                 url: url.clone(),
                 format: *format,
+                frame: *frame,
             })
         } else {
             None
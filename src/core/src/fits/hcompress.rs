@@ -0,0 +1,513 @@
+//! HCOMPRESS_1 decompression
+//!
+//! This is a port of the decoder of the H-transform compression scheme
+//! as implemented in CFITSIO. Smoothing during the inversion is not supported.
+
+use super::Error;
+
+const MAGIC: [u8; 2] = [0xDD, 0x99];
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn read_bit(&mut self) -> Result<u8, Error> {
+        let byte = self.bytes.get(self.pos / 8).ok_or_else(end_of_stream)?;
+        let bit = (byte >> (7 - (self.pos % 8))) & 1;
+        self.pos += 1;
+
+        Ok(bit)
+    }
+
+    fn read(&mut self, num_bits: u32) -> Result<u8, Error> {
+        let mut v = 0;
+        for _ in 0..num_bits {
+            v = (v << 1) | self.read_bit()?;
+        }
+
+        Ok(v)
+    }
+
+    fn read_nybble(&mut self) -> Result<u8, Error> {
+        self.read(4)
+    }
+
+    // Skip the remaining bits of the current byte
+    fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+
+    fn read_huffman(&mut self) -> Result<u8, Error> {
+        let c = self.read(3)?;
+        if c < 4 {
+            return Ok(1 << c);
+        }
+
+        let c = (c << 1) | self.read_bit()?;
+        match c {
+            8 => return Ok(3),
+            9 => return Ok(5),
+            10 => return Ok(10),
+            11 => return Ok(12),
+            12 => return Ok(15),
+            _ => (),
+        }
+
+        let c = (c << 1) | self.read_bit()?;
+        match c {
+            26 => return Ok(6),
+            27 => return Ok(7),
+            28 => return Ok(9),
+            29 => return Ok(11),
+            30 => return Ok(13),
+            _ => (),
+        }
+
+        let c = (c << 1) | self.read_bit()?;
+        Ok(if c == 62 { 0 } else { 14 })
+    }
+}
+
+fn end_of_stream() -> Error {
+    Error::Format {
+        message: "Unexpected end of the HCOMPRESS compressed stream".to_string(),
+    }
+}
+
+fn read_be<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N], Error> {
+    let mut b = [0; N];
+    b.copy_from_slice(bytes.get(offset..(offset + N)).ok_or_else(end_of_stream)?);
+
+    Ok(b)
+}
+
+// ceil(log2(n))
+fn log2_ceil(n: usize) -> u32 {
+    let mut log2n = 0;
+    while (1 << log2n) < n {
+        log2n += 1;
+    }
+
+    log2n
+}
+
+/// Decode an image compressed with HCOMPRESS
+///
+/// Returns the image whose first axis is the fastest varying one
+pub fn decompress(bytes: &[u8]) -> Result<Vec<i64>, Error> {
+    if bytes.get(..2) != Some(&MAGIC[..]) {
+        return Err(Error::Format {
+            message: "Bad HCOMPRESS magic number".to_string(),
+        });
+    }
+
+    let nx = i32::from_be_bytes(read_be(bytes, 2)?).max(0) as usize;
+    let ny = i32::from_be_bytes(read_be(bytes, 6)?).max(0) as usize;
+    let scale = i32::from_be_bytes(read_be(bytes, 10)?) as i64;
+    let sum_all = i64::from_be_bytes(read_be(bytes, 14)?);
+    let num_bitplanes: [u8; 3] = read_be(bytes, 22)?;
+
+    let mut a = vec![0i64; nx * ny];
+    let mut reader = BitReader::new(&bytes[25..]);
+
+    // Decode the bit planes of the four quadrants
+    let (nx2, ny2) = (nx.div_ceil(2), ny.div_ceil(2));
+    for (offset, nqx, nqy, num_bitplanes) in [
+        (0, nx2, ny2, num_bitplanes[0]),
+        (ny2, nx2, ny / 2, num_bitplanes[1]),
+        (ny * nx2, nx / 2, ny2, num_bitplanes[1]),
+        (ny * nx2 + ny2, nx / 2, ny / 2, num_bitplanes[2]),
+    ] {
+        qtree_decode(&mut reader, &mut a[offset..], ny, nqx, nqy, num_bitplanes)?;
+    }
+
+    if reader.read_nybble()? != 0 {
+        return Err(Error::Format {
+            message: "Bad HCOMPRESS bit plane values".to_string(),
+        });
+    }
+
+    // Sign bits of the non zero values
+    reader.align();
+    for v in a.iter_mut().filter(|v| **v != 0) {
+        if reader.read_bit()? == 1 {
+            *v = -*v;
+        }
+    }
+
+    if let Some(a0) = a.first_mut() {
+        *a0 = sum_all;
+    }
+
+    if scale > 1 {
+        for v in a.iter_mut() {
+            *v *= scale;
+        }
+    }
+
+    hinv(&mut a, nx, ny);
+
+    Ok(a)
+}
+
+fn qtree_decode(
+    reader: &mut BitReader,
+    a: &mut [i64],
+    n: usize,
+    nqx: usize,
+    nqy: usize,
+    num_bitplanes: u8,
+) -> Result<(), Error> {
+    let log2n = log2_ceil(nqx.max(nqy));
+    let (nqx2, nqy2) = (nqx.div_ceil(2), nqy.div_ceil(2));
+    let mut scratch = vec![0u8; (nqx2 * nqy2).max(1)];
+
+    for bit in (0..num_bitplanes).rev() {
+        match reader.read_nybble()? {
+            // The bit plane has been written directly
+            0 => {
+                for s in scratch.iter_mut().take(nqx2 * nqy2) {
+                    *s = reader.read_nybble()?;
+                }
+            }
+            // The bit plane has been quadtree coded
+            0xF => {
+                scratch[0] = reader.read_huffman()?;
+
+                let (mut nx, mut ny) = (1, 1);
+                let (mut nfx, mut nfy) = (nqx, nqy);
+                let mut c = 1 << log2n;
+                for _ in 1..log2n {
+                    // Generate the sequence n[k-1] = (n[k]+1)/2 where n[log2n] = nqx or nqy
+                    c >>= 1;
+                    nx <<= 1;
+                    ny <<= 1;
+                    if nfx <= c {
+                        nx -= 1;
+                    } else {
+                        nfx -= c;
+                    }
+                    if nfy <= c {
+                        ny -= 1;
+                    } else {
+                        nfy -= c;
+                    }
+
+                    qtree_expand(reader, &mut scratch, nx, ny)?;
+                }
+            }
+            _ => {
+                return Err(Error::Format {
+                    message: "Bad HCOMPRESS quadtree format code".to_string(),
+                })
+            }
+        }
+
+        qtree_bitins(&scratch, nqx, nqy, a, n, bit);
+    }
+
+    Ok(())
+}
+
+// Expand the 4-bit codes of a[(nx+1)/2, (ny+1)/2] to 2x2 pixels of a[nx, ny]
+// and read the new codes of the non zero pixels
+fn qtree_expand(reader: &mut BitReader, a: &mut [u8], nx: usize, ny: usize) -> Result<(), Error> {
+    qtree_copy(a, nx, ny);
+
+    for v in a[..(nx * ny)].iter_mut().rev() {
+        if *v != 0 {
+            *v = reader.read_huffman()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn qtree_copy(a: &mut [u8], nx: usize, ny: usize) {
+    let (nx2, ny2) = (nx.div_ceil(2), ny.div_ceil(2));
+
+    // Copy the codes to the even positions, starting at the end as
+    // the source and destination share the same array
+    for i in (0..nx2).rev() {
+        for j in (0..ny2).rev() {
+            a[2 * (ny * i + j)] = a[ny2 * i + j];
+        }
+    }
+
+    // Expand each 2x2 block
+    for i in (0..nx).step_by(2) {
+        for j in (0..ny).step_by(2) {
+            let s00 = ny * i + j;
+            let code = a[s00];
+
+            a[s00] = (code >> 3) & 1;
+            if j + 1 < ny {
+                a[s00 + 1] = (code >> 2) & 1;
+            }
+            if i + 1 < nx {
+                a[s00 + ny] = (code >> 1) & 1;
+                if j + 1 < ny {
+                    a[s00 + ny + 1] = code & 1;
+                }
+            }
+        }
+    }
+}
+
+// Insert the 4-bit codes of a[(nx+1)/2, (ny+1)/2] into the bit plane `bit` of b[nx, ny]
+fn qtree_bitins(a: &[u8], nx: usize, ny: usize, b: &mut [i64], n: usize, bit: u8) {
+    let plane_val = 1i64 << bit;
+
+    let mut k = 0;
+    for i in (0..nx).step_by(2) {
+        for j in (0..ny).step_by(2) {
+            let s00 = n * i + j;
+            let code = a[k];
+
+            if code & 8 != 0 {
+                b[s00] |= plane_val;
+            }
+            if j + 1 < ny && code & 4 != 0 {
+                b[s00 + 1] |= plane_val;
+            }
+            if i + 1 < nx {
+                if code & 2 != 0 {
+                    b[s00 + n] |= plane_val;
+                }
+                if j + 1 < ny && code & 1 != 0 {
+                    b[s00 + n + 1] |= plane_val;
+                }
+            }
+
+            k += 1;
+        }
+    }
+}
+
+// Interleave the coefficients of the first and second halves of a[0], a[n2], ..., a[(n-1)*n2]
+fn unshuffle(a: &mut [i64], n: usize, n2: usize, tmp: &mut Vec<i64>) {
+    let nhalf = (n + 1) >> 1;
+
+    tmp.clear();
+    tmp.extend((nhalf..n).map(|i| a[n2 * i]));
+
+    for i in (0..nhalf).rev() {
+        a[2 * n2 * i] = a[n2 * i];
+    }
+    for (i, &v) in (1..n).step_by(2).zip(tmp.iter()) {
+        a[n2 * i] = v;
+    }
+}
+
+// Inverse H-transform of a[nx, ny]
+fn hinv(a: &mut [i64], nx: usize, ny: usize) {
+    let nmax = nx.max(ny);
+    let log2n = log2_ceil(nmax);
+    if log2n == 0 {
+        return;
+    }
+
+    let mut tmp = Vec::with_capacity(nmax.div_ceil(2));
+
+    // Masks and rounding values
+    let mut shift = 1;
+    let mut bit0: i64 = 1 << (log2n - 1);
+    let mut bit1 = bit0 << 1;
+    let bit2 = bit0 << 2;
+    let mut mask0 = -bit0;
+    let mut mask1 = mask0 << 1;
+    let mask2 = mask0 << 2;
+    let mut prnd0 = bit0 >> 1;
+    let mut prnd1 = bit1 >> 1;
+    let prnd2 = bit2 >> 1;
+    let mut nrnd0 = prnd0 - 1;
+    let mut nrnd1 = prnd1 - 1;
+    let nrnd2 = prnd2 - 1;
+
+    let round =
+        |v: i64, prnd: i64, nrnd: i64, mask: i64| (v + if v >= 0 { prnd } else { nrnd }) & mask;
+
+    // Round h0 to a multiple of bit2
+    a[0] = round(a[0], prnd2, nrnd2, mask2);
+
+    let (mut nxtop, mut nytop) = (1, 1);
+    let (mut nxf, mut nyf) = (nx, ny);
+    let mut c = 1 << log2n;
+    for k in (0..log2n).rev() {
+        // Generate the sequence ntop[k-1] = (ntop[k]+1)/2 where ntop[0] = nmax
+        c >>= 1;
+        nxtop <<= 1;
+        nytop <<= 1;
+        if nxf <= c {
+            nxtop -= 1;
+        } else {
+            nxf -= c;
+        }
+        if nyf <= c {
+            nytop -= 1;
+        } else {
+            nyf -= c;
+        }
+
+        // Double shift and fix nrnd0 (because prnd0 = 0) on the last pass
+        if k == 0 {
+            nrnd0 = 0;
+            shift = 2;
+        }
+
+        for i in 0..nxtop {
+            unshuffle(&mut a[(ny * i)..], nytop, 1, &mut tmp);
+        }
+        for j in 0..nytop {
+            unshuffle(&mut a[j..], nxtop, ny, &mut tmp);
+        }
+
+        let (oddx, oddy) = (nxtop % 2, nytop % 2);
+        let mut i = 0;
+        while i < nxtop - oddx {
+            let mut s00 = ny * i;
+            let mut s10 = s00 + ny;
+
+            let mut j = 0;
+            while j < nytop - oddy {
+                let h0 = a[s00];
+                // Round hx and hy to a multiple of bit1, hc to a multiple of bit0
+                let hx = round(a[s10], prnd1, nrnd1, mask1);
+                let hy = round(a[s00 + 1], prnd1, nrnd1, mask1);
+                let hc = round(a[s10 + 1], prnd0, nrnd0, mask0);
+
+                // Propagate bit0 of hc to hx, hy
+                let lowbit0 = hc & bit0;
+                let hx = if hx >= 0 { hx - lowbit0 } else { hx + lowbit0 };
+                let hy = if hy >= 0 { hy - lowbit0 } else { hy + lowbit0 };
+
+                // Propagate bits 0 and 1 of hc, hx, hy to h0
+                let lowbit1 = (hc ^ hx ^ hy) & bit1;
+                let h0 = if h0 >= 0 {
+                    h0 + lowbit0 - lowbit1
+                } else if lowbit0 == 0 {
+                    h0 + lowbit1
+                } else {
+                    h0 + lowbit0 - lowbit1
+                };
+
+                a[s10 + 1] = (h0 + hx + hy + hc) >> shift;
+                a[s10] = (h0 + hx - hy - hc) >> shift;
+                a[s00 + 1] = (h0 - hx + hy - hc) >> shift;
+                a[s00] = (h0 - hx - hy + hc) >> shift;
+
+                s00 += 2;
+                s10 += 2;
+                j += 2;
+            }
+
+            if oddy == 1 {
+                // Last element of a row of odd length
+                let hx = round(a[s10], prnd1, nrnd1, mask1);
+                let lowbit1 = hx & bit1;
+                let h0 = a[s00];
+                let h0 = if h0 >= 0 { h0 - lowbit1 } else { h0 + lowbit1 };
+
+                a[s10] = (h0 + hx) >> shift;
+                a[s00] = (h0 - hx) >> shift;
+            }
+
+            i += 2;
+        }
+
+        if oddx == 1 {
+            // Last row of a column of odd length
+            let mut s00 = ny * i;
+
+            let mut j = 0;
+            while j < nytop - oddy {
+                let hy = round(a[s00 + 1], prnd1, nrnd1, mask1);
+                let lowbit1 = hy & bit1;
+                let h0 = a[s00];
+                let h0 = if h0 >= 0 { h0 - lowbit1 } else { h0 + lowbit1 };
+
+                a[s00 + 1] = (h0 + hy) >> shift;
+                a[s00] = (h0 - hy) >> shift;
+
+                s00 += 2;
+                j += 2;
+            }
+
+            if oddy == 1 {
+                // Corner element
+                a[s00] >>= shift;
+            }
+        }
+
+        // Divide the masks and rounding values by 2
+        bit1 = bit0;
+        bit0 >>= 1;
+        mask1 = mask0;
+        mask0 >>= 1;
+        prnd1 = prnd0;
+        prnd0 >>= 1;
+        nrnd1 = nrnd0;
+        nrnd0 = prnd0 - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompress;
+
+    fn stream(nx: i32, ny: i32, sum_all: i64, num_bitplanes: [u8; 3], codes: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0xDD, 0x99];
+        bytes.extend_from_slice(&nx.to_be_bytes());
+        bytes.extend_from_slice(&ny.to_be_bytes());
+        bytes.extend_from_slice(&0i32.to_be_bytes());
+        bytes.extend_from_slice(&sum_all.to_be_bytes());
+        bytes.extend_from_slice(&num_bitplanes);
+        bytes.extend_from_slice(codes);
+
+        bytes
+    }
+
+    #[test]
+    fn constant_image() {
+        // No bit planes, only the EOF nybble and the sum of all the pixels
+        let bytes = stream(2, 2, 28, [0, 0, 0], &[0x00]);
+        assert_eq!(decompress(&bytes).unwrap(), vec![7; 4]);
+    }
+
+    #[test]
+    fn direct_bit_planes() {
+        // The H-transform of [1, 2, 3, 4] is h0 = 12, hy = 2, hx = 4, hc = 0
+        // hy and hx are written directly on 3 bit planes followed by the EOF nybble
+        // and the (positive) sign bits
+        let codes = [0x00, 0x08, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00];
+        let bytes = stream(2, 2, 12, [0, 3, 0], &codes);
+        assert_eq!(decompress(&bytes).unwrap(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn non_square_image() {
+        // The stream gives the number of rows (nx) then the length of a row (ny).
+        // The image of 2 rows [1, 1, 1, 1] and [3, 3, 3, 3] is transformed into
+        // [16, 0, 0, 0] and [4, 4, 0, 0], the [4, 4] quadrant being written directly
+        // on 3 bit planes
+        let codes = [0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x00];
+        let bytes = stream(2, 4, 16, [0, 3, 0], &codes);
+        assert_eq!(decompress(&bytes).unwrap(), vec![1, 1, 1, 1, 3, 3, 3, 3]);
+
+        let bytes = stream(2, 4, 40, [0, 0, 0], &[0x00]);
+        assert_eq!(decompress(&bytes).unwrap(), vec![5; 8]);
+        let bytes = stream(4, 2, 40, [0, 0, 0], &[0x00]);
+        assert_eq!(decompress(&bytes).unwrap(), vec![5; 8]);
+    }
+
+    #[test]
+    fn bad_magic() {
+        assert!(decompress(&[0x00; 32]).is_err());
+    }
+}
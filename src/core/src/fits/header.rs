@@ -0,0 +1,183 @@
+//! FITS header cards reading and writing
+
+pub const CARD_SIZE: usize = 80;
+pub const BLOCK_SIZE: usize = 2880;
+
+pub type Card = [u8; CARD_SIZE];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Logical(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+/// The cards of a FITS header, the END card excluded
+#[derive(Debug, Clone)]
+pub struct Header {
+    cards: Vec<Card>,
+}
+
+impl Header {
+    /// Parse the header starting at the beginning of `bytes`
+    ///
+    /// Returns the header and the number of bytes it occupies (padding included)
+    /// or `None` if `bytes` does not contain a complete header
+    pub fn parse(bytes: &[u8]) -> Option<(Self, usize)> {
+        let mut cards = vec![];
+
+        for (idx, card) in bytes.chunks_exact(CARD_SIZE).enumerate() {
+            let mut c = [b' '; CARD_SIZE];
+            c.copy_from_slice(card);
+
+            if idx == 0 && !(key(&c) == "SIMPLE" || key(&c) == "XTENSION") {
+                return None;
+            }
+
+            if key(&c) == "END" {
+                let num_bytes = ((idx + 1) * CARD_SIZE).div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+                return Some((Header { cards }, num_bytes));
+            }
+
+            cards.push(c);
+        }
+
+        None
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn get(&self, k: &str) -> Option<Value> {
+        self.cards.iter().find(|c| key(c) == k).and_then(value)
+    }
+
+    pub fn get_i64(&self, k: &str) -> Option<i64> {
+        match self.get(k)? {
+            Value::Integer(v) => Some(v),
+            Value::Float(v) => Some(v as i64),
+            _ => None,
+        }
+    }
+
+    pub fn get_f64(&self, k: &str) -> Option<f64> {
+        match self.get(k)? {
+            Value::Integer(v) => Some(v as f64),
+            Value::Float(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_str(&self, k: &str) -> Option<String> {
+        match self.get(k)? {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, k: &str) -> Option<bool> {
+        match self.get(k)? {
+            Value::Logical(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes of the data unit, padding excluded
+    pub fn data_num_bytes(&self) -> usize {
+        let naxis = self.get_i64("NAXIS").unwrap_or(0);
+        if naxis <= 0 {
+            return 0;
+        }
+
+        let num_items = (1..=naxis)
+            .map(|i| self.get_i64(&format!("NAXIS{}", i)).unwrap_or(0).max(0) as usize)
+            .product::<usize>();
+        let bitpix = self.get_i64("BITPIX").unwrap_or(8).unsigned_abs() as usize;
+        let pcount = self.get_i64("PCOUNT").unwrap_or(0).max(0) as usize;
+        let gcount = self.get_i64("GCOUNT").unwrap_or(1).max(0) as usize;
+
+        bitpix / 8 * gcount * (pcount + num_items)
+    }
+}
+
+/// The keyword of a card
+pub fn key(card: &Card) -> &str {
+    std::str::from_utf8(&card[..8]).unwrap_or("").trim_end()
+}
+
+fn value(card: &Card) -> Option<Value> {
+    if &card[8..10] != b"= " {
+        return None;
+    }
+
+    let text = card[10..].iter().map(|&b| b as char).collect::<String>();
+    let text = text.trim_start();
+
+    if let Some(s) = text.strip_prefix('\'') {
+        // Quotes are escaped by doubling them
+        let mut v = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            v.push(c);
+        }
+
+        return Some(Value::String(v.trim_end().to_string()));
+    }
+
+    let v = text.split('/').next().unwrap_or("").trim();
+    match v {
+        "T" => Some(Value::Logical(true)),
+        "F" => Some(Value::Logical(false)),
+        _ => v
+            .parse::<i64>()
+            .map(Value::Integer)
+            .or_else(|_| v.replace(['D', 'd'], "E").parse::<f64>().map(Value::Float))
+            .ok(),
+    }
+}
+
+/// Create a card whose value is written in the fixed format
+pub fn card(k: &str, v: &Value) -> Card {
+    let v = match v {
+        Value::Logical(true) => format!("{:>20}", "T"),
+        Value::Logical(false) => format!("{:>20}", "F"),
+        Value::Integer(v) => format!("{:>20}", v),
+        Value::Float(v) => format!("{:>20}", format!("{:E}", v)),
+        Value::String(v) => format!("{:<20}", format!("'{:<8}'", v.replace('\'', "''"))),
+    };
+
+    let mut c = [b' '; CARD_SIZE];
+    let text = format!("{:<8}= {}", k, v);
+    let n = text.len().min(CARD_SIZE);
+    c[..n].copy_from_slice(&text.as_bytes()[..n]);
+
+    c
+}
+
+/// Write the cards of a header followed by the END card and the padding
+pub fn write(cards: &[Card], out: &mut Vec<u8>) {
+    for c in cards {
+        out.extend_from_slice(c);
+    }
+
+    let mut end = [b' '; CARD_SIZE];
+    end[..3].copy_from_slice(b"END");
+    out.extend_from_slice(&end);
+
+    pad(out, b' ');
+}
+
+/// Pad to a multiple of the FITS block size
+pub fn pad(out: &mut Vec<u8>, byte: u8) {
+    let len = out.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+    out.resize(len, byte);
+}
@@ -0,0 +1,676 @@
+//! Decompression of an image stored in a binary table with the tiled image convention
+
+use std::convert::TryInto;
+use std::io::Read;
+
+use super::header::{self, Card, Header, Value};
+use super::quantize::{self, Method};
+use super::{hcompress, rice, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    Rice1,
+    Gzip1,
+    Gzip2,
+    Hcompress1,
+    NoCompress,
+}
+
+impl Compression {
+    fn from_zcmptype(zcmptype: &str) -> Result<Self, Error> {
+        match zcmptype {
+            "RICE_1" | "RICE_ONE" => Ok(Compression::Rice1),
+            "GZIP_1" => Ok(Compression::Gzip1),
+            "GZIP_2" => Ok(Compression::Gzip2),
+            "HCOMPRESS_1" => Ok(Compression::Hcompress1),
+            "NOCOMPRESS" => Ok(Compression::NoCompress),
+            _ => Err(Error::Unsupported {
+                message: format!("{} compressed images are not supported", zcmptype),
+            }),
+        }
+    }
+}
+
+/// A column of the binary table
+#[derive(Debug)]
+struct Column {
+    // Byte offset of the column in a row
+    offset: usize,
+    // Data type code of the field (or of the array for variable length arrays)
+    code: char,
+    // Whether it is a variable length array descriptor (P or Q)
+    descriptor: Option<char>,
+}
+
+impl Column {
+    fn parse(tform: &str, offset: usize) -> Result<(Self, usize), Error> {
+        let tform = tform.trim();
+        let num_digits = tform.chars().take_while(|c| c.is_ascii_digit()).count();
+        let repeat = tform[..num_digits].parse::<usize>().unwrap_or(1);
+
+        let mut codes = tform[num_digits..].chars();
+        let code = codes.next().ok_or_else(|| Error::Format {
+            message: format!("Invalid TFORM {}", tform),
+        })?;
+
+        let (column, width) = match code {
+            'P' | 'Q' => {
+                let elem = codes.next().unwrap_or('B');
+                let width = if code == 'P' { 8 } else { 16 };
+                (
+                    Column {
+                        offset,
+                        code: elem,
+                        descriptor: Some(code),
+                    },
+                    width * repeat,
+                )
+            }
+            'X' => (
+                Column {
+                    offset,
+                    code,
+                    descriptor: None,
+                },
+                repeat.div_ceil(8),
+            ),
+            _ => {
+                let width = type_width(code).ok_or_else(|| Error::Format {
+                    message: format!("Invalid TFORM {}", tform),
+                })?;
+                (
+                    Column {
+                        offset,
+                        code,
+                        descriptor: None,
+                    },
+                    width * repeat,
+                )
+            }
+        };
+
+        Ok((column, width))
+    }
+}
+
+fn type_width(code: char) -> Option<usize> {
+    match code {
+        'L' | 'B' | 'A' => Some(1),
+        'I' => Some(2),
+        'J' | 'E' => Some(4),
+        'K' | 'D' | 'C' => Some(8),
+        'M' => Some(16),
+        _ => None,
+    }
+}
+
+/// The decompressed values of a tile
+enum Samples {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+}
+
+impl Samples {
+    fn len(&self) -> usize {
+        match self {
+            Samples::Int(v) => v.len(),
+            Samples::Float(v) => v.len(),
+        }
+    }
+
+    // Decode big endian values stored on `width` bytes
+    fn from_be_bytes(bytes: &[u8], width: usize, float: bool) -> Result<Self, Error> {
+        let samples = match (float, width) {
+            (true, 4) => Samples::Float(
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64)
+                    .collect(),
+            ),
+            (true, 8) => Samples::Float(
+                bytes
+                    .chunks_exact(8)
+                    .map(|b| f64::from_be_bytes(b.try_into().unwrap()))
+                    .collect(),
+            ),
+            (false, 1) => Samples::Int(bytes.iter().map(|&b| b as i64).collect()),
+            (false, 2) => Samples::Int(
+                bytes
+                    .chunks_exact(2)
+                    .map(|b| i16::from_be_bytes([b[0], b[1]]) as i64)
+                    .collect(),
+            ),
+            (false, 4) => Samples::Int(
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as i64)
+                    .collect(),
+            ),
+            (false, 8) => Samples::Int(
+                bytes
+                    .chunks_exact(8)
+                    .map(|b| i64::from_be_bytes(b.try_into().unwrap()))
+                    .collect(),
+            ),
+            _ => {
+                return Err(Error::Format {
+                    message: format!("Invalid tile values of {} bytes", width),
+                })
+            }
+        };
+
+        Ok(samples)
+    }
+}
+
+/// An image compressed in a binary table HDU
+struct CompressedImage<'a> {
+    header: &'a Header,
+    data: &'a [u8],
+
+    // Number of bytes of a row
+    row_len: usize,
+    num_rows: usize,
+    heap_offset: usize,
+    columns: Vec<(String, Column)>,
+
+    zbitpix: i64,
+    naxes: Vec<usize>,
+    ztile: Vec<usize>,
+    compression: Compression,
+    block_size: usize,
+    bytepix: usize,
+    quantize: Method,
+    zdither0: i64,
+}
+
+impl<'a> CompressedImage<'a> {
+    fn new(header: &'a Header, data: &'a [u8]) -> Result<Self, Error> {
+        let missing = |key: &str| Error::Format {
+            message: format!("The {} keyword of the compressed image is missing", key),
+        };
+
+        let row_len = header.get_i64("NAXIS1").ok_or_else(|| missing("NAXIS1"))? as usize;
+        let num_rows = header.get_i64("NAXIS2").ok_or_else(|| missing("NAXIS2"))? as usize;
+        let heap_offset = header
+            .get_i64("THEAP")
+            .map(|h| h as usize)
+            .unwrap_or(row_len * num_rows);
+
+        let num_fields = header.get_i64("TFIELDS").unwrap_or(0);
+        let mut columns = vec![];
+        let mut offset = 0;
+        for i in 1..=num_fields {
+            let tform = header
+                .get_str(&format!("TFORM{}", i))
+                .ok_or_else(|| missing(&format!("TFORM{}", i)))?;
+            let name = header
+                .get_str(&format!("TTYPE{}", i))
+                .unwrap_or_default()
+                .to_uppercase();
+
+            let (column, width) = Column::parse(&tform, offset)?;
+            columns.push((name, column));
+            offset += width;
+        }
+
+        let zbitpix = header
+            .get_i64("ZBITPIX")
+            .ok_or_else(|| missing("ZBITPIX"))?;
+        let znaxis = header.get_i64("ZNAXIS").ok_or_else(|| missing("ZNAXIS"))?;
+        let naxes = (1..=znaxis)
+            .map(|i| {
+                let key = format!("ZNAXIS{}", i);
+                header
+                    .get_i64(&key)
+                    .map(|n| n.max(0) as usize)
+                    .ok_or_else(|| missing(&key))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let ztile = naxes
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| {
+                let default = if i == 0 { n } else { 1 };
+                header
+                    .get_i64(&format!("ZTILE{}", i + 1))
+                    .map(|t| (t.max(1) as usize).min(n.max(1)))
+                    .unwrap_or(default)
+            })
+            .collect::<Vec<_>>();
+
+        let compression = Compression::from_zcmptype(
+            &header
+                .get_str("ZCMPTYPE")
+                .ok_or_else(|| missing("ZCMPTYPE"))?,
+        )?;
+
+        // Compression parameters
+        // Integer images are RICE compressed on their pixel size, quantized ones on 4 bytes
+        let mut block_size = 32;
+        let mut bytepix = if zbitpix > 0 {
+            (zbitpix / 8) as usize
+        } else {
+            4
+        };
+        for i in 1.. {
+            let name = if let Some(name) = header.get_str(&format!("ZNAME{}", i)) {
+                name.to_uppercase()
+            } else {
+                break;
+            };
+            let value = header.get_i64(&format!("ZVAL{}", i));
+
+            match (name.as_str(), value) {
+                ("BLOCKSIZE", Some(v)) => block_size = v.max(1) as usize,
+                ("BYTEPIX", Some(v)) => bytepix = v as usize,
+                _ => (),
+            }
+        }
+
+        let quantize = Method::from_zquantiz(header.get_str("ZQUANTIZ").as_deref());
+        let zdither0 = header.get_i64("ZDITHER0").unwrap_or(1);
+
+        Ok(Self {
+            header,
+            data,
+            row_len,
+            num_rows,
+            heap_offset,
+            columns,
+            zbitpix,
+            naxes,
+            ztile,
+            compression,
+            block_size,
+            bytepix,
+            quantize,
+            zdither0,
+        })
+    }
+
+    fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|(n, _)| n == name).map(|(_, c)| c)
+    }
+
+    fn field(&self, column: &Column, row: usize, width: usize) -> Result<&'a [u8], Error> {
+        let start = row * self.row_len + column.offset;
+        self.data
+            .get(start..(start + width))
+            .ok_or_else(|| Error::Format {
+                message: "The compressed image table is truncated".to_string(),
+            })
+    }
+
+    // Get the bytes of a variable length array, None if it is empty
+    fn array(&self, name: &str, row: usize) -> Result<Option<(&'a [u8], char)>, Error> {
+        let column = if let Some(column) = self.column(name) {
+            column
+        } else {
+            return Ok(None);
+        };
+
+        let (count, offset) = match column.descriptor {
+            Some('P') => {
+                let b = self.field(column, row, 8)?;
+                (
+                    u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize,
+                    u32::from_be_bytes([b[4], b[5], b[6], b[7]]) as usize,
+                )
+            }
+            Some(_) => {
+                let b = self.field(column, row, 16)?;
+                (
+                    u64::from_be_bytes(b[..8].try_into().unwrap()) as usize,
+                    u64::from_be_bytes(b[8..].try_into().unwrap()) as usize,
+                )
+            }
+            None => {
+                return Err(Error::Format {
+                    message: format!("The {} column is not a variable length array", name),
+                })
+            }
+        };
+
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let width = type_width(column.code).unwrap_or(1);
+        let start = self.heap_offset + offset;
+        let bytes = self
+            .data
+            .get(start..(start + count * width))
+            .ok_or_else(|| Error::Format {
+                message: "The heap of the compressed image is truncated".to_string(),
+            })?;
+
+        Ok(Some((bytes, column.code)))
+    }
+
+    // Get a scalar given either in a column or as a keyword
+    fn scalar(&self, name: &str, row: usize) -> Result<Option<f64>, Error> {
+        if let Some(column) = self.column(name).filter(|c| c.descriptor.is_none()) {
+            let width = type_width(column.code).unwrap_or(0);
+            let b = self.field(column, row, width)?;
+
+            let v = match column.code {
+                'B' => b[0] as f64,
+                'I' => i16::from_be_bytes([b[0], b[1]]) as f64,
+                'J' => i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                'K' => i64::from_be_bytes(b.try_into().unwrap()) as f64,
+                'E' => f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64,
+                'D' => f64::from_be_bytes(b.try_into().unwrap()),
+                _ => return Ok(None),
+            };
+
+            Ok(Some(v))
+        } else {
+            Ok(self.header.get_f64(name))
+        }
+    }
+
+    fn decompress_tile(&self, row: usize, num_pixels: usize) -> Result<Samples, Error> {
+        let float = self.zbitpix < 0;
+
+        if let Some((bytes, _)) = self.array("COMPRESSED_DATA", row)? {
+            let samples = match self.compression {
+                Compression::Rice1 => Samples::Int(rice::decompress(
+                    bytes,
+                    num_pixels,
+                    self.bytepix,
+                    self.block_size,
+                )?),
+                Compression::Hcompress1 => Samples::Int(hcompress::decompress(bytes)?),
+                Compression::Gzip1 | Compression::Gzip2 | Compression::NoCompress => {
+                    let raw = if self.compression == Compression::NoCompress {
+                        bytes.to_vec()
+                    } else {
+                        gunzip(bytes)?
+                    };
+                    // The values are either the pixels or the quantized integers
+                    let width = raw.len() / num_pixels.max(1);
+                    let raw = if self.compression == Compression::Gzip2 {
+                        unshuffle(&raw, width)
+                    } else {
+                        raw
+                    };
+
+                    let quantized = float
+                        && (self.column("ZSCALE").is_some() || self.header.get("ZSCALE").is_some());
+                    Samples::from_be_bytes(&raw, width, float && !quantized)?
+                }
+            };
+
+            return self.unquantize(samples, row);
+        }
+
+        // Tiles that could not be compressed
+        if let Some((bytes, _)) = self.array("GZIP_COMPRESSED_DATA", row)? {
+            let raw = gunzip(bytes)?;
+            return Samples::from_be_bytes(&raw, raw.len() / num_pixels.max(1), float);
+        }
+        if let Some((bytes, code)) = self.array("UNCOMPRESSED_DATA", row)? {
+            return Samples::from_be_bytes(
+                bytes,
+                type_width(code).unwrap_or(1),
+                code == 'E' || code == 'D',
+            );
+        }
+
+        // An empty tile is made of null pixels
+        Ok(if float {
+            Samples::Float(vec![f64::NAN; num_pixels])
+        } else {
+            Samples::Int(vec![0; num_pixels])
+        })
+    }
+
+    fn unquantize(&self, samples: Samples, row: usize) -> Result<Samples, Error> {
+        match samples {
+            Samples::Int(values) if self.zbitpix < 0 => {
+                let scale = self.scalar("ZSCALE", row)?.unwrap_or(1.0);
+                let zero = self.scalar("ZZERO", row)?.unwrap_or(0.0);
+                let blank = self.scalar("ZBLANK", row)?.map(|b| b as i64);
+
+                Ok(Samples::Float(quantize::unquantize(
+                    &values,
+                    self.quantize,
+                    scale,
+                    zero,
+                    blank,
+                    row,
+                    self.zdither0,
+                )))
+            }
+            _ => Ok(samples),
+        }
+    }
+
+    fn decompress(&self) -> Result<Vec<u8>, Error> {
+        let bytes_per_pixel = (self.zbitpix.unsigned_abs() / 8) as usize;
+        let num_pixels = self.naxes.iter().product::<usize>();
+        let mut out = vec![0; num_pixels * bytes_per_pixel];
+
+        if num_pixels == 0 {
+            return Ok(out);
+        }
+
+        // Number of tiles along each axis
+        let num_tiles = self
+            .naxes
+            .iter()
+            .zip(self.ztile.iter())
+            .map(|(&n, &t)| n.div_ceil(t))
+            .collect::<Vec<_>>();
+
+        for row in 0..self.num_rows.min(num_tiles.iter().product()) {
+            // Position and size of the tile, the first axis varying the fastest
+            let mut idx = row;
+            let (start, size): (Vec<_>, Vec<_>) = num_tiles
+                .iter()
+                .zip(self.ztile.iter().zip(self.naxes.iter()))
+                .map(|(&nt, (&t, &n))| {
+                    let s = (idx % nt) * t;
+                    idx /= nt;
+                    (s, t.min(n - s))
+                })
+                .unzip();
+
+            let tile_num_pixels = size.iter().product::<usize>();
+            let samples = self.decompress_tile(row, tile_num_pixels)?;
+            if samples.len() != tile_num_pixels {
+                return Err(Error::Format {
+                    message: format!(
+                        "The tile {} has {} pixels instead of {}",
+                        row,
+                        samples.len(),
+                        tile_num_pixels
+                    ),
+                });
+            }
+
+            // Copy the tile into the image
+            let mut pos = vec![0; size.len()];
+            for k in 0..tile_num_pixels {
+                let mut offset = 0;
+                for axis in (0..pos.len()).rev() {
+                    offset = offset * self.naxes[axis] + start[axis] + pos[axis];
+                }
+
+                let dst = &mut out[(offset * bytes_per_pixel)..((offset + 1) * bytes_per_pixel)];
+                match &samples {
+                    Samples::Int(v) => write_int(dst, v[k], self.zbitpix),
+                    Samples::Float(v) => write_float(dst, v[k], self.zbitpix),
+                }
+
+                for axis in 0..pos.len() {
+                    pos[axis] += 1;
+                    if pos[axis] < size[axis] {
+                        break;
+                    }
+                    pos[axis] = 0;
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// The header of the uncompressed image, either a primary or an IMAGE extension one
+    fn cards(&self, primary: bool) -> Vec<Card> {
+        let mut cards = vec![if primary {
+            header::card("SIMPLE", &Value::Logical(true))
+        } else {
+            header::card("XTENSION", &Value::String("IMAGE".to_string()))
+        }];
+        cards.push(header::card("BITPIX", &Value::Integer(self.zbitpix)));
+        cards.push(header::card(
+            "NAXIS",
+            &Value::Integer(self.naxes.len() as i64),
+        ));
+        for (i, &n) in self.naxes.iter().enumerate() {
+            cards.push(header::card(
+                &format!("NAXIS{}", i + 1),
+                &Value::Integer(n as i64),
+            ));
+        }
+        if !primary {
+            cards.push(header::card("PCOUNT", &Value::Integer(0)));
+            cards.push(header::card("GCOUNT", &Value::Integer(1)));
+        }
+
+        if self.zbitpix > 0 && self.header.get("BLANK").is_none() {
+            if let Some(blank) = self.header.get_i64("ZBLANK") {
+                cards.push(header::card("BLANK", &Value::Integer(blank)));
+            }
+        }
+
+        cards.extend(
+            self.header
+                .cards()
+                .iter()
+                .filter(|c| !is_table_or_compression_key(header::key(c))),
+        );
+
+        cards
+    }
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut raw = vec![];
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut raw)
+        .map_err(|e| Error::Format {
+            message: format!("GZIP decompression: {}", e),
+        })?;
+
+    Ok(raw)
+}
+
+// GZIP_2 shuffles the bytes so that the most significant bytes of all the values come first
+fn unshuffle(bytes: &[u8], width: usize) -> Vec<u8> {
+    let n = bytes.len() / width.max(1);
+    let mut out = vec![0; bytes.len()];
+    for i in 0..n {
+        for k in 0..width {
+            out[i * width + k] = bytes[k * n + i];
+        }
+    }
+
+    out
+}
+
+fn write_int(dst: &mut [u8], v: i64, bitpix: i64) {
+    match bitpix {
+        8 => dst.copy_from_slice(&[v as u8]),
+        16 => dst.copy_from_slice(&(v as i16).to_be_bytes()),
+        32 => dst.copy_from_slice(&(v as i32).to_be_bytes()),
+        64 => dst.copy_from_slice(&v.to_be_bytes()),
+        -32 => dst.copy_from_slice(&(v as f32).to_be_bytes()),
+        _ => dst.copy_from_slice(&(v as f64).to_be_bytes()),
+    }
+}
+
+fn write_float(dst: &mut [u8], v: f64, bitpix: i64) {
+    match bitpix {
+        -32 => dst.copy_from_slice(&(v as f32).to_be_bytes()),
+        -64 => dst.copy_from_slice(&v.to_be_bytes()),
+        _ => write_int(dst, v.round() as i64, bitpix),
+    }
+}
+
+fn is_table_or_compression_key(key: &str) -> bool {
+    const KEYS: &[&str] = &[
+        "SIMPLE", "XTENSION", "BITPIX", "NAXIS", "PCOUNT", "GCOUNT", "TFIELDS", "THEAP",
+        "CHECKSUM", "DATASUM", "END", "ZIMAGE", "ZCMPTYPE", "ZBITPIX", "ZNAXIS", "ZSIMPLE",
+        "ZTENSION", "ZEXTEND", "ZBLOCKED", "ZPCOUNT", "ZGCOUNT", "ZHECKSUM", "ZDATASUM",
+        "ZQUANTIZ", "ZDITHER0", "ZMASKCMP", "ZSCALE", "ZZERO", "ZBLANK",
+    ];
+    // Keywords followed by an index
+    const INDEXED_KEYS: &[&str] = &[
+        "NAXIS", "TTYPE", "TFORM", "TUNIT", "TSCAL", "TZERO", "TNULL", "TDIM", "TDISP", "ZNAXIS",
+        "ZTILE", "ZNAME", "ZVAL",
+    ];
+
+    KEYS.contains(&key)
+        || INDEXED_KEYS.iter().any(|k| {
+            key.strip_prefix(k)
+                .map(|idx| !idx.is_empty() && idx.chars().all(|c| c.is_ascii_digit()))
+                .unwrap_or(false)
+        })
+}
+
+/// Whether the HDU holds a compressed image
+pub fn is_compressed_image(header: &Header) -> bool {
+    header.get_str("XTENSION").as_deref() == Some("BINTABLE")
+        && header.get_bool("ZIMAGE") == Some(true)
+}
+
+/// Decompress the image of a compressed HDU
+///
+/// Returns the cards of the uncompressed image header and its data unit, padding excluded
+pub fn decompress(
+    header: &Header,
+    data: &[u8],
+    primary: bool,
+) -> Result<(Vec<Card>, Vec<u8>), Error> {
+    let image = CompressedImage::new(header, data)?;
+
+    Ok((image.cards(primary), image.decompress()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_table_or_compression_key, unshuffle, Column};
+
+    #[test]
+    fn tform() {
+        let (c, width) = Column::parse("1PB(2880)", 0).unwrap();
+        assert_eq!((c.descriptor, c.code, width), (Some('P'), 'B', 8));
+
+        let (c, width) = Column::parse("1QE", 8).unwrap();
+        assert_eq!(
+            (c.descriptor, c.code, c.offset, width),
+            (Some('Q'), 'E', 8, 16)
+        );
+
+        let (c, width) = Column::parse("D", 0).unwrap();
+        assert_eq!((c.descriptor, c.code, width), (None, 'D', 8));
+    }
+
+    #[test]
+    fn gzip2_unshuffle() {
+        let shuffled = [0x01, 0x03, 0x02, 0x04];
+        assert_eq!(unshuffle(&shuffled, 2), vec![0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn excluded_keys() {
+        assert!(is_table_or_compression_key("TFORM1"));
+        assert!(is_table_or_compression_key("ZTILE2"));
+        assert!(is_table_or_compression_key("NAXIS3"));
+        assert!(!is_table_or_compression_key("CRVAL1"));
+        assert!(!is_table_or_compression_key("BSCALE"));
+    }
+}
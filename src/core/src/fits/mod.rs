@@ -0,0 +1,294 @@
+//! Tile-compressed FITS images
+//!
+//! Images following the tiled image compression convention are stored in binary table
+//! extensions (ZIMAGE = T) whose tiles are compressed with RICE_1, GZIP_1, GZIP_2 or
+//! HCOMPRESS_1 (see <https://fits.gsfc.nasa.gov/registry/tilecompression.html>).
+//! They are converted back to uncompressed FITS files so that they follow the same
+//! parsing and texture paths as regular FITS images.
+
+mod hcompress;
+mod header;
+mod image;
+mod quantize;
+mod rice;
+
+use futures::{AsyncRead, AsyncReadExt};
+use wasm_bindgen::JsValue;
+
+use header::{Header, BLOCK_SIZE};
+
+#[derive(Debug)]
+pub enum Error {
+    Format { message: String },
+    Unsupported { message: String },
+}
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Format { message } | Error::Unsupported { message } => {
+                JsValue::from_str(&format!("Error decompressing the FITS image: {}", message))
+            }
+        }
+    }
+}
+
+// Iterate over the HDUs as (header, offset of the HDU, offset of the data, offset of the next HDU)
+fn hdus(bytes: &[u8]) -> impl Iterator<Item = (Header, usize, usize, usize)> + '_ {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let (header, header_len) = Header::parse(bytes.get(offset..)?)?;
+
+        let hdu_offset = offset;
+        let data_offset = offset + header_len;
+        offset = data_offset + header.data_num_bytes().div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+
+        Some((header, hdu_offset, data_offset, offset))
+    })
+}
+
+/// Whether the FITS file contains a compressed image
+///
+/// Only the HDUs whose header is entirely given in `bytes` are looked for
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    hdus(bytes).any(|(header, ..)| image::is_compressed_image(&header))
+}
+
+/// Convert the compressed images of a FITS file to uncompressed image HDUs
+///
+/// Other HDUs are copied unchanged. If the primary HDU has no data and is directly followed
+/// by a compressed image, that image becomes the primary HDU so that readers of the primary
+/// HDU only (e.g. HiPS tiles) get the image.
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = vec![];
+    let mut empty_primary = false;
+
+    for (idx, (header, hdu_offset, data_offset, next_offset)) in hdus(bytes).enumerate() {
+        if image::is_compressed_image(&header) {
+            let data = bytes
+                .get(data_offset..(data_offset + header.data_num_bytes()))
+                .ok_or_else(|| Error::Format {
+                    message: "The compressed image is truncated".to_string(),
+                })?;
+
+            let primary = idx == 1 && empty_primary;
+            if primary {
+                out.clear();
+            }
+
+            let (cards, data) = image::decompress(&header, data, primary)?;
+            header::write(&cards, &mut out);
+            out.extend_from_slice(&data);
+        } else {
+            empty_primary = idx == 0 && header.data_num_bytes() == 0;
+
+            out.extend_from_slice(&bytes[hdu_offset..next_offset.min(bytes.len())]);
+        }
+
+        header::pad(&mut out, 0);
+    }
+
+    if out.is_empty() {
+        Err(Error::Format {
+            message: "No FITS HDU found".to_string(),
+        })
+    } else {
+        Ok(out)
+    }
+}
+
+/// Read the primary header of a FITS stream, plus the header of the first extension
+/// if the primary HDU has no data
+///
+/// This is enough to know whether the file contains a compressed image. The bytes read
+/// are returned so that the stream can be parsed from its beginning.
+pub async fn read_leading_headers<R>(reader: &mut R) -> std::io::Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut bytes = vec![];
+
+    for hdu in 0..2 {
+        let start = bytes.len();
+        loop {
+            let mut block = vec![0; BLOCK_SIZE];
+            let mut num_bytes = 0;
+            while num_bytes < BLOCK_SIZE {
+                let n = reader.read(&mut block[num_bytes..]).await?;
+                if n == 0 {
+                    break;
+                }
+                num_bytes += n;
+            }
+            bytes.extend_from_slice(&block[..num_bytes]);
+
+            if num_bytes < BLOCK_SIZE {
+                // End of the stream
+                return Ok(bytes);
+            }
+
+            match Header::parse(&bytes[start..]) {
+                Some(_) => break,
+                None => {
+                    let first_key = bytes[start..].get(..8).unwrap_or(&[]);
+                    if !(first_key == b"SIMPLE  " || first_key == b"XTENSION") {
+                        // Not a FITS header
+                        return Ok(bytes);
+                    }
+                }
+            }
+        }
+
+        let has_data = Header::parse(&bytes[start..])
+            .map(|(h, _)| h.data_num_bytes() > 0)
+            .unwrap_or(true);
+        if hdu == 0 && has_data {
+            break;
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::header::{self, Card, Value};
+    use super::{decompress, is_compressed};
+
+    fn hdu(cards: &[Card], data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![];
+        header::write(cards, &mut bytes);
+        bytes.extend_from_slice(data);
+        header::pad(&mut bytes, 0);
+
+        bytes
+    }
+
+    #[test]
+    fn uncompressed_column() {
+        // A 2x2 16 bits image split into two 2x1 tiles stored uncompressed
+        // in the UNCOMPRESSED_DATA column, followed by the heap
+        let mut table = vec![];
+        // Descriptors (count, offset) of the two rows
+        for (count, offset) in [(2u32, 0u32), (2, 4)] {
+            table.extend_from_slice(&count.to_be_bytes());
+            table.extend_from_slice(&offset.to_be_bytes());
+        }
+        for v in [1i16, 2, 3, -4] {
+            table.extend_from_slice(&v.to_be_bytes());
+        }
+
+        let card = |k: &str, v: Value| header::card(k, &v);
+        let mut bytes = hdu(
+            &[
+                card("SIMPLE", Value::Logical(true)),
+                card("BITPIX", Value::Integer(8)),
+                card("NAXIS", Value::Integer(0)),
+            ],
+            &[],
+        );
+        bytes.extend(hdu(
+            &[
+                card("XTENSION", Value::String("BINTABLE".to_string())),
+                card("BITPIX", Value::Integer(8)),
+                card("NAXIS", Value::Integer(2)),
+                card("NAXIS1", Value::Integer(8)),
+                card("NAXIS2", Value::Integer(2)),
+                card("PCOUNT", Value::Integer(8)),
+                card("GCOUNT", Value::Integer(1)),
+                card("TFIELDS", Value::Integer(1)),
+                card("TTYPE1", Value::String("UNCOMPRESSED_DATA".to_string())),
+                card("TFORM1", Value::String("1PI(2)".to_string())),
+                card("ZIMAGE", Value::Logical(true)),
+                card("ZBITPIX", Value::Integer(16)),
+                card("ZNAXIS", Value::Integer(2)),
+                card("ZNAXIS1", Value::Integer(2)),
+                card("ZNAXIS2", Value::Integer(2)),
+                card("ZTILE1", Value::Integer(2)),
+                card("ZTILE2", Value::Integer(1)),
+                card("ZCMPTYPE", Value::String("RICE_1".to_string())),
+                card("CRVAL1", Value::Float(10.5)),
+            ],
+            &table,
+        ));
+
+        assert!(is_compressed(&bytes));
+
+        let out = decompress(&bytes).unwrap();
+        assert!(!is_compressed(&out));
+        // The image replaces the empty primary HDU
+        assert_eq!(out.len(), 2 * 2880);
+        assert_eq!(&out[..30], b"SIMPLE  =                    T");
+
+        let (h, num_bytes) = header::Header::parse(&out).unwrap();
+        assert_eq!(h.get_i64("BITPIX"), Some(16));
+        assert_eq!(h.get_i64("NAXIS2"), Some(2));
+        assert_eq!(h.get_f64("CRVAL1"), Some(10.5));
+        assert_eq!(h.get("TFORM1"), None);
+        assert_eq!(
+            &out[num_bytes..(num_bytes + 8)],
+            &[0, 1, 0, 2, 0, 3, 0xFF, 0xFC]
+        );
+    }
+
+    #[test]
+    fn hcompress_non_square_tile() {
+        // A 4x2 16 bits image stored in one HCOMPRESS tile whose stream gives
+        // the number of rows (ZTILE2) before the length of a row (ZTILE1)
+        let mut stream = vec![0xDD, 0x99];
+        stream.extend_from_slice(&2i32.to_be_bytes());
+        stream.extend_from_slice(&4i32.to_be_bytes());
+        stream.extend_from_slice(&0i32.to_be_bytes());
+        stream.extend_from_slice(&16i64.to_be_bytes());
+        stream.extend_from_slice(&[0, 3, 0]);
+        stream.extend_from_slice(&[0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x00]);
+
+        let mut table = vec![];
+        table.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+        table.extend_from_slice(&0u32.to_be_bytes());
+        table.extend_from_slice(&stream);
+
+        let card = |k: &str, v: Value| header::card(k, &v);
+        let mut bytes = hdu(
+            &[
+                card("SIMPLE", Value::Logical(true)),
+                card("BITPIX", Value::Integer(8)),
+                card("NAXIS", Value::Integer(0)),
+            ],
+            &[],
+        );
+        bytes.extend(hdu(
+            &[
+                card("XTENSION", Value::String("BINTABLE".to_string())),
+                card("BITPIX", Value::Integer(8)),
+                card("NAXIS", Value::Integer(2)),
+                card("NAXIS1", Value::Integer(8)),
+                card("NAXIS2", Value::Integer(1)),
+                card("PCOUNT", Value::Integer(stream.len() as i64)),
+                card("GCOUNT", Value::Integer(1)),
+                card("TFIELDS", Value::Integer(1)),
+                card("TTYPE1", Value::String("COMPRESSED_DATA".to_string())),
+                card("TFORM1", Value::String("1PB(33)".to_string())),
+                card("ZIMAGE", Value::Logical(true)),
+                card("ZBITPIX", Value::Integer(16)),
+                card("ZNAXIS", Value::Integer(2)),
+                card("ZNAXIS1", Value::Integer(4)),
+                card("ZNAXIS2", Value::Integer(2)),
+                card("ZTILE1", Value::Integer(4)),
+                card("ZTILE2", Value::Integer(2)),
+                card("ZCMPTYPE", Value::String("HCOMPRESS_1".to_string())),
+            ],
+            &table,
+        ));
+
+        let out = decompress(&bytes).unwrap();
+        let (h, num_bytes) = header::Header::parse(&out).unwrap();
+        assert_eq!(h.get_i64("NAXIS1"), Some(4));
+        assert_eq!(h.get_i64("NAXIS2"), Some(2));
+        // The first row of the image comes first
+        assert_eq!(
+            &out[num_bytes..(num_bytes + 16)],
+            &[0, 1, 0, 1, 0, 1, 0, 1, 0, 3, 0, 3, 0, 3, 0, 3]
+        );
+    }
+}
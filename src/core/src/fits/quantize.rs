@@ -0,0 +1,127 @@
+//! Restore floating point values from the quantized integers
+
+const NUM_RANDOM_VALUES: usize = 10000;
+
+// Value of the null quantized integers with SUBTRACTIVE_DITHER_2
+const ZERO_VALUE: i64 = -2147483646;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    NoDither,
+    SubtractiveDither1,
+    SubtractiveDither2,
+}
+
+impl Method {
+    pub fn from_zquantiz(zquantiz: Option<&str>) -> Self {
+        match zquantiz {
+            Some("SUBTRACTIVE_DITHER_1") => Method::SubtractiveDither1,
+            Some("SUBTRACTIVE_DITHER_2") => Method::SubtractiveDither2,
+            _ => Method::NoDither,
+        }
+    }
+}
+
+// The pseudo random sequence shared by every FITS quantizer
+// (Park & Miller minimal standard generator)
+fn random_values() -> Vec<f32> {
+    const A: f64 = 16807.0;
+    const M: f64 = 2147483647.0;
+
+    let mut seed = 1.0;
+    (0..NUM_RANDOM_VALUES)
+        .map(|_| {
+            let temp = A * seed;
+            seed = temp - M * (temp / M).floor();
+            (seed / M) as f32
+        })
+        .collect()
+}
+
+/// Unquantize the integers of a tile
+///
+/// `row` is the 0-based index of the tile in the compressed table that seeds the dither.
+/// The `blank` integers are converted to NaN.
+pub fn unquantize(
+    values: &[i64],
+    method: Method,
+    scale: f64,
+    zero: f64,
+    blank: Option<i64>,
+    row: usize,
+    zdither0: i64,
+) -> Vec<f64> {
+    if method == Method::NoDither {
+        return values
+            .iter()
+            .map(|&v| {
+                if Some(v) == blank {
+                    f64::NAN
+                } else {
+                    v as f64 * scale + zero
+                }
+            })
+            .collect();
+    }
+
+    let rand = random_values();
+
+    let mut iseed = (row as i64 + zdither0 - 1).rem_euclid(NUM_RANDOM_VALUES as i64) as usize;
+    let mut next_rand = (rand[iseed] * 500.0) as usize;
+
+    values
+        .iter()
+        .map(|&v| {
+            let value = if Some(v) == blank {
+                f64::NAN
+            } else if method == Method::SubtractiveDither2 && v == ZERO_VALUE {
+                0.0
+            } else {
+                (v as f64 - rand[next_rand] as f64 + 0.5) * scale + zero
+            };
+
+            next_rand += 1;
+            if next_rand == NUM_RANDOM_VALUES {
+                iseed = (iseed + 1) % NUM_RANDOM_VALUES;
+                next_rand = (rand[iseed] * 500.0) as usize;
+            }
+
+            value
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{random_values, unquantize, Method};
+
+    #[test]
+    fn random_sequence() {
+        // The 10000th value of the sequence is given by the FITS standard
+        let rand = random_values();
+        let last = rand[9999] as f64 * 2147483647.0;
+        assert!((last - 1043618065.0).abs() < 1e3);
+    }
+
+    #[test]
+    fn no_dither() {
+        let values = unquantize(&[0, 2, -5], Method::NoDither, 0.5, 1.0, Some(-5), 0, 1);
+        assert_eq!(values[..2], [1.0, 2.0]);
+        assert!(values[2].is_nan());
+    }
+
+    #[test]
+    fn subtractive_dither() {
+        let values = unquantize(
+            &[10, -2147483646],
+            Method::SubtractiveDither2,
+            1.0,
+            0.0,
+            None,
+            0,
+            1,
+        );
+        assert!((values[0] - 10.0).abs() <= 0.5);
+        assert_eq!(values[1], 0.0);
+    }
+}
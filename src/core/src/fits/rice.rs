@@ -0,0 +1,149 @@
+//! RICE_1 decompression
+
+use super::Error;
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn read_bit(&mut self) -> Result<u32, Error> {
+        let byte = self.bytes.get(self.pos / 8).ok_or_else(end_of_stream)?;
+        let bit = (byte >> (7 - (self.pos % 8))) & 1;
+        self.pos += 1;
+
+        Ok(bit as u32)
+    }
+
+    fn read(&mut self, num_bits: u32) -> Result<u32, Error> {
+        let mut v = 0u32;
+        for _ in 0..num_bits {
+            v = (v << 1) | self.read_bit()?;
+        }
+
+        Ok(v)
+    }
+
+    // Count the zero bits preceding the next one bit, that bit is consumed
+    fn read_unary(&mut self) -> Result<u32, Error> {
+        let mut n = 0;
+        while self.read_bit()? == 0 {
+            n += 1;
+        }
+
+        Ok(n)
+    }
+}
+
+fn end_of_stream() -> Error {
+    Error::Format {
+        message: "Unexpected end of the RICE compressed stream".to_string(),
+    }
+}
+
+/// Decode `num_pixels` values compressed with the RICE algorithm
+///
+/// `bytepix` is the number of bytes of the original integer values (1, 2 or 4)
+/// and `block_size` the number of pixels sharing the same coding parameter.
+pub fn decompress(
+    bytes: &[u8],
+    num_pixels: usize,
+    bytepix: usize,
+    block_size: usize,
+) -> Result<Vec<i64>, Error> {
+    let (fs_bits, fs_max, b_bits) = match bytepix {
+        1 => (3, 6, 8),
+        2 => (4, 14, 16),
+        4 => (5, 25, 32),
+        _ => {
+            return Err(Error::Unsupported {
+                message: format!("RICE_1 with BYTEPIX = {} is not supported", bytepix),
+            })
+        }
+    };
+    let mask = if bytepix == 4 {
+        u32::MAX
+    } else {
+        (1u32 << (8 * bytepix)) - 1
+    };
+
+    let first = bytes.get(..bytepix).ok_or_else(end_of_stream)?;
+    let mut last_pix = first.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+
+    let mut reader = BitReader::new(&bytes[bytepix..]);
+    let mut pixels = Vec::with_capacity(num_pixels);
+
+    while pixels.len() < num_pixels {
+        let n = block_size.min(num_pixels - pixels.len());
+        let fs = reader.read(fs_bits)? as i32 - 1;
+
+        for _ in 0..n {
+            if fs >= 0 {
+                let diff = if fs == fs_max {
+                    reader.read(b_bits)?
+                } else {
+                    let nzero = reader.read_unary()?;
+                    (nzero << fs) | reader.read(fs as u32)?
+                };
+                // Undo the mapping of the differences to positive integers
+                let diff = if diff & 1 == 0 {
+                    diff >> 1
+                } else {
+                    !(diff >> 1)
+                };
+                last_pix = diff.wrapping_add(last_pix) & mask;
+            }
+
+            pixels.push(signed(last_pix, bytepix));
+        }
+    }
+
+    Ok(pixels)
+}
+
+fn signed(v: u32, bytepix: usize) -> i64 {
+    match bytepix {
+        // 8 bits values are stored unsigned
+        1 => v as i64,
+        2 => v as u16 as i16 as i64,
+        _ => v as i32 as i64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompress;
+
+    #[test]
+    fn constant_block() {
+        // The first pixel followed by a block whose fs is -1 (all the pixels equal)
+        let bytes = [0x00, 0x07, 0b0000_0000];
+        assert_eq!(decompress(&bytes, 5, 2, 32).unwrap(), vec![7; 5]);
+    }
+
+    #[test]
+    fn fs_zero_block() {
+        // 8 bits pixels starting at 10, block coded with fs = 0 (fs bits = 001)
+        // differences +1, -1, 0 are mapped to 2, 1, 0 i.e. unary codes 001, 01, 1
+        let bytes = [10, 0b0010_0101, 0b1000_0000];
+        assert_eq!(decompress(&bytes, 3, 1, 32).unwrap(), vec![11, 10, 10]);
+    }
+
+    #[test]
+    fn raw_block() {
+        // 16 bits pixels, fs = fs_max (15 on 4 bits) so the differences are stored on 16 bits
+        // difference -3 is mapped to 5
+        let bytes = [0x00, 0x04, 0b1111_0000, 0b0000_0000, 0b0101_0000];
+        assert_eq!(decompress(&bytes, 1, 2, 32).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn truncated_stream() {
+        assert!(decompress(&[0x00], 1, 2, 32).is_err());
+    }
+}
@@ -4,7 +4,10 @@ use crate::math::{self, lonlat::LonLat};
 
 use cgmath::{Vector4};
 use moclib::{
-    moc::range::{CellSelection, RangeMOC},
+    moc::{
+        range::{CellSelection, RangeMOC},
+        CellMOCIterator, CellOrCellRangeMOCIterator, RangeMOCIntoIterator, RangeMOCIterator,
+    },
     qty::Hpx,
     ranges::SNORanges,
 };
@@ -114,9 +117,79 @@ impl HEALPixCoverage {
         HEALPixCoverage(self.0.not())
     }
 
+    pub fn union(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.union(&other.0))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.intersection(&other.0))
+    }
+
+    /// The cells of self not covered by other
+    pub fn difference(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.minus(&other.0))
+    }
+
+    /// The cells covered by exactly one of self and other
+    pub fn xor(&self, other: &Self) -> Self {
+        HEALPixCoverage(self.0.xor(&other.0))
+    }
+
+    /// Degrade the coverage to a lower depth, a cell partially covered at the
+    /// new depth is considered fully covered
+    pub fn degrade(&self, depth: u8) -> Self {
+        HEALPixCoverage(self.0.degraded(depth))
+    }
+
+    /// Add the ring of cells of depth `self.depth()` lying along the border of the coverage
+    pub fn expand(&self) -> Self {
+        HEALPixCoverage(self.0.expanded())
+    }
+
+    /// Remove the ring of cells of depth `self.depth()` lying along the border of the coverage
+    pub fn contract(&self) -> Self {
+        HEALPixCoverage(self.0.contracted())
+    }
+
     pub fn empty(depth: u8) -> Self {
         HEALPixCoverage(RangeMOC::new_empty(depth))
     }
+
+    /// Serialize the coverage to the Aladin JSON format
+    pub fn to_json(&self) -> Result<String, String> {
+        let mut buf: Vec<u8> = Default::default();
+        (&self.0)
+            .into_range_moc_iter()
+            .cells()
+            .to_json_aladin(None, &mut buf)
+            .map_err(|err| format!("{:?}", err))?;
+
+        String::from_utf8(buf).map_err(|err| format!("{:?}", err))
+    }
+
+    /// Serialize the coverage to the IVOA MOC 2.0 ASCII format
+    pub fn to_ascii(&self) -> Result<String, String> {
+        let mut buf: Vec<u8> = Default::default();
+        (&self.0)
+            .into_range_moc_iter()
+            .cells()
+            .cellranges()
+            .to_ascii_ivoa(Some(80), false, &mut buf)
+            .map_err(|err| format!("{:?}", err))?;
+
+        String::from_utf8(buf).map_err(|err| format!("{:?}", err))
+    }
+
+    /// Serialize the coverage to the IVOA MOC 2.0 FITS format
+    pub fn to_fits(&self) -> Result<Vec<u8>, String> {
+        let mut buf: Vec<u8> = Default::default();
+        (&self.0)
+            .into_range_moc_iter()
+            .to_fits_ivoa(None, None, &mut buf)
+            .map_err(|err| format!("{:?}", err))?;
+
+        Ok(buf)
+    }
 }
 
 use core::ops::Deref;
@@ -127,3 +200,117 @@ impl Deref for HEALPixCoverage {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HEALPixCoverage;
+    use crate::downloader::request::moc::from_fits_hpx;
+
+    use moclib::deser::fits::{self, MocIdxType, MocQtyType};
+    use moclib::moc::{
+        CellMOCIntoIterator, CellMOCIterator, CellOrCellRangeMOCIntoIterator,
+        CellOrCellRangeMOCIterator, RangeMOCIterator,
+    };
+    use moclib::qty::Hpx;
+    use std::io::Cursor;
+
+    fn coverage(depth: u8, cells: &[u64]) -> HEALPixCoverage {
+        HEALPixCoverage::from_fixed_hpx_cells(depth, cells.iter().copied(), None)
+    }
+
+    fn cells(moc: &HEALPixCoverage) -> Vec<u64> {
+        moc.flatten_to_fixed_depth_cells().collect()
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = coverage(1, &[0, 1, 2, 3]);
+        let b = coverage(1, &[2, 3, 4, 5]);
+
+        assert_eq!(cells(&a.union(&b)), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(cells(&a.intersection(&b)), vec![2, 3]);
+        assert_eq!(cells(&a.difference(&b)), vec![0, 1]);
+        assert_eq!(cells(&b.difference(&a)), vec![4, 5]);
+        assert_eq!(cells(&a.xor(&b)), vec![0, 1, 4, 5]);
+        assert_eq!(cells(&a.not().intersection(&a)), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn degrade() {
+        // The 4 children of the base cell 0 and one child of the base cell 1
+        let moc = coverage(1, &[0, 1, 2, 3, 4]);
+
+        let degraded = moc.degrade(0);
+        assert_eq!(degraded.depth(), 0);
+        // the partially covered base cell 1 becomes fully covered
+        assert_eq!(cells(&degraded), vec![0, 1]);
+    }
+
+    #[test]
+    fn expand_and_contract() {
+        // A cell at the center of the base cell 4, far from its border
+        let center = 4 * 64 + 15;
+        let moc = coverage(3, &[center]);
+
+        let expanded = moc.expand();
+        assert_eq!(expanded.depth(), 3);
+        // the cell and its 8 neighbours
+        let expanded_cells = cells(&expanded);
+        assert_eq!(expanded_cells.len(), 9);
+        assert!(expanded_cells.contains(&center));
+
+        assert_eq!(cells(&expanded.contract()), vec![center]);
+        assert!(moc.contract().is_empty());
+    }
+
+    // A coverage made of cells of different depths
+    fn mixed_depth_coverage() -> HEALPixCoverage {
+        coverage(3, &[0, 1, 2, 3, 4, 100, 101, 640, 767])
+    }
+
+    fn ranges(moc: &HEALPixCoverage) -> Vec<std::ops::Range<u64>> {
+        moc.moc_ranges().iter().cloned().collect()
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let moc = mixed_depth_coverage();
+
+        let json = moc.to_json().unwrap();
+        let parsed = moclib::deser::json::from_json_aladin::<u64, Hpx<u64>>(&json)
+            .unwrap()
+            .into_cell_moc_iter()
+            .ranges()
+            .into_range_moc();
+
+        assert_eq!(ranges(&HEALPixCoverage(parsed)), ranges(&moc));
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let moc = mixed_depth_coverage();
+
+        let ascii = moc.to_ascii().unwrap();
+        let parsed = moclib::deser::ascii::from_ascii_ivoa::<u64, Hpx<u64>>(&ascii)
+            .unwrap()
+            .into_cellcellrange_moc_iter()
+            .ranges()
+            .into_range_moc();
+
+        assert_eq!(ranges(&HEALPixCoverage(parsed)), ranges(&moc));
+    }
+
+    #[test]
+    fn fits_round_trip() {
+        let moc = mixed_depth_coverage();
+
+        let bytes = moc.to_fits().unwrap();
+        let parsed = match fits::from_fits_ivoa(Cursor::new(&bytes[..])).unwrap() {
+            MocIdxType::U64(MocQtyType::<u64, _>::Hpx(moc)) => from_fits_hpx(moc),
+            _ => panic!("A spatial MOC with u64 indices is expected"),
+        };
+
+        assert_eq!(parsed.depth_max(), moc.depth());
+        assert_eq!(ranges(&HEALPixCoverage(parsed)), ranges(&moc));
+    }
+}
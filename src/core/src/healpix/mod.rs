@@ -1,4 +1,5 @@
 pub mod cell;
 pub mod coverage;
+pub mod st_coverage;
 pub mod utils;
 pub mod index_vector;
\ No newline at end of file
@@ -0,0 +1,236 @@
+use crate::healpix::coverage::HEALPixCoverage;
+use crate::math::lonlat::LonLatT;
+
+use moclib::{
+    deser::fits::{self, MocIdxType, MocQtyType, STMocType},
+    elemset::range::MocRanges,
+    moc::range::RangeMOC,
+    moc2d::{range::RangeMOC2Elem, CellMOC2IntoIterator, CellMOC2Iterator},
+    qty::{Hpx, MocQty, Time},
+};
+use std::io::Cursor;
+pub type Tmoc = RangeMOC<u64, Time<u64>>;
+
+// The time MOC indices are microseconds since JD=0
+const MICROSEC_PER_DAY: f64 = 86_400_000_000.0;
+
+#[derive(Clone, Debug)]
+pub struct TimeCoverage(pub Tmoc);
+
+impl TimeCoverage {
+    /// Create the time coverage of a range of julian days
+    pub fn from_jd_range(jd_min: f64, jd_max: f64) -> Self {
+        let start = (jd_min.max(0.0) * MICROSEC_PER_DAY) as u64;
+        let end = (jd_max.max(0.0) * MICROSEC_PER_DAY) as u64;
+
+        if start < end {
+            let ranges = MocRanges::new_unchecked(vec![start..end]);
+            TimeCoverage(RangeMOC::new(Time::<u64>::MAX_DEPTH, ranges))
+        } else {
+            Self::empty(Time::<u64>::MAX_DEPTH)
+        }
+    }
+
+    pub fn empty(depth: u8) -> Self {
+        TimeCoverage(RangeMOC::new_empty(depth))
+    }
+
+    /// The time ranges covered given as a flat list of (start, end) julian days
+    pub fn to_jd_ranges(&self) -> Vec<f64> {
+        self.0
+            .moc_ranges()
+            .iter()
+            .flat_map(|r| {
+                [
+                    (r.start as f64) / MICROSEC_PER_DAY,
+                    (r.end as f64) / MICROSEC_PER_DAY,
+                ]
+            })
+            .collect()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        TimeCoverage(self.0.union(&other.0))
+    }
+
+    pub fn is_intersecting(&self, other: &Self) -> bool {
+        !self.0.intersection(&other.0).is_empty()
+    }
+}
+
+/// A Space-Time MOC
+///
+/// It is stored as the list of its elements, i.e. a time coverage associated with
+/// the spatial coverage observed during it
+pub struct SpaceTimeCoverage {
+    elems: Vec<(TimeCoverage, HEALPixCoverage)>,
+    // The depth of the spatial coverages
+    depth: u8,
+}
+
+impl SpaceTimeCoverage {
+    pub fn from_range_moc2_elems(
+        elems: impl Iterator<Item = RangeMOC2Elem<u64, Time<u64>, u64, Hpx<u64>>>,
+    ) -> Self {
+        let elems = elems
+            .map(|elem| {
+                let (tmoc, smoc) = elem.mocs();
+                (TimeCoverage(tmoc), HEALPixCoverage(smoc))
+            })
+            .collect::<Vec<_>>();
+        let depth = elems
+            .iter()
+            .map(|(_, smoc)| smoc.depth())
+            .max()
+            .unwrap_or(0);
+
+        Self { elems, depth }
+    }
+
+    /// Parse a Space-Time MOC from a FITS file
+    ///
+    /// Only the Space-Time MOCs whose indices are stored on 64 bits are supported
+    pub fn from_fits(data: &[u8]) -> Result<Self, String> {
+        match fits::from_fits_ivoa_custom(Cursor::new(data), false).map_err(|e| e.to_string())? {
+            MocIdxType::U64(MocQtyType::<u64, _>::TimeHpx(STMocType::V2(moc))) => {
+                Ok(Self::from_range_moc2_elems(moc))
+            }
+            MocIdxType::U64(MocQtyType::<u64, _>::TimeHpx(STMocType::PreV2(moc))) => {
+                Ok(Self::from_range_moc2_elems(moc))
+            }
+            MocIdxType::U16(MocQtyType::<u16, _>::TimeHpx(_))
+            | MocIdxType::U32(MocQtyType::<u32, _>::TimeHpx(_)) => Err(
+                "Space-Time MOC not supported. Its indices must be stored on 64 bits".to_string(),
+            ),
+            _ => Err("MOC not supported. Must be a Space-Time MOC".to_string()),
+        }
+    }
+
+    /// Parse a Space-Time MOC from its Aladin JSON serialization
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let moc =
+            moclib::deser::json::cellmoc2d_from_json_aladin::<u64, Time<u64>, u64, Hpx<u64>>(json)
+                .map_err(|e| e.to_string())?;
+
+        Ok(Self::from_range_moc2_elems(
+            moc.into_cell_moc2_iter().into_range_moc2_iter(),
+        ))
+    }
+
+    /// The first and last julian days covered
+    pub fn jd_range(&self) -> Option<(f64, f64)> {
+        self.elems
+            .iter()
+            .flat_map(|(tmoc, _)| {
+                let ranges = tmoc.0.moc_ranges();
+                ranges
+                    .iter()
+                    .next()
+                    .zip(ranges.iter().last())
+                    .map(|(first, last)| (first.start, last.end))
+            })
+            .reduce(|(s1, e1), (s2, e2)| (s1.min(s2), e1.max(e2)))
+            .map(|(start, end)| {
+                (
+                    (start as f64) / MICROSEC_PER_DAY,
+                    (end as f64) / MICROSEC_PER_DAY,
+                )
+            })
+    }
+
+    /// The spatial coverage observed during a time coverage
+    pub fn spatial_coverage(&self, time: &TimeCoverage) -> HEALPixCoverage {
+        self.elems
+            .iter()
+            .filter(|(tmoc, _)| tmoc.is_intersecting(time))
+            .fold(HEALPixCoverage::empty(self.depth), |acc, (_, smoc)| {
+                acc.union(smoc)
+            })
+    }
+
+    /// The spatial coverage observed whatever the time
+    pub fn full_spatial_coverage(&self) -> HEALPixCoverage {
+        self.elems
+            .iter()
+            .fold(HEALPixCoverage::empty(self.depth), |acc, (_, smoc)| {
+                acc.union(smoc)
+            })
+    }
+
+    /// The time coverage during which a sky position has been observed
+    pub fn time_coverage(&self, lonlat: &LonLatT<f64>) -> TimeCoverage {
+        self.elems
+            .iter()
+            .filter(|(_, smoc)| smoc.contains_lonlat(lonlat))
+            .fold(
+                TimeCoverage::empty(Time::<u64>::MAX_DEPTH),
+                |acc, (tmoc, _)| acc.union(tmoc),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SpaceTimeCoverage, TimeCoverage, MICROSEC_PER_DAY};
+    use crate::math::angle::Angle;
+    use crate::math::lonlat::LonLatT;
+
+    // Duration in days of `n` time cells of depth 25
+    fn days(n: u64) -> f64 {
+        ((n << 36) as f64) / MICROSEC_PER_DAY
+    }
+
+    // Two observations: the base cell 0 during the time cells 0 to 3,
+    // then the base cell 5 during the time cells 8 and 9
+    fn stmoc() -> SpaceTimeCoverage {
+        let json = r#"[
+            {"t": {"25": [0, 1, 2, 3]}, "s": {"0": [0]}},
+            {"t": {"25": [8, 9]}, "s": {"0": [5]}}
+        ]"#;
+
+        SpaceTimeCoverage::from_json(json).unwrap()
+    }
+
+    fn base_cell_center(idx: u64) -> LonLatT<f64> {
+        let (lon, lat) = ::healpix::nested::center(0, idx);
+        LonLatT::new(Angle(lon), Angle(lat))
+    }
+
+    #[test]
+    fn time_range() {
+        assert_eq!(stmoc().jd_range(), Some((0.0, days(10))));
+    }
+
+    #[test]
+    fn spatial_coverage_during_a_time_range() {
+        let stmoc = stmoc();
+
+        let first = stmoc.spatial_coverage(&TimeCoverage::from_jd_range(0.0, days(1)));
+        assert!(first.contains_lonlat(&base_cell_center(0)));
+        assert!(!first.contains_lonlat(&base_cell_center(5)));
+
+        let second = stmoc.spatial_coverage(&TimeCoverage::from_jd_range(days(8), days(9)));
+        assert!(!second.contains_lonlat(&base_cell_center(0)));
+        assert!(second.contains_lonlat(&base_cell_center(5)));
+
+        let none = stmoc.spatial_coverage(&TimeCoverage::from_jd_range(days(5), days(6)));
+        assert!(none.is_empty());
+
+        let sky_fraction = stmoc.full_spatial_coverage().sky_fraction();
+        assert!((sky_fraction - 2.0 / 12.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn time_coverage_at_a_position() {
+        let stmoc = stmoc();
+
+        assert_eq!(
+            stmoc.time_coverage(&base_cell_center(5)).to_jd_ranges(),
+            vec![days(8), days(10)]
+        );
+        assert!(stmoc
+            .time_coverage(&base_cell_center(3))
+            .to_jd_ranges()
+            .is_empty());
+    }
+}
@@ -85,8 +85,6 @@ mod utils;
 
 use math::projection::*;
 
-use moclib::moc::RangeMOCIntoIterator;
-//use votable::votable::VOTableWrapper;
 use crate::tile_fetcher::HiPSLocalFiles;
 use wasm_bindgen::prelude::*;
 use web_sys::HtmlElement;
@@ -100,20 +98,26 @@ mod shaders;
 
 mod coosys;
 mod downloader;
-mod fifo_cache;
+mod fits;
 mod healpix;
 mod inertia;
+mod lru_cache;
 pub mod math;
 pub mod renderable;
 mod shader;
 mod survey;
 mod tile_fetcher;
 mod time;
+mod votable;
 
-use crate::downloader::request::moc::from_fits_hpx;
+use crate::downloader::request::moc::{from_fits_hpx, from_fits_time};
 use crate::{
-    camera::CameraViewPort, healpix::coverage::HEALPixCoverage, math::lonlat::LonLatT,
-    shader::ShaderManager, time::DeltaTime,
+    camera::CameraViewPort,
+    healpix::coverage::HEALPixCoverage,
+    healpix::st_coverage::{SpaceTimeCoverage, TimeCoverage},
+    math::lonlat::LonLatT,
+    shader::ShaderManager,
+    time::DeltaTime,
 };
 use moclib::deser::fits;
 use moclib::deser::fits::MocIdxType;
@@ -123,7 +127,7 @@ use std::io::Cursor;
 
 use al_api::color::{Color, ColorRGBA};
 use al_api::coo_system::CooSystem;
-use al_api::hips::HiPSProperties;
+use al_api::hips::{HiPSCfg, HiPSProperties};
 
 use al_core::colormap::Colormaps;
 use al_core::Colormap;
@@ -136,7 +140,7 @@ use crate::healpix::cell::HEALPixCell;
 use math::angle::ArcDeg;
 use moclib::{
     moc::{CellMOCIntoIterator, CellMOCIterator, RangeMOCIterator},
-    qty::Hpx,
+    qty::{Hpx, MocQty, Time},
 };
 
 #[wasm_bindgen]
@@ -150,6 +154,7 @@ pub struct WebClient {
 }
 
 use al_api::hips::ImageMetadata;
+use al_api::image::{CutAlgorithm, HistogramParams};
 use std::convert::TryInto;
 #[wasm_bindgen]
 impl WebClient {
@@ -219,8 +224,7 @@ impl WebClient {
     /// * `width` - The width in pixels of the view
     /// * `height` - The height in pixels of the view
     pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
-        self.app.resize(width, height);
-        Ok(())
+        self.app.resize(width, height)
     }
 
     /// Set the type of projections
@@ -244,7 +248,7 @@ impl WebClient {
             "ZEA" => self
                 .app
                 .set_projection(ProjectionType::Zea(mapproj::zenithal::zea::Zea::new())), /* Equal-area 		         */
-            /*"FEYE" => self
+            "FEYE" => self
                 .app
                 .set_projection(ProjectionType::Feye(mapproj::zenithal::feye::Feye::new())),
             "AIR" => {
@@ -252,19 +256,19 @@ impl WebClient {
                 //air_proj.set_n_iter(10);
                 //air_proj.set_eps(1e-12);
                 self.app.set_projection(ProjectionType::Air(air_proj))
-            }*/
+            }
             //"AZP",
-            /*"ARC" => self
+            "ARC" => self
                 .app
                 .set_projection(ProjectionType::Arc(mapproj::zenithal::arc::Arc::new())),
             "NCP" => self
                 .app
-                .set_projection(ProjectionType::Ncp(mapproj::zenithal::ncp::Ncp::new())),*/
+                .set_projection(ProjectionType::Ncp(mapproj::zenithal::ncp::Ncp::new())),
             // Cylindrical
             "MER" => self
                 .app
                 .set_projection(ProjectionType::Mer(mapproj::cylindrical::mer::Mer::new())),
-            /*"CAR" => self
+            "CAR" => self
                 .app
                 .set_projection(ProjectionType::Car(mapproj::cylindrical::car::Car::new())),
             "CEA" => self
@@ -272,33 +276,34 @@ impl WebClient {
                 .set_projection(ProjectionType::Cea(mapproj::cylindrical::cea::Cea::new())),
             "CYP" => self
                 .app
-                .set_projection(ProjectionType::Cyp(mapproj::cylindrical::cyp::Cyp::new())),*/
+                .set_projection(ProjectionType::Cyp(mapproj::cylindrical::cyp::Cyp::new())),
             // Pseudo-cylindrical
             "AIT" => self
                 .app
                 .set_projection(ProjectionType::Ait(mapproj::pseudocyl::ait::Ait::new())),
-            /*"PAR" => self
+            "PAR" => self
                 .app
                 .set_projection(ProjectionType::Par(mapproj::pseudocyl::par::Par::new())),
             "SFL" => self
                 .app
-                .set_projection(ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl::new())),*/
+                .set_projection(ProjectionType::Sfl(mapproj::pseudocyl::sfl::Sfl::new())),
             "MOL" => {
                 let mut mol_proj = mapproj::pseudocyl::mol::Mol::new();
                 mol_proj.set_n_iter(10);
                 mol_proj.set_epsilon(1e-12);
 
                 self.app.set_projection(ProjectionType::Mol(mol_proj))
-            } // Conic
-            /*"COD" => self
+            }
+            // Conic
+            "COD" => self
                 .app
                 .set_projection(ProjectionType::Cod(mapproj::conic::cod::Cod::new())),
             // Hybrid
             "HPX" => self
                 .app
-                .set_projection(ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx::new())),*/
+                .set_projection(ProjectionType::Hpx(mapproj::hybrid::hpx::Hpx::new())),
             _ => Err(JsValue::from_str(
-                "Not a valid projection name. AIT, ZEA, SIN, STG, TAN, MOL and MER are accepted",
+                "Not a valid projection name. TAN, STG, SIN, ZEA, FEYE, AIR, ARC, NCP, MER, CAR, CEA, CYP, AIT, PAR, SFL, MOL, COD and HPX are accepted",
             )),
         }
     }
@@ -367,6 +372,12 @@ impl WebClient {
     /// * If the number of surveys is greater than 4. For the moment, due to the limitations
     ///   of WebGL2 texture units on some architectures, the total number of surveys rendered is
     ///   limited to 4.
+    ///
+    /// # Request options
+    ///
+    /// An optional `requestOptions: {headers, credentials, refreshToken}` object customizes
+    /// the requests of the HiPS. `refreshToken` is a function giving a new token (or a promise of it),
+    /// called when the server answers HTTP 401 and sent as a bearer `Authorization` header.
     #[wasm_bindgen(js_name = addHiPS)]
     pub fn add_image_hips(
         &mut self,
@@ -374,22 +385,63 @@ impl WebClient {
         files: Option<HiPSLocalFiles>,
     ) -> Result<(), JsValue> {
         // Deserialize the survey objects that compose the survey
-        let hips = serde_wasm_bindgen::from_value(hips)?;
-        self.app.add_image_hips(hips, files)?;
+        let mut cfg: HiPSCfg = serde_wasm_bindgen::from_value(hips.clone())?;
+        // Functions cannot be deserialized, the token refresh callback is retrieved by hand
+        let request_options = js_sys::Reflect::get(&hips, &"requestOptions".into())?;
+        if request_options.is_object() {
+            cfg.request_options.refresh_token =
+                js_sys::Reflect::get(&request_options, &"refreshToken".into())?
+                    .dyn_into::<js_sys::Function>()
+                    .ok();
+        }
+
+        self.app.add_image_hips(cfg, files)?;
 
         Ok(())
     }
 
+    /// Add a RGB composite layer or update its config if it already exists
+    ///
+    /// The three channels are given by existing HiPS or FITS image layers, drawn
+    /// whether they are visible or not. They are combined with the asinh stretch
+    /// of Lupton et al. (2004)
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - An object `{layer, red, green, blue, stretch, meta}`:
+    ///   * `red`, `green`, `blue` - The channels `{layer, minCut, maxCut}`, the cuts
+    ///     of the channel layer being taken if not given
+    ///   * `stretch` - An optional object `{Q, alpha}` (default to Q = 8 and alpha = 1)
+    ///   * `meta` - The opacity, blending and tonal corrections of the composite
+    #[wasm_bindgen(js_name = addRGBComposite)]
+    pub fn add_rgb_composite(&mut self, cfg: JsValue) -> Result<(), JsValue> {
+        let cfg = serde_wasm_bindgen::from_value(cfg)?;
+        self.app.add_rgb_composite(cfg)
+    }
+
+    /// Add a FITS image layer
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The bytes of the FITS file
+    /// * `cfg` - The color configuration of the layer
+    /// * `layer` - The name of the layer
+    /// * `cut_algorithm` - The algorithm computing the cuts, an object
+    ///   `{algorithm: 'percentile' | 'zscale' | 'sigmaClip', ...parameters}`.
+    ///   The 1st and 99th percentiles are taken if not given.
     #[wasm_bindgen(js_name = addImageFITS)]
     pub fn add_image_fits(
         &mut self,
         stream: web_sys::ReadableStream,
         cfg: JsValue,
         layer: String,
+        cut_algorithm: JsValue,
     ) -> Result<js_sys::Promise, JsValue> {
         let cfg: ImageMetadata = serde_wasm_bindgen::from_value(cfg)?;
+        let cut_algorithm: Option<CutAlgorithm> = serde_wasm_bindgen::from_value(cut_algorithm)?;
 
-        self.app.add_image_fits(stream, cfg, layer)
+        self.app
+            .add_image_fits(stream, cfg, layer, cut_algorithm.unwrap_or_default())
     }
 
     #[wasm_bindgen(js_name = addImageWithWCS)]
@@ -438,6 +490,20 @@ impl WebClient {
         self.app.set_hips_url(&cdid, new_url)
     }
 
+    /// Set the frame displayed of a HiPS cube
+    ///
+    /// The tiles of the frames near the current one are kept in memory so that
+    /// going back and forth between frames does not fetch them again
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the HiPS cube layer
+    /// * `frame` - The 0-based index of the frame, must be < `hips_cube_depth`
+    #[wasm_bindgen(js_name = setHiPSCubeFrame)]
+    pub fn set_hips_cube_frame(&mut self, layer: &str, frame: u32) -> Result<(), JsValue> {
+        self.app.set_hips_cube_frame(layer, frame)
+    }
+
     #[wasm_bindgen(js_name = getImageMetadata)]
     pub fn get_layer_cfg(&self, layer: String) -> Result<ImageMetadata, JsValue> {
         self.app.get_layer_cfg(&layer)
@@ -714,6 +780,109 @@ impl WebClient {
         vertices.into_boxed_slice()
     }
 
+    /// Propagate a batch of source positions from one epoch to another
+    ///
+    /// Positions must be given in the ICRS coo system.
+    /// Unknown values can be given as NaN. `parallax` and `rv` can also be left empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `ra` - Right ascensions in degrees
+    /// * `dec` - Declinations in degrees
+    /// * `pm_ra` - Proper motions in right ascension, multiplied by cos(dec), in mas/yr
+    /// * `pm_dec` - Proper motions in declination in mas/yr
+    /// * `parallax` - Parallaxes in mas
+    /// * `rv` - Radial velocities in km/s
+    /// * `epoch` - The julian epoch of the positions given (e.g. 2016.0 for Gaia DR3)
+    /// * `target_epoch` - The julian epoch at which the positions are computed
+    ///
+    /// # Return
+    ///
+    /// The flattened list of the (ra, dec) positions in degrees at `target_epoch`
+    #[wasm_bindgen(js_name = propagateToEpoch)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn propagate_to_epoch(
+        &self,
+        ra: &[f64],
+        dec: &[f64],
+        pm_ra: &[f64],
+        pm_dec: &[f64],
+        parallax: &[f64],
+        rv: &[f64],
+        epoch: f64,
+        target_epoch: f64,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let num_sources = ra.len();
+        if dec.len() != num_sources || pm_ra.len() != num_sources || pm_dec.len() != num_sources
+        {
+            return Err(JsValue::from_str(
+                "ra, dec, pm_ra and pm_dec must have the same length",
+            ));
+        }
+        if (!parallax.is_empty() && parallax.len() != num_sources)
+            || (!rv.is_empty() && rv.len() != num_sources)
+        {
+            return Err(JsValue::from_str(
+                "parallax and rv must be empty or have the same length as ra",
+            ));
+        }
+
+        let positions = (0..num_sources)
+            .flat_map(|i| {
+                let src = math::astrometry::Astrometry {
+                    lonlat: LonLatT::new(ArcDeg(ra[i]).into(), ArcDeg(dec[i]).into()),
+                    pm_ra: pm_ra[i],
+                    pm_dec: pm_dec[i],
+                    parallax: parallax.get(i).copied().unwrap_or(f64::NAN),
+                    radial_velocity: rv.get(i).copied().unwrap_or(f64::NAN),
+                };
+
+                let lonlat = src.propagate(epoch, target_epoch).lonlat;
+                [lonlat.lon().to_degrees(), lonlat.lat().to_degrees()]
+            })
+            .collect::<Vec<_>>();
+
+        Ok(positions.into_boxed_slice())
+    }
+
+    /// Precess a batch of FK5 positions from one equinox to another
+    ///
+    /// # Arguments
+    ///
+    /// * `ra` - Right ascensions in degrees
+    /// * `dec` - Declinations in degrees
+    /// * `equinox` - The julian epoch of the equinox of the given positions (e.g. 2000.0)
+    /// * `target_equinox` - The julian epoch of the equinox to precess to
+    ///
+    /// # Return
+    ///
+    /// The flattened list of the precessed (ra, dec) positions in degrees
+    #[wasm_bindgen(js_name = precessFK5)]
+    pub fn precess_fk5(
+        &self,
+        ra: &[f64],
+        dec: &[f64],
+        equinox: f64,
+        target_equinox: f64,
+    ) -> Result<Box<[f64]>, JsValue> {
+        if ra.len() != dec.len() {
+            return Err(JsValue::from_str("ra and dec must have the same length"));
+        }
+
+        let positions = ra
+            .iter()
+            .zip(dec.iter())
+            .flat_map(|(&ra, &dec)| {
+                let lonlat = LonLatT::new(ArcDeg(ra).into(), ArcDeg(dec).into());
+                let lonlat = math::astrometry::precess(&lonlat, equinox, target_equinox);
+
+                [lonlat.lon().to_degrees(), lonlat.lat().to_degrees()]
+            })
+            .collect::<Vec<_>>();
+
+        Ok(positions.into_boxed_slice())
+    }
+
     /*#[wasm_bindgen(js_name = drawCatalog)]
     pub fn world_to_screen_vec(&self, lon: &[f64], lat: &[f64], shape: &'static str, pixel_size) -> Box<[f64]> {
         let vertices = Time::measure_perf("projection rust side", || {
@@ -799,10 +968,15 @@ impl WebClient {
 
     /// Add a catalog rendered as a heatmap.
     ///
+    /// The sources are indexed by HEALPix cells so that only a subset of the sources lying in the view
+    /// is rendered. Each source is splatted with a gaussian kernel and the resulting density map
+    /// is colored with a colormap. A catalog already added with the same name is replaced.
+    ///
     /// # Arguments
     ///
     /// * `name_catalog` - The name of the catalog
-    /// * `data` - The list of the catalog sources.
+    /// * `data` - The list of the catalog sources given as a flat array (e.g. a `Float64Array`)
+    ///   of ICRS (ra, dec) coordinates in degrees: `[ra0, dec0, ra1, dec1, ...]`.
     /// * `colormap` - The name of the colormap. Check out the list of possible colormaps names `getAvailableColormapList`.
     #[wasm_bindgen(js_name = addCatalog)]
     pub fn add_catalog(
@@ -811,9 +985,187 @@ impl WebClient {
         data: JsValue,
         colormap: String,
     ) -> Result<(), JsValue> {
-        self.app.add_catalog(name_catalog, data, colormap);
+        self.app.add_catalog(name_catalog, data, colormap)
+    }
 
-        Ok(())
+    /// Add a catalog rendered with one marker per source.
+    ///
+    /// Only the sources lying in the view are drawn. The markers are instanced on the GPU
+    /// so that catalogs of millions of sources can be displayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog. A catalog already added with the same name is replaced.
+    /// * `cfg` - The rendering options: `{shape, size, color, opacity, sizeMapping, colorMapping}`.
+    ///   The shape can be `circle`, `square`, `plus`, `cross`, `rhomb` or `triangle`, the size is given in pixels.
+    ///   `sizeMapping: {min, max, minSize, maxSize}` maps linearly the values of `size_column` to sizes in pixels.
+    ///   `colorMapping: {colormap, min, max, reversed}` maps the values of `color_column` to the colors of a colormap.
+    /// * `coo` - The ICRS (ra, dec) coordinates of the sources in degrees: `[ra0, dec0, ra1, dec1, ...]`.
+    /// * `size_column` - The values of the column mapped to the marker sizes (one per source).
+    /// * `color_column` - The values of the column mapped to the marker colors (one per source).
+    #[wasm_bindgen(js_name = addCatalogMarkers)]
+    pub fn add_catalog_markers(
+        &mut self,
+        name_catalog: String,
+        cfg: JsValue,
+        coo: &[f64],
+        size_column: Option<Box<[f32]>>,
+        color_column: Option<Box<[f32]>>,
+    ) -> Result<(), JsValue> {
+        let cfg = serde_wasm_bindgen::from_value(cfg)?;
+
+        self.app.add_catalog_markers(
+            name_catalog,
+            cfg,
+            coo,
+            size_column.as_deref(),
+            color_column.as_deref(),
+        )
+    }
+
+    /// Remove a catalog, whatever it is rendered as a heatmap or with markers
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog to remove
+    #[wasm_bindgen(js_name = removeCatalog)]
+    pub fn remove_catalog(&mut self, name_catalog: String) -> Result<(), JsValue> {
+        self.app.remove_catalog(name_catalog)
+    }
+
+    /// Get the sources of a catalog near a screen position
+    ///
+    /// Returns the indices of the sources, in the order they were given when adding the
+    /// catalog, sorted from the nearest to the farthest from the position
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog
+    /// * `pos_x` - The x screen coordinate in pixels
+    /// * `pos_y` - The y screen coordinate in pixels
+    /// * `tolerance` - The maximum distance in pixels between a source and the position
+    #[wasm_bindgen(js_name = pickCatalogSources)]
+    pub fn pick_catalog_sources(
+        &self,
+        name_catalog: &str,
+        pos_x: f64,
+        pos_y: f64,
+        tolerance: f64,
+    ) -> Result<Box<[u32]>, JsValue> {
+        let sources =
+            self.app
+                .pick_catalog_sources(name_catalog, &Vector2::new(pos_x, pos_y), tolerance)?;
+
+        Ok(sources.into_boxed_slice())
+    }
+
+    /// Attach footprints (e.g. the observed regions) to a catalog
+    ///
+    /// The footprints replace the ones previously attached to the catalog and
+    /// are removed with it
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog
+    /// * `coo` - The flat list of the ICRS (ra, dec) coordinates in degrees of the footprint vertices
+    /// * `num_vertices` - The number of vertices of each footprint
+    #[wasm_bindgen(js_name = setCatalogFootprints)]
+    pub fn set_catalog_footprints(
+        &mut self,
+        name_catalog: String,
+        coo: &[f64],
+        num_vertices: &[u32],
+    ) -> Result<(), JsValue> {
+        self.app
+            .set_catalog_footprints(name_catalog, coo, num_vertices)
+    }
+
+    /// Get the footprints of a catalog near a screen position
+    ///
+    /// Returns the indices of the footprints, in the order they were given, sorted
+    /// from the nearest to the farthest from the position
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog
+    /// * `pos_x` - The x screen coordinate in pixels
+    /// * `pos_y` - The y screen coordinate in pixels
+    /// * `tolerance` - The maximum distance in pixels between the edges of a footprint and the position
+    #[wasm_bindgen(js_name = pickCatalogFootprints)]
+    pub fn pick_catalog_footprints(
+        &self,
+        name_catalog: &str,
+        pos_x: f64,
+        pos_y: f64,
+        tolerance: f64,
+    ) -> Result<Box<[u32]>, JsValue> {
+        let footprints = self.app.pick_catalog_footprints(
+            name_catalog,
+            &Vector2::new(pos_x, pos_y),
+            tolerance,
+        )?;
+
+        Ok(footprints.into_boxed_slice())
+    }
+
+    /// Get the sources of a catalog inside a screen rectangle
+    ///
+    /// Returns the indices of the sources, in the order they were given when adding the
+    /// catalog, sorted in increasing order
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog
+    /// * `x1`, `y1` - A corner of the rectangle in pixels
+    /// * `x2`, `y2` - The opposite corner of the rectangle in pixels
+    #[wasm_bindgen(js_name = selectCatalogSourcesInRect)]
+    pub fn select_catalog_sources_in_rect(
+        &self,
+        name_catalog: &str,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Result<Box<[u32]>, JsValue> {
+        let vertices = [
+            Vector2::new(x1, y1),
+            Vector2::new(x2, y1),
+            Vector2::new(x2, y2),
+            Vector2::new(x1, y2),
+        ];
+        let sources = self.app.select_catalog_sources(name_catalog, &vertices)?;
+
+        Ok(sources.into_boxed_slice())
+    }
+
+    /// Get the sources of a catalog inside a screen polygon (e.g. a lasso selection)
+    ///
+    /// Returns the indices of the sources, in the order they were given when adding the
+    /// catalog, sorted in increasing order
+    ///
+    /// # Arguments
+    ///
+    /// * `name_catalog` - The name of the catalog
+    /// * `vertices` - The flat list of the (x, y) screen coordinates in pixels of the polygon vertices
+    #[wasm_bindgen(js_name = selectCatalogSourcesInPolygon)]
+    pub fn select_catalog_sources_in_polygon(
+        &self,
+        name_catalog: &str,
+        vertices: &[f64],
+    ) -> Result<Box<[u32]>, JsValue> {
+        if vertices.len() % 2 != 0 {
+            return Err(JsValue::from_str(
+                "The polygon vertices must be given as a flat list of (x, y) pairs",
+            ));
+        }
+
+        let vertices = vertices
+            .chunks_exact(2)
+            .map(|xy| Vector2::new(xy[0], xy[1]))
+            .collect::<Vec<_>>();
+        let sources = self.app.select_catalog_sources(name_catalog, &vertices)?;
+
+        Ok(sources.into_boxed_slice())
     }
 
     /// Set the catalog heatmap colormap
@@ -832,7 +1184,7 @@ impl WebClient {
         Ok(cat_loaded)
     }
 
-    /// Set the catalog heatmap opacity
+    /// Set the opacity of a catalog, rendered as a heatmap or with markers
     ///
     /// # Arguments
     ///
@@ -973,6 +1325,9 @@ impl WebClient {
     /// - An array of 3 items (rgb) for JPG tiles
     /// - An array of 4 items (rgba) for PNG tiles
     /// - A single value for FITS tiles
+    /// - An object `{x, y, value}` for image layers, `x` and `y` being the pixel
+    ///   coordinates in the image. For FITS images, `value` is the physical value
+    ///   (BSCALE/BZERO applied), `null` for BLANK pixels
     ///
     /// # Arguments
     ///
@@ -985,6 +1340,166 @@ impl WebClient {
         Ok(pixel)
     }
 
+    /// Select the slice displayed for the FITS data cubes of an image layer
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the image layer
+    /// * `slice` - The 0-based index of the slice along the third axis
+    #[wasm_bindgen(js_name = setImageSlice)]
+    pub fn set_image_slice(&mut self, layer: &str, slice: u32) -> Result<(), JsValue> {
+        self.app.set_image_slice(layer, slice as u64)
+    }
+
+    /// Recompute the cuts of the FITS images of a layer
+    ///
+    /// The pixel values are sampled when the image is loaded so that the cuts
+    /// can be recomputed without downloading it again
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the image layer
+    /// * `cut_algorithm` - An object `{algorithm, ...parameters}` where `algorithm` is:
+    ///   * `percentile` with `low` and `high` (defaults to 1 and 99)
+    ///   * `zscale` with `contrast` (0.25), `numSamples` (1000), `maxReject` (0.5),
+    ///     `minNpixels` (5), `krej` (2.5) and `maxIterations` (5)
+    ///   * `sigmaClip` with `sigma` (3) and `maxIterations` (5), sigma being estimated from
+    ///     the median absolute deviation
+    ///
+    /// # Returns
+    ///
+    /// The cuts `[min, max]` of the first image of the layer
+    #[wasm_bindgen(js_name = setImageCutAlgorithm)]
+    pub fn set_image_cut_algorithm(
+        &mut self,
+        layer: &str,
+        cut_algorithm: JsValue,
+    ) -> Result<Box<[f32]>, JsValue> {
+        let cut_algorithm: CutAlgorithm = serde_wasm_bindgen::from_value(cut_algorithm)?;
+        let cuts = self.app.set_image_cut_algorithm(layer, cut_algorithm)?;
+
+        Ok(Box::new([cuts.start, cuts.end]))
+    }
+
+    /// Set the cuts of a FITS HiPS layer from the tiles in the view
+    ///
+    /// The cuts are computed from pixel values sampled from the tiles currently
+    /// received for the view, BSCALE and BZERO being applied
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the HiPS layer
+    /// * `cut_algorithm` - An optional object `{algorithm, ...parameters}`, see `setImageCutAlgorithm`.
+    ///   The 1st and 99th percentiles are taken if not given.
+    ///
+    /// # Returns
+    ///
+    /// The new cuts `[min, max]`
+    #[wasm_bindgen(js_name = setHiPSCutsFromView)]
+    pub fn set_hips_cuts_from_view(
+        &mut self,
+        layer: &str,
+        cut_algorithm: JsValue,
+    ) -> Result<Box<[f32]>, JsValue> {
+        let cut_algorithm: Option<CutAlgorithm> = serde_wasm_bindgen::from_value(cut_algorithm)?;
+        let cuts = self
+            .app
+            .set_hips_cuts_from_view(layer, cut_algorithm.unwrap_or_default())?;
+
+        Ok(Box::new([cuts.start, cuts.end]))
+    }
+
+    /// Compute the histogram of the pixel values of a FITS layer
+    ///
+    /// It is computed from the pixel values kept in memory, i.e. those sampled when a FITS image
+    /// is loaded or from the tiles in the view for a FITS HiPS. BSCALE and BZERO are applied
+    /// and blank values excluded.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the layer
+    /// * `params` - An optional object `{numBins, log, min, max}`. `numBins` defaults to 256, `log` set
+    ///   to true gives logarithmically spaced bins, `min` and `max` give the range of the bins
+    ///   (the extrema of the values if not given)
+    ///
+    /// # Returns
+    ///
+    /// An object `{counts, edges, numValues}`, `edges` being the `numBins + 1` edges of the bins
+    #[wasm_bindgen(js_name = getLayerHistogram)]
+    pub fn get_layer_histogram(&self, layer: &str, params: JsValue) -> Result<JsValue, JsValue> {
+        let params: Option<HistogramParams> = serde_wasm_bindgen::from_value(params)?;
+        let histogram = self
+            .app
+            .get_layer_histogram(layer, &params.unwrap_or_default())?;
+
+        Ok(serde_wasm_bindgen::to_value(&histogram)?)
+    }
+
+    /// Get the third axis of the FITS data cube of an image layer
+    ///
+    /// # Returns
+    ///
+    /// `undefined` if the layer does not contain any data cube. Otherwise an object
+    /// `{numSlices, slice, ctype, cunit, values}`, `values` being the world coordinate
+    /// (e.g. the wavelength) of each slice computed from CRVAL3, CDELT3 and CRPIX3
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - The name of the image layer
+    #[wasm_bindgen(js_name = getImageCubeAxis)]
+    pub fn get_image_cube_axis(&self, layer: &str) -> Result<Option<JsValue>, JsValue> {
+        self.app.get_image_cube_axis(layer)
+    }
+
+    /// Get the mirrors of a HiPS layer
+    ///
+    /// The tiles are requested from another mirror when the current one starts failing.
+    ///
+    /// # Returns
+    ///
+    /// An array of objects `{url, current, stats}`:
+    /// * `url` - The url of the mirror
+    /// * `current` - Whether the tiles are currently requested from this mirror
+    /// * `stats` - An object `{latency, errorRate, numRequests}` giving the moving averages of the duration in milliseconds
+    ///   of the successful tile requests and of the ratio of failed ones, and the number of requests resolved
+    #[wasm_bindgen(js_name = getHiPSMirrors)]
+    pub fn get_hips_mirrors(&self, layer: &str) -> Result<JsValue, JsValue> {
+        let mirrors = self.app.get_hips_mirrors(layer)?;
+        Ok(serde_wasm_bindgen::to_value(&mirrors)?)
+    }
+
+    /// Get the statistics of the cache of the received tiles
+    ///
+    /// # Returns
+    ///
+    /// An object `{hits, misses, evictions, numEntries, numPinned, size, budget}`:
+    /// * `hits`, `misses` - The number of queried tiles found or not in the cache
+    /// * `evictions` - The number of tiles removed to stay within the budget
+    /// * `numEntries`, `numPinned` - The number of cached tiles and of those never evicted (allsky and root tiles)
+    /// * `size`, `budget` - The size of the evictable tiles and the memory budget in bytes
+    #[wasm_bindgen(js_name = getCacheStats)]
+    pub fn get_cache_stats(&self) -> Result<JsValue, JsValue> {
+        let stats = self.app.get_cache_stats();
+        Ok(serde_wasm_bindgen::to_value(&stats)?)
+    }
+
+    /// Set the memory budget in bytes of the cache of the received tiles
+    ///
+    /// The least recently used tiles are evicted when it is exceeded. It defaults to 256MB.
+    #[wasm_bindgen(js_name = setCacheBudget)]
+    pub fn set_cache_budget(&mut self, budget: usize) {
+        self.app.set_cache_budget(budget);
+    }
+
+    /// Set the GPU memory budget in bytes of the textures of the HiPS layers
+    ///
+    /// It is shared equally between the HiPS, whose textures are sized from the view
+    /// within their share of the budget. It defaults to 512MB.
+    #[wasm_bindgen(js_name = setGpuMemoryBudget)]
+    pub fn set_gpu_memory_budget(&mut self, budget: usize) -> Result<(), JsValue> {
+        self.app.set_gpu_memory_budget(budget)
+    }
+
     #[wasm_bindgen(js_name = getVisibleCells)]
     pub fn get_visible_cells(&self, depth: u8) -> Result<JsValue, JsValue> {
         let cells = self.app.get_visible_cells(depth);
@@ -1001,17 +1516,27 @@ impl WebClient {
         self.app.draw_grid_labels()
     }
 
+    /// Parse a VOTable document
+    ///
+    /// The TABLEDATA, BINARY and BINARY2 serializations are supported.
+    ///
+    /// # Returns
+    ///
+    /// An object `{tables: [{name, ID, description, fields, columns}]}`.
+    /// Each field gives the metadata of a column (name, ID, datatype, arraysize,
+    /// ucd, unit, utype, width, precision, description). Columns are given in the
+    /// order of the fields. Float columns are typed arrays (NaN being null),
+    /// integer columns too if they do not contain any null value. Other columns
+    /// are javascript arrays, null cells being `null`.
+    ///
+    /// # Arguments
+    ///
+    /// * `s` - The VOTable document
     #[wasm_bindgen(js_name = parseVOTable)]
-    pub fn parse_votable(&mut self, _s: &str) -> Result<JsValue, JsValue> {
-        /*let votable: VOTableWrapper<votable::impls::mem::InMemTableDataRows> =
-            votable::votable::VOTableWrapper::from_ivoa_xml_str(s)
-                .map_err(|err| JsValue::from_str(&format!("Error parsing votable: {:?}", err)))?;
-
-        let votable = serde_wasm_bindgen::to_value(&votable)
-            .map_err(|_| JsValue::from_str("cannot convert votable to js type"))?;
+    pub fn parse_votable(&mut self, s: &str) -> Result<JsValue, JsValue> {
+        let votable = votable::VOTable::parse(s)?;
 
-        Ok(votable)*/
-        Ok(JsValue::null())
+        votable.to_js()
     }
 
     #[wasm_bindgen(js_name = addJSONMoc)]
@@ -1106,6 +1631,198 @@ impl WebClient {
         Ok(())
     }
 
+    /// Add a Space-Time MOC from a FITS file
+    ///
+    /// Its spatial coverage over the whole time range is displayed as a MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the MOC layer
+    /// * `data` - The bytes of the FITS file
+    #[wasm_bindgen(js_name = addFITSSTMOC)]
+    pub fn add_fits_stmoc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        data: &[u8],
+    ) -> Result<(), JsValue> {
+        let stmoc = SpaceTimeCoverage::from_fits(data).map_err(|err| JsValue::from_str(&err))?;
+
+        self.app.add_stmoc(params.clone(), stmoc)
+    }
+
+    /// Add a Space-Time MOC from its JSON serialization
+    ///
+    /// Its spatial coverage over the whole time range is displayed as a MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the MOC layer
+    /// * `data` - The JSON object of the Space-Time MOC
+    #[wasm_bindgen(js_name = addJSONSTMOC)]
+    pub fn add_json_stmoc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        data: &JsValue,
+    ) -> Result<(), JsValue> {
+        let str: String = js_sys::JSON::stringify(data)?.into();
+
+        let stmoc = SpaceTimeCoverage::from_json(&str)
+            .map_err(|err| JsValue::from(js_sys::Error::new(&err)))?;
+
+        self.app.add_stmoc(params.clone(), stmoc)
+    }
+
+    /// Display the spatial coverage of a Space-Time MOC for a time range
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The Space-Time MOC
+    /// * `jd_min` - The start of the time range in julian days
+    /// * `jd_max` - The end of the time range in julian days
+    #[wasm_bindgen(js_name = setSTMOCTimeRange)]
+    pub fn set_stmoc_time_range(
+        &mut self,
+        params: &al_api::moc::MOC,
+        jd_min: f64,
+        jd_max: f64,
+    ) -> Result<(), JsValue> {
+        let time = TimeCoverage::from_jd_range(jd_min, jd_max);
+
+        self.app.set_stmoc_time_coverage(params, &time)
+    }
+
+    /// Get the first and last julian days covered by a Space-Time MOC
+    #[wasm_bindgen(js_name = getSTMOCTimeRange)]
+    pub fn get_stmoc_time_range(
+        &self,
+        params: &al_api::moc::MOC,
+    ) -> Result<Option<Box<[f64]>>, JsValue> {
+        let stmoc = self
+            .app
+            .get_stmoc(params)
+            .ok_or_else(|| JsValue::from(js_sys::Error::new("Space-Time MOC not found")))?;
+
+        Ok(stmoc
+            .jd_range()
+            .map(|(jd_min, jd_max)| Box::new([jd_min, jd_max]) as Box<[f64]>))
+    }
+
+    /// Get the time coverage of a Space-Time MOC at a sky position
+    ///
+    /// Returns the time ranges during which the position has been observed
+    /// as a flat list of (start, end) julian days
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The Space-Time MOC
+    /// * `ra` - The right ascension of the position in degrees
+    /// * `dec` - The declination of the position in degrees
+    #[wasm_bindgen(js_name = getSTMOCTimeCoverage)]
+    pub fn get_stmoc_time_coverage(
+        &self,
+        params: &al_api::moc::MOC,
+        ra: f64,
+        dec: f64,
+    ) -> Result<Box<[f64]>, JsValue> {
+        let stmoc = self
+            .app
+            .get_stmoc(params)
+            .ok_or_else(|| JsValue::from(js_sys::Error::new("Space-Time MOC not found")))?;
+        let location = LonLatT::new(ArcDeg(ra).into(), ArcDeg(dec).into());
+
+        Ok(stmoc
+            .time_coverage(&location)
+            .to_jd_ranges()
+            .into_boxed_slice())
+    }
+
+    /// Parse a Time MOC from a FITS file
+    ///
+    /// Returns the time ranges covered as a flat list of (start, end) julian days
+    #[wasm_bindgen(js_name = parseFITSTMOC)]
+    pub fn parse_fits_tmoc(&self, data: &[u8]) -> Result<Box<[f64]>, JsValue> {
+        let moc = match fits::from_fits_ivoa_custom(Cursor::new(data), false)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?
+        {
+            MocIdxType::U16(MocQtyType::<u16, _>::Time(moc)) => Ok(from_fits_time(moc)),
+            MocIdxType::U32(MocQtyType::<u32, _>::Time(moc)) => Ok(from_fits_time(moc)),
+            MocIdxType::U64(MocQtyType::<u64, _>::Time(moc)) => Ok(from_fits_time(moc)),
+            _ => Err(JsValue::from_str("MOC not supported. Must be a Time MOC")),
+        }?;
+
+        Ok(TimeCoverage(moc).to_jd_ranges().into_boxed_slice())
+    }
+
+    /// Parse a Time MOC from its JSON serialization
+    ///
+    /// Returns the time ranges covered as a flat list of (start, end) julian days
+    #[wasm_bindgen(js_name = parseJSONTMOC)]
+    pub fn parse_json_tmoc(&self, data: &JsValue) -> Result<Box<[f64]>, JsValue> {
+        let str: String = js_sys::JSON::stringify(data)?.into();
+
+        let moc = moclib::deser::json::from_json_aladin::<u64, Time<u64>>(&str)
+            .map_err(|e| JsValue::from(js_sys::Error::new(&e.to_string())))?
+            .into_cell_moc_iter()
+            .ranges()
+            .into_range_moc();
+
+        Ok(TimeCoverage(moc).to_jd_ranges().into_boxed_slice())
+    }
+
+    /// Add the coverage of the footprint of an image layer as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `layer` - The name of the image layer
+    /// * `depth` - The depth of the MOC
+    #[wasm_bindgen(js_name = addImageMOC)]
+    pub fn add_image_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        layer: &str,
+        depth: u8,
+    ) -> Result<(), JsValue> {
+        check_moc_depth(depth)?;
+        let moc = self.app.get_image_coverage(layer, depth)?;
+
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add the coverage of the sources of a catalog as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `name_catalog` - The name of the catalog
+    /// * `depth` - The depth of the MOC
+    #[wasm_bindgen(js_name = addCatalogMOC)]
+    pub fn add_catalog_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        name_catalog: &str,
+        depth: u8,
+    ) -> Result<(), JsValue> {
+        check_moc_depth(depth)?;
+        let moc = self.app.get_catalog_coverage(name_catalog, depth)?;
+
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add the coverage of the current field of view as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `depth` - The depth of the MOC
+    #[wasm_bindgen(js_name = addFoVMOC)]
+    pub fn add_fov_moc(&mut self, params: &al_api::moc::MOC, depth: u8) -> Result<(), JsValue> {
+        check_moc_depth(depth)?;
+        let moc = self.app.get_fov_coverage(depth);
+
+        self.app.add_moc(params.clone(), moc)
+    }
+
     #[wasm_bindgen(js_name = removeMoc)]
     pub fn remove_moc(&mut self, params: &al_api::moc::MOC) -> Result<(), JsValue> {
         self.app.remove_moc(params)?;
@@ -1136,26 +1853,45 @@ impl WebClient {
         Ok(moc.contains_lonlat(&location))
     }
 
+    /// Serialize a MOC
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The MOC to serialize
+    /// * `format` - The serialization format:
+    ///   * "json" - Aladin JSON, returned as a string
+    ///   * "ascii" - IVOA MOC 2.0 ASCII, returned as a string
+    ///   * "fits" - IVOA MOC 2.0 FITS, returned as a Uint8Array
     #[wasm_bindgen(js_name = mocSerialize)]
     pub fn moc_serialize(
         &mut self,
         params: &al_api::moc::MOC,
-        _format: String, // todo support the fits/ascii serialization
+        format: String,
     ) -> Result<JsValue, JsValue> {
-        let moc = self
-            .app
-            .get_moc(params)
-            .ok_or_else(|| JsValue::from(js_sys::Error::new("MOC not found")))?;
+        let moc = self.get_moc(params)?;
 
-        let mut buf: Vec<u8> = Default::default();
-        let json = (&moc.0)
-            .into_range_moc_iter()
-            .cells()
-            .to_json_aladin(None, &mut buf)
-            .map(|()| unsafe { String::from_utf8_unchecked(buf) })
-            .map_err(|err| JsValue::from_str(&format!("{:?}", err)))?;
+        match format.to_lowercase().as_str() {
+            "json" => {
+                let json = moc.to_json().map_err(|err| JsValue::from_str(&err))?;
+
+                serde_wasm_bindgen::to_value(&json)
+                    .map_err(|err| JsValue::from_str(&format!("{:?}", err)))
+            }
+            "ascii" => {
+                let ascii = moc.to_ascii().map_err(|err| JsValue::from_str(&err))?;
+
+                Ok(JsValue::from_str(&ascii))
+            }
+            "fits" => {
+                let bytes = moc.to_fits().map_err(|err| JsValue::from_str(&err))?;
 
-        serde_wasm_bindgen::to_value(&json).map_err(|err| JsValue::from_str(&format!("{:?}", err)))
+                Ok(js_sys::Uint8Array::from(&bytes[..]).into())
+            }
+            _ => Err(JsValue::from_str(&format!(
+                "{} MOC serialization format not supported. Must be json, ascii or fits",
+                format
+            ))),
+        }
     }
 
     #[wasm_bindgen(js_name = getMOCSkyFraction)]
@@ -1166,6 +1902,156 @@ impl WebClient {
             0.0
         }
     }
+
+    /// Add the union of two MOCs as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc1` - The first MOC operand
+    /// * `moc2` - The second MOC operand
+    #[wasm_bindgen(js_name = addUnionMOC)]
+    pub fn add_union_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc1: &al_api::moc::MOC,
+        moc2: &al_api::moc::MOC,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc1)?.union(self.get_moc(moc2)?);
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add the intersection of two MOCs as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc1` - The first MOC operand
+    /// * `moc2` - The second MOC operand
+    #[wasm_bindgen(js_name = addIntersectionMOC)]
+    pub fn add_intersection_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc1: &al_api::moc::MOC,
+        moc2: &al_api::moc::MOC,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc1)?.intersection(self.get_moc(moc2)?);
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add the difference of two MOCs as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc1` - The MOC from which the cells of `moc2` are removed
+    /// * `moc2` - The MOC to remove
+    #[wasm_bindgen(js_name = addDifferenceMOC)]
+    pub fn add_difference_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc1: &al_api::moc::MOC,
+        moc2: &al_api::moc::MOC,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc1)?.difference(self.get_moc(moc2)?);
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add the symmetric difference of two MOCs as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc1` - The first MOC operand
+    /// * `moc2` - The second MOC operand
+    #[wasm_bindgen(js_name = addXORMOC)]
+    pub fn add_xor_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc1: &al_api::moc::MOC,
+        moc2: &al_api::moc::MOC,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc1)?.xor(self.get_moc(moc2)?);
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add a MOC degraded to a lower depth as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc` - The MOC to degrade
+    /// * `depth` - The new depth. It must not be greater than the depth of `moc`
+    #[wasm_bindgen(js_name = addDegradedMOC)]
+    pub fn add_degraded_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc: &al_api::moc::MOC,
+        depth: u8,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc)?;
+        if depth > moc.depth() {
+            return Err(JsValue::from(js_sys::Error::new(&format!(
+                "Cannot degrade a MOC of depth {} to the depth {}",
+                moc.depth(),
+                depth
+            ))));
+        }
+
+        let moc = moc.degrade(depth);
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add a MOC expanded by one ring of cells as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc` - The MOC to expand. The cells added are of the depth of this MOC
+    #[wasm_bindgen(js_name = addExpandedMOC)]
+    pub fn add_expanded_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc: &al_api::moc::MOC,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc)?.expand();
+        self.app.add_moc(params.clone(), moc)
+    }
+
+    /// Add a MOC contracted by one ring of cells as a new MOC layer
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the new MOC layer
+    /// * `moc` - The MOC to contract. The cells removed are of the depth of this MOC
+    #[wasm_bindgen(js_name = addContractedMOC)]
+    pub fn add_contracted_moc(
+        &mut self,
+        params: &al_api::moc::MOC,
+        moc: &al_api::moc::MOC,
+    ) -> Result<(), JsValue> {
+        let moc = self.get_moc(moc)?.contract();
+        self.app.add_moc(params.clone(), moc)
+    }
+}
+
+fn check_moc_depth(depth: u8) -> Result<(), JsValue> {
+    if depth > Hpx::<u64>::MAX_DEPTH {
+        Err(JsValue::from(js_sys::Error::new(&format!(
+            "The MOC depth must be <= {}",
+            Hpx::<u64>::MAX_DEPTH
+        ))))
+    } else {
+        Ok(())
+    }
+}
+
+impl WebClient {
+    fn get_moc(&self, params: &al_api::moc::MOC) -> Result<&HEALPixCoverage, JsValue> {
+        self.app
+            .get_moc(params)
+            .ok_or_else(|| JsValue::from(js_sys::Error::new("MOC not found")))
+    }
 }
 
 #[wasm_bindgen]
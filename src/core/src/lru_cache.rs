@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+
+use serde::Serialize;
+
+/// A value whose memory size can be estimated
+pub trait ByteSize {
+    /// Size of the value in bytes
+    fn byte_size(&self) -> usize;
+}
+
+/// Statistics of a cache
+#[derive(Default, Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    /// Number of values found in the cache
+    pub hits: u32,
+    /// Number of values not found in the cache
+    pub misses: u32,
+    /// Number of values removed to stay within the budget
+    pub evictions: u32,
+    /// Number of values in the cache
+    pub num_entries: usize,
+    /// Number of pinned values in the cache
+    pub num_pinned: usize,
+    /// Total size of the values in bytes
+    pub size: usize,
+    /// Memory budget in bytes
+    pub budget: usize,
+}
+
+struct Entry<V> {
+    value: V,
+    size: usize,
+    // Time of the last use, 0 for pinned values
+    last_use: u64,
+}
+
+/// A least recently used cache bounded by the total size of its values
+///
+/// Pinned values are never evicted and do not count in the budget
+pub struct Cache<K, V> {
+    data: HashMap<K, Entry<V>>,
+    // The unpinned keys ordered by their last use
+    order: BTreeMap<u64, K>,
+    pinned: HashSet<K>,
+    // Incremented each time a value is used
+    time: u64,
+
+    // Size of the unpinned values
+    size: usize,
+    budget: usize,
+
+    hits: u32,
+    misses: u32,
+    evictions: u32,
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Clone + Eq + Hash,
+    V: ByteSize,
+{
+    pub fn new(budget: usize) -> Self {
+        Cache {
+            data: HashMap::new(),
+            order: BTreeMap::new(),
+            pinned: HashSet::new(),
+            time: 0,
+            size: 0,
+            budget,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Insert a value, evicting the least recently used ones if the budget is exceeded
+    pub fn insert(&mut self, key: K, value: V) {
+        self.extract(&key);
+
+        let size = value.byte_size();
+        let last_use = if self.pinned.contains(&key) {
+            0
+        } else {
+            self.size += size;
+            self.touch(&key)
+        };
+
+        self.data.insert(
+            key,
+            Entry {
+                value,
+                size,
+                last_use,
+            },
+        );
+
+        self.evict();
+    }
+
+    /// Get a value, counting a hit or a miss
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if self.data.contains_key(key) {
+            self.hits += 1;
+
+            if !self.pinned.contains(key) {
+                let last_use = self.touch(key);
+                let entry = self.data.get_mut(key)?;
+
+                self.order.remove(&entry.last_use);
+                entry.last_use = last_use;
+            }
+
+            self.data.get(key).map(|entry| &entry.value)
+        } else {
+            self.misses += 1;
+
+            None
+        }
+    }
+
+    /// Get a value without counting a hit or a miss nor changing its last use
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.data.get(key).map(|entry| &entry.value)
+    }
+
+    /// Remove a value
+    pub fn extract(&mut self, key: &K) -> Option<V> {
+        let entry = self.data.remove(key)?;
+
+        if !self.pinned.contains(key) {
+            self.order.remove(&entry.last_use);
+            self.size -= entry.size;
+        }
+
+        Some(entry.value)
+    }
+
+    /// Remove the values whose key verifies a predicate
+    pub fn extract_where<F>(&mut self, f: F) -> Vec<(K, V)>
+    where
+        F: Fn(&K) -> bool,
+    {
+        let keys: Vec<_> = self.data.keys().filter(|key| f(key)).cloned().collect();
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let value = self.extract(&key)?;
+                Some((key, value))
+            })
+            .collect()
+    }
+
+    /// Remove all the values, the pinned keys staying pinned
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.order.clear();
+        self.size = 0;
+    }
+
+    /// Prevent a value from being evicted
+    ///
+    /// The key can be pinned before its value is inserted
+    pub fn pin(&mut self, key: K) {
+        if let Some(entry) = self.data.get_mut(&key) {
+            if !self.pinned.contains(&key) {
+                self.order.remove(&entry.last_use);
+                self.size -= entry.size;
+                entry.last_use = 0;
+            }
+        }
+
+        self.pinned.insert(key);
+    }
+
+    /// Let a pinned value be evicted again
+    pub fn unpin(&mut self, key: &K) {
+        if self.pinned.remove(key) && self.data.contains_key(key) {
+            let last_use = self.touch(key);
+            if let Some(entry) = self.data.get_mut(key) {
+                entry.last_use = last_use;
+                self.size += entry.size;
+            }
+
+            self.evict();
+        }
+    }
+
+    /// Set the memory budget in bytes, evicting values if it is exceeded
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            num_entries: self.data.len(),
+            num_pinned: self.data.keys().filter(|k| self.pinned.contains(k)).count(),
+            size: self.size,
+            budget: self.budget,
+        }
+    }
+
+    // Mark a key as the most recently used one, returns its time of use
+    fn touch(&mut self, key: &K) -> u64 {
+        self.time += 1;
+        self.order.insert(self.time, key.clone());
+
+        self.time
+    }
+
+    fn evict(&mut self) {
+        while self.size > self.budget {
+            let oldest = self.order.keys().next().copied();
+            if let Some(last_use) = oldest {
+                if let Some(key) = self.order.remove(&last_use) {
+                    if let Some(entry) = self.data.remove(&key) {
+                        self.size -= entry.size;
+                        self.evictions += 1;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ByteSize, Cache};
+
+    impl ByteSize for Vec<u8> {
+        fn byte_size(&self) -> usize {
+            self.len()
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = Cache::new(10);
+        cache.insert("a", vec![0; 4]);
+        cache.insert("b", vec![0; 4]);
+        // "a" becomes the most recently used value
+        assert!(cache.get(&"a").is_some());
+        cache.insert("c", vec![0; 4]);
+
+        assert!(cache.peek(&"a").is_some());
+        assert!(cache.peek(&"b").is_none());
+        assert!(cache.peek(&"c").is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.size, 8);
+        assert_eq!(stats.evictions, 1);
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let mut cache = Cache::new(10);
+        cache.insert("a", vec![0; 4]);
+
+        assert!(cache.get(&"a").is_some());
+        assert!(cache.get(&"b").is_none());
+        assert!(cache.get(&"b").is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+    }
+
+    #[test]
+    fn pinned_values_are_kept() {
+        let mut cache = Cache::new(4);
+        cache.pin("allsky");
+        cache.insert("allsky", vec![0; 8]);
+        cache.insert("a", vec![0; 4]);
+        cache.insert("b", vec![0; 4]);
+
+        assert!(cache.peek(&"allsky").is_some());
+        assert!(cache.peek(&"a").is_none());
+        assert!(cache.peek(&"b").is_some());
+        assert_eq!(cache.stats().size, 4);
+
+        // Once unpinned, the value counts in the budget
+        cache.unpin(&"allsky");
+        assert!(cache.peek(&"b").is_none());
+        assert!(cache.peek(&"allsky").is_none());
+        assert_eq!(cache.stats().size, 0);
+    }
+
+    #[test]
+    fn shrinking_the_budget_evicts() {
+        let mut cache = Cache::new(12);
+        cache.insert("a", vec![0; 4]);
+        cache.insert("b", vec![0; 4]);
+        cache.insert("c", vec![0; 4]);
+
+        cache.set_budget(4);
+        assert!(cache.peek(&"a").is_none());
+        assert!(cache.peek(&"b").is_none());
+        assert!(cache.peek(&"c").is_some());
+    }
+
+    #[test]
+    fn extracts_values_by_key() {
+        let mut cache = Cache::new(12);
+        cache.insert((0, "a"), vec![0; 4]);
+        cache.insert((1, "a"), vec![0; 4]);
+        cache.insert((1, "b"), vec![0; 4]);
+
+        let mut extracted = cache.extract_where(|(frame, _)| *frame == 1);
+        extracted.sort_unstable();
+        assert_eq!(
+            extracted,
+            vec![((1, "a"), vec![0; 4]), ((1, "b"), vec![0; 4])]
+        );
+
+        assert!(cache.peek(&(0, "a")).is_some());
+        assert_eq!(cache.stats().size, 4);
+
+        cache.clear();
+        assert_eq!(cache.stats().num_entries, 0);
+        assert_eq!(cache.stats().size, 0);
+    }
+}
@@ -0,0 +1,188 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::math::angle::ToAngle;
+use crate::math::lonlat::{LonLat, LonLatT};
+
+/// Astronomical unit expressed in km.yr/s
+///
+/// A star at 1 pc having a transverse velocity of 1 km/s
+/// shows a proper motion of 1/A_KM_YR_PER_S arcsec/yr
+pub const A_KM_YR_PER_S: f64 = 4.740_470_463_5;
+
+const MAS_TO_RAD: f64 = std::f64::consts::PI / (180.0 * 3600.0 * 1000.0);
+
+/// The astrometric parameters of a source at a given epoch
+#[derive(Clone, Copy, Debug)]
+pub struct Astrometry {
+    /// ICRS position
+    pub lonlat: LonLatT<f64>,
+    /// Proper motion in right ascension, multiplied by cos(dec), in mas/yr
+    pub pm_ra: f64,
+    /// Proper motion in declination in mas/yr
+    pub pm_dec: f64,
+    /// Parallax in mas
+    pub parallax: f64,
+    /// Radial velocity in km/s
+    pub radial_velocity: f64,
+}
+
+impl Astrometry {
+    /// Propagate the astrometric parameters from `epoch` to `target_epoch`.
+    ///
+    /// Epochs are julian epochs (e.g. 2016.0 for Gaia DR3).
+    /// The rigorous treatment of the space motion is used, i.e. the
+    /// source is assumed to move along a straight line at constant velocity
+    /// (see ESA 1997, The Hipparcos and Tycho catalogues, Vol. 1, Sect. 1.5.5).
+    ///
+    /// When the parallax is not known (zero, negative or NaN), the radial
+    /// velocity cannot be converted to a radial proper motion and is ignored.
+    pub fn propagate(&self, epoch: f64, target_epoch: f64) -> Self {
+        let t = target_epoch - epoch;
+
+        let (lon, lat) = (self.lonlat.lon(), self.lonlat.lat());
+        let (sa, ca) = (lon.sin(), lon.cos());
+        let (sd, cd) = (lat.sin(), lat.cos());
+
+        // Normal triad in the usual equatorial cartesian frame
+        let r0 = Vector3::new(cd * ca, cd * sa, sd);
+        let p0 = Vector3::new(-sa, ca, 0.0);
+        let q0 = Vector3::new(-sd * ca, -sd * sa, cd);
+
+        let pm_ra = or_zero(self.pm_ra);
+        let pm_dec = or_zero(self.pm_dec);
+        let parallax = or_zero(self.parallax);
+        let rv = or_zero(self.radial_velocity);
+
+        // Proper motion vector and radial proper motion, in mas/yr
+        let pm0 = p0 * pm_ra + q0 * pm_dec;
+        let zeta0 = if parallax > 0.0 {
+            rv * parallax / A_KM_YR_PER_S
+        } else {
+            0.0
+        };
+
+        // Work in rad/yr for the propagation
+        let pm0 = pm0 * MAS_TO_RAD;
+        let zeta0 = zeta0 * MAS_TO_RAD;
+        let pm0_2 = pm0.magnitude2();
+
+        let f = 1.0 / (1.0 + 2.0 * zeta0 * t + (pm0_2 + zeta0 * zeta0) * t * t).sqrt();
+
+        let r = (r0 * (1.0 + zeta0 * t) + pm0 * t) * f;
+        let pm = (pm0 * (1.0 + zeta0 * t) - r0 * pm0_2 * t) * (f * f * f);
+        let zeta = (zeta0 + (pm0_2 + zeta0 * zeta0) * t) * f * f;
+
+        let lon = r.y.atan2(r.x);
+        let lat = r.z.atan2((r.x * r.x + r.y * r.y).sqrt());
+
+        // Project the new proper motion on the new normal triad
+        let (sa, ca) = lon.sin_cos();
+        let (sd, cd) = lat.sin_cos();
+        let p = Vector3::new(-sa, ca, 0.0);
+        let q = Vector3::new(-sd * ca, -sd * sa, cd);
+
+        let (parallax, radial_velocity) = if parallax > 0.0 {
+            let new_parallax = parallax * f;
+            (
+                new_parallax,
+                zeta / MAS_TO_RAD * A_KM_YR_PER_S / new_parallax,
+            )
+        } else {
+            (self.parallax, self.radial_velocity)
+        };
+
+        Astrometry {
+            lonlat: LonLatT::new(lon.to_angle(), lat.to_angle()),
+            pm_ra: pm.dot(p) / MAS_TO_RAD,
+            pm_dec: pm.dot(q) / MAS_TO_RAD,
+            parallax,
+            radial_velocity,
+        }
+    }
+}
+
+/// Precess a position from the mean equator and equinox of `from_equinox`
+/// to the ones of `to_equinox` (FK5, IAU 1976 precession)
+pub fn precess(lonlat: &LonLatT<f64>, from_equinox: f64, to_equinox: f64) -> LonLatT<f64> {
+    let prec = al_api::coo_system::fk5_precession(from_equinox, to_equinox);
+    let xyzw: cgmath::Vector4<f64> = lonlat.vector();
+
+    (prec * xyzw).lonlat()
+}
+
+#[inline]
+fn or_zero(v: f64) -> f64 {
+    if v.is_nan() {
+        0.0
+    } else {
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Astrometry;
+    use crate::math::angle::ToAngle;
+    use crate::math::lonlat::LonLatT;
+
+    #[test]
+    fn straight_line_motion() {
+        // 1000 arcsec/yr toward the north pole without any parallax
+        let star = Astrometry {
+            lonlat: LonLatT::new(0.0_f64.to_angle(), 0.0_f64.to_angle()),
+            pm_ra: 0.0,
+            pm_dec: 1e6,
+            parallax: f64::NAN,
+            radial_velocity: f64::NAN,
+        };
+
+        let p = star.propagate(2000.0, 2100.0);
+        // The source moves along a straight line tangent to the sphere
+        let expected_dec = (1e5_f64 / 3600.0).to_radians().atan();
+
+        assert!(p.lonlat.lon().to_radians().abs() < 1e-12);
+        assert!((p.lonlat.lat().to_radians() - expected_dec).abs() < 1e-12);
+    }
+
+    #[test]
+    fn barnard_star_roundtrip() {
+        // Gaia DR3 astrometry of Barnard's star at epoch 2016.0
+        let barnard = Astrometry {
+            lonlat: LonLatT::new(
+                269.44850252543836_f64.to_radians().to_angle(),
+                4.739420051112412_f64.to_radians().to_angle(),
+            ),
+            pm_ra: -801.551,
+            pm_dec: 10362.394,
+            parallax: 546.9759,
+            radial_velocity: -110.47,
+        };
+
+        let p = barnard.propagate(2016.0, 2000.0);
+        // Position at J2000 as given by SIMBAD
+        assert!((p.lonlat.lon().to_degrees() - 269.452075).abs() * 3600.0 < 0.1);
+        assert!((p.lonlat.lat().to_degrees() - 4.693391).abs() * 3600.0 < 0.1);
+
+        // Going back to the initial epoch gives the initial parameters
+        let b = p.propagate(2000.0, 2016.0);
+        assert!((b.lonlat.lon().to_degrees() - 269.44850252543836).abs() * 3600.0 < 1e-6);
+        assert!((b.lonlat.lat().to_degrees() - 4.739420051112412).abs() * 3600.0 < 1e-6);
+        assert!((b.pm_ra - barnard.pm_ra).abs() < 1e-6);
+        assert!((b.pm_dec - barnard.pm_dec).abs() < 1e-6);
+        assert!((b.parallax - barnard.parallax).abs() < 1e-6);
+        assert!((b.radial_velocity - barnard.radial_velocity).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fk5_precession_b1950_to_j2000() {
+        // Position of the equinox B1950 in J2000 (precession only)
+        let lonlat = LonLatT::new(0.0_f64.to_angle(), 0.0_f64.to_angle());
+        let p = super::precess(&lonlat, 1950.0, 2000.0);
+
+        assert!((p.lon().to_degrees() - 0.640_5).abs() < 1e-3);
+        assert!((p.lat().to_degrees() - 0.278_4).abs() < 1e-3);
+
+        let back = super::precess(&p, 2000.0, 1950.0);
+        assert!(back.lat().to_degrees().abs() < 1e-9);
+    }
+}
@@ -10,6 +10,7 @@ pub const SQRT_TWO: f64 = 1.41421356237;
 pub const ZERO: f64 = 0.0;
 
 pub mod angle;
+pub mod astrometry;
 pub mod lonlat;
 pub mod projection;
 pub mod rotation;
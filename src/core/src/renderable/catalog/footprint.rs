@@ -0,0 +1,206 @@
+use al_api::coo_system::CooSystem;
+
+use cgmath::{InnerSpace, MetricSpace, Vector4};
+
+use super::manager::Error;
+use super::source::{ang_dist, pick_cone};
+use crate::camera::CameraViewPort;
+use crate::coosys;
+use crate::math::angle::Angle;
+use crate::math::projection::coo_space::{XYScreen, XYZWModel};
+use crate::ProjectionType;
+
+// A closed polygon on the sky with its bounding cone
+struct Footprint {
+    // ICRS positions of the vertices
+    vertices: Box<[XYZWModel<f64>]>,
+    center: XYZWModel<f64>,
+    // Radius of the bounding cone in radians
+    rad: f64,
+}
+
+impl Footprint {
+    fn new(vertices: Box<[XYZWModel<f64>]>) -> Self {
+        let center = vertices
+            .iter()
+            .fold(Vector4::new(0.0, 0.0, 0.0, 0.0), |acc, v| acc + v)
+            .truncate()
+            .normalize()
+            .extend(1.0);
+        let rad = vertices
+            .iter()
+            .map(|v| ang_dist(&center, v))
+            .fold(0.0, f64::max);
+
+        Self {
+            vertices,
+            center,
+            rad,
+        }
+    }
+
+    // Distance in pixels between a screen position and the edges of the footprint
+    //
+    // The edges having a vertex out of the projection are ignored
+    fn screen_distance(
+        &self,
+        pos: &XYScreen<f64>,
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Option<f64> {
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|v| proj.icrs_celestial_to_screen_space(v, camera))
+            .collect::<Vec<_>>();
+
+        vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .filter_map(|(v1, v2)| Some(dist_to_segment(pos, v1.as_ref()?, v2.as_ref()?)))
+            .min_by(|d1, d2| d1.total_cmp(d2))
+    }
+}
+
+/// The footprints (e.g. the observed regions) attached to the sources of a catalog
+pub struct Footprints {
+    footprints: Box<[Footprint]>,
+}
+
+impl Footprints {
+    /// Build the footprints from a flat list of the ICRS (ra, dec) coordinates in degrees
+    /// of their vertices
+    ///
+    /// `num_vertices` gives the number of vertices of each footprint, the last vertex
+    /// of a footprint being linked to its first one.
+    pub fn from_radec(coo: &[f64], num_vertices: &[u32]) -> Result<Self, Error> {
+        let num_coo = num_vertices.iter().map(|&n| 2 * n as usize).sum::<usize>();
+        if num_coo != coo.len() {
+            return Err(Error::InvalidData {
+                message: "The number of footprint vertices does not match the coordinates given"
+                    .to_string(),
+            });
+        }
+        if num_vertices.iter().any(|&n| n < 2) {
+            return Err(Error::InvalidData {
+                message: "A footprint must have at least 2 vertices".to_string(),
+            });
+        }
+        if coo.iter().any(|c| !c.is_finite()) {
+            return Err(Error::InvalidData {
+                message: "The footprint vertices must have finite coordinates".to_string(),
+            });
+        }
+
+        let mut start = 0;
+        let footprints = num_vertices
+            .iter()
+            .map(|&n| {
+                let end = start + 2 * n as usize;
+                let vertices = coo[start..end]
+                    .chunks_exact(2)
+                    .map(|radec| {
+                        crate::math::lonlat::radec_to_xyzw(
+                            Angle(radec[0].to_radians()),
+                            Angle(radec[1].to_radians()),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                start = end;
+
+                Footprint::new(vertices.into_boxed_slice())
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            footprints: footprints.into_boxed_slice(),
+        })
+    }
+
+    /// Get the indices of the footprints whose edges are located at less than `tolerance`
+    /// pixels from a screen position
+    ///
+    /// The indices are sorted from the nearest footprint to the farthest one
+    pub(super) fn pick(
+        &self,
+        pos: &XYScreen<f64>,
+        tolerance: f64,
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Vec<u32> {
+        let (center, rad) = if let Some(cone) = pick_cone(pos, tolerance, camera, proj) {
+            cone
+        } else {
+            return vec![];
+        };
+        let center = coosys::apply_coo_system(camera.get_coo_system(), CooSystem::ICRS, &center);
+
+        let mut picked = self
+            .footprints
+            .iter()
+            .enumerate()
+            // Only the footprints whose bounding cone overlaps the picking one are projected
+            .filter(|(_, f)| ang_dist(&center, &f.center) <= rad + f.rad)
+            .filter_map(|(idx, f)| {
+                let d = f.screen_distance(pos, camera, proj)?;
+
+                if d <= tolerance {
+                    Some((d, idx as u32))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        picked.sort_unstable_by(|(d1, _), (d2, _)| d1.total_cmp(d2));
+
+        picked.into_iter().map(|(_, idx)| idx).collect()
+    }
+}
+
+fn dist_to_segment(p: &XYScreen<f64>, a: &XYScreen<f64>, b: &XYScreen<f64>) -> f64 {
+    let ab = b - a;
+    let len2 = ab.magnitude2();
+    let t = if len2 > 0.0 {
+        ((p - a).dot(ab) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (a + ab * t).distance(*p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dist_to_segment, Footprints};
+    use cgmath::Vector2;
+
+    #[test]
+    fn distance_to_a_segment() {
+        let a = Vector2::new(0.0, 0.0);
+        let b = Vector2::new(10.0, 0.0);
+
+        assert_eq!(dist_to_segment(&Vector2::new(5.0, 3.0), &a, &b), 3.0);
+        // Beyond the ends, the distance is the one to the nearest end
+        assert_eq!(dist_to_segment(&Vector2::new(13.0, 4.0), &a, &b), 5.0);
+        assert_eq!(dist_to_segment(&Vector2::new(-3.0, -4.0), &a, &b), 5.0);
+        // Degenerated segment
+        assert_eq!(dist_to_segment(&Vector2::new(3.0, 4.0), &a, &a), 5.0);
+    }
+
+    #[test]
+    fn footprints_from_radec() {
+        let coo = [
+            10.0, 10.0, 11.0, 10.0, 11.0, 11.0, 10.0, 11.0, // a square
+            200.0, -30.0, 201.0, -30.0, 200.5, -29.0, // a triangle
+        ];
+        let footprints = Footprints::from_radec(&coo, &[4, 3]).unwrap();
+        assert_eq!(footprints.footprints.len(), 2);
+
+        // The bounding cone of the square contains its vertices
+        let square = &footprints.footprints[0];
+        assert!(square.rad > 0.5_f64.to_radians() && square.rad < 1.0_f64.to_radians());
+
+        assert!(Footprints::from_radec(&coo, &[4, 2]).is_err());
+        assert!(Footprints::from_radec(&coo[..2], &[1]).is_err());
+    }
+}
@@ -3,10 +3,10 @@ use crate::ShaderManager;
 use al_api::coo_system::CooSystem;
 use al_api::resources::Resources;
 
-use al_core::colormap::Colormap;
+use al_core::image::format::RGBA8U;
 use al_core::Colormaps;
 use al_core::FrameBufferObject;
-use al_core::{VecData, VertexArrayObject, WebGlContext};
+use al_core::{Texture2D, VecData, VertexArrayObject, WebGlContext};
 
 use crate::ProjectionType;
 use std::collections::HashMap;
@@ -16,27 +16,34 @@ use web_sys::WebGl2RenderingContext;
 #[derive(Debug)]
 pub enum Error {
     CatalogNotPresent { message: String },
+    InvalidData { message: String },
 }
 use wasm_bindgen::JsValue;
 impl From<Error> for JsValue {
     fn from(err: Error) -> Self {
         match err {
             Error::CatalogNotPresent { message } => message.into(),
+            Error::InvalidData { message } => message.into(),
         }
     }
 }
 
-// Num of shapes
-const _NUM_SHAPES: usize = 5;
 pub struct Manager {
     gl: WebGlContext,
-    //kernels: HashMap<&'static str, Texture2D>,
+    // The gaussian kernel splatted for each source
+    kernel: Texture2D,
+    // The density map is first rendered into this fbo
     fbo: FrameBufferObject,
 
     // VAOs
     vertex_array_object_screen: VertexArrayObject,
 
+    // Catalogs rendered as density maps
     catalogs: HashMap<String, Catalog>,
+    // Catalogs rendered with markers
+    markers: HashMap<String, Markers>,
+    // Footprints attached to the catalogs
+    footprints: HashMap<String, Footprints>,
     kernel_size: Vector2<f32>,
 }
 
@@ -48,8 +55,8 @@ impl Manager {
         resources: &Resources,
     ) -> Result<Self, JsValue> {
         // Load the texture of the gaussian kernel
-        let _kernel_filename = resources.get_filename("kernel").unwrap_abort();
-        let _params = &[
+        let kernel_filename = resources.get_filename("kernel").unwrap_abort();
+        let params = &[
             (
                 WebGl2RenderingContext::TEXTURE_MIN_FILTER,
                 WebGl2RenderingContext::LINEAR,
@@ -69,50 +76,27 @@ impl Manager {
                 WebGl2RenderingContext::CLAMP_TO_EDGE,
             ),
         ];
-        /*let kernels = [
-            (
-                "gaussian",
-                Texture2D::create_from_path::<_, RGBA8U>(gl, "kernel", &kernel_filename, params)?,
-            ),
-            (
-                "plus",
-                Texture2D::create_from_raw_pixels::<R8UI>(
-                    gl,
-                    3,
-                    3,
-                    params,
-                    Some(&[0, 0xff, 0, 0xff, 0xff, 0xff, 0, 0xff, 0]),
-                )?,
-            ),
-            (
-                "square",
-                Texture2D::create_from_raw_pixels::<R8UI>(
-                    gl,
-                    3,
-                    3,
-                    params,
-                    Some(&[0xff, 0xff, 0xff, 0xff, 0, 0xff, 0xff, 0xff, 0xff]),
-                )?,
-            ),
-        ]
-        .into();*/
+        let kernel =
+            Texture2D::create_from_path::<_, RGBA8U>(gl, "kernel", kernel_filename, params)?;
+
         // Create the VAO for the screen
         let vertex_array_object_screen = {
             let vertices = [
                 -1.0_f32, -1.0_f32, 0.0_f32, 0.0_f32, 1.0_f32, -1.0_f32, 1.0_f32, 0.0_f32, 1.0_f32,
                 1.0_f32, 1.0_f32, 1.0_f32, -1.0_f32, 1.0_f32, 0.0_f32, 1.0_f32,
             ];
-            let _position = [
+            #[cfg(feature = "webgl1")]
+            let position = [
                 -1.0_f32, -1.0_f32, 1.0_f32, -1.0_f32, 1.0_f32, 1.0_f32, -1.0_f32, 1.0_f32,
             ];
-            let _uv = [
+            #[cfg(feature = "webgl1")]
+            let uv = [
                 0.0_f32, 0.0_f32, 1.0_f32, 0.0_f32, 1.0_f32, 1.0_f32, 0.0_f32, 1.0_f32,
             ];
 
             let indices = [0_u16, 1, 2, 0, 2, 3];
 
             let mut vao = VertexArrayObject::new(gl);
-            //let shader = Colormap::get_catalog_shader(gl, shaders)?;
             #[cfg(feature = "webgl2")]
             vao.bind_for_update()
                 // Store the screen and uv of the billboard in a VBO
@@ -157,6 +141,8 @@ impl Manager {
         };
 
         let catalogs = HashMap::new();
+        let markers = HashMap::new();
+        let footprints = HashMap::new();
         let kernel_size = Vector2::new(0.0, 0.0);
 
         let fbo = FrameBufferObject::new(gl, 768, 768).unwrap_abort();
@@ -164,12 +150,14 @@ impl Manager {
         let gl = gl.clone();
         let mut manager = Manager {
             gl,
-            //kernels,
+            kernel,
             fbo,
 
             vertex_array_object_screen,
 
             catalogs,
+            markers,
+            footprints,
             kernel_size,
         };
 
@@ -178,42 +166,86 @@ impl Manager {
         Ok(manager)
     }
 
-    // Private method adding a catalog into the manager
-    pub fn add_catalog<P: Projection>(
+    /// Add a catalog into the manager
+    ///
+    /// A catalog with the same name is replaced
+    pub fn add_catalog(
         &mut self,
         name: String,
-        sources: Box<[LonLatT<f32>]>,
-        colormap: Colormap,
+        coo: &[f64],
+        colormap: String,
         camera: &mut CameraViewPort,
         proj: &ProjectionType,
-    ) {
-        // Create the HashMap storing the source indices with respect to the
+    ) -> Result<(), Error> {
+        // The sources are sorted and indexed with respect to the
         // HEALPix cell at depth 7 in which they are contained
-        let catalog = Catalog::new::<P>(&self.gl, colormap, sources);
+        let sources = Sources::from_radec(coo, |_| (0.0, 0.0))?;
+        let mut catalog = Catalog::new(&self.gl, colormap, sources);
+        // Only the sources in the view are sent to the GPU
+        if !self.remove(&name) {
+            camera.register_view_frame(CooSystem::ICRS, proj);
+        }
+        catalog.update(&Self::cells_in_view(camera));
 
-        // Update the number of sources loaded
-        //self.num_sources += num_instances_in_catalog as usize;
         self.catalogs.insert(name, catalog);
-        camera.register_view_frame(CooSystem::ICRS, proj);
-
-        // At this point, all the sources memory will be deallocated here
-        // These sources have been copied to the GPU so we do not need them
-        // in the CPU side
 
-        // We also keep a hash map of all the sources indices located in HEALPix cell
-        // at depth 7
+        Ok(())
     }
 
-    pub fn remove_catalog<P: Projection>(
+    /// Add a catalog rendered with markers into the manager
+    ///
+    /// A catalog with the same name is replaced
+    pub fn add_markers(
         &mut self,
         name: String,
+        mut markers: Markers,
         camera: &mut CameraViewPort,
         proj: &ProjectionType,
     ) {
-        // Update the number of sources loaded
-        //self.num_sources += num_instances_in_catalog as usize;
-        self.catalogs.remove(&name);
-        camera.unregister_view_frame(CooSystem::ICRS, proj);
+        if !self.remove(&name) {
+            camera.register_view_frame(CooSystem::ICRS, proj);
+        }
+        markers.update(&Self::cells_in_view(camera), camera);
+
+        self.markers.insert(name, markers);
+    }
+
+    pub fn remove_catalog(
+        &mut self,
+        name: String,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Result<(), Error> {
+        if self.remove(&name) {
+            camera.unregister_view_frame(CooSystem::ICRS, proj);
+
+            Ok(())
+        } else {
+            Err(Error::CatalogNotPresent {
+                message: format!("{} catalog is not present!", name),
+            })
+        }
+    }
+
+    /// Attach footprints to a catalog
+    ///
+    /// They replace the footprints previously attached and are removed with the catalog
+    pub fn set_footprints(&mut self, name: String, footprints: Footprints) -> Result<(), Error> {
+        if self.catalogs.contains_key(&name) || self.markers.contains_key(&name) {
+            self.footprints.insert(name, footprints);
+
+            Ok(())
+        } else {
+            Err(Error::CatalogNotPresent {
+                message: format!("{} catalog is not present!", name),
+            })
+        }
+    }
+
+    // Remove a catalog whatever its rendering mode, returns true if it was present
+    fn remove(&mut self, name: &str) -> bool {
+        self.footprints.remove(name);
+        self.catalogs.remove(name).is_some() || self.markers.remove(name).is_some()
     }
 
     pub fn set_kernel_size(&mut self, camera: &CameraViewPort) {
@@ -227,61 +259,152 @@ impl Manager {
         })
     }
 
-    pub fn update(&mut self, camera: &mut CameraViewPort) {
-        // Render only the sources in the current field of view
-        // Cells that are of depth > 7 are not handled by the hashmap (limited to depth 7)
-        // For these cells, we draw all the sources lying in the ancestor cell of depth 7 containing
-        // this cell
-        //if camera.get_aperture() > P::RASTER_THRESHOLD_ANGLE {
-        if camera.get_field_of_view().is_allsky() {
-            let cells = crate::healpix::cell::ALLSKY_HPX_CELLS_D0;
-
-            for catalog in self.catalogs.values_mut() {
-                catalog.update(cells);
-            }
+    pub fn get_mut_markers(&mut self, name: &str) -> Result<&mut Markers, Error> {
+        self.markers.get_mut(name).ok_or(Error::CatalogNotPresent {
+            message: format!("{} catalog is not present!", name),
+        })
+    }
+
+    fn get_sources(&self, name: &str) -> Result<&Sources, Error> {
+        self.catalogs
+            .get(name)
+            .map(|catalog| &catalog.sources)
+            .or_else(|| self.markers.get(name).map(|markers| markers.sources()))
+            .ok_or(Error::CatalogNotPresent {
+                message: format!("{} catalog is not present!", name),
+            })
+    }
+
+    /// Get the indices of the sources of a catalog located at less than `tolerance` pixels
+    /// from a screen position, sorted from the nearest to the farthest
+    ///
+    /// The indices refer to the order in which the sources were given when adding the catalog
+    pub fn pick_sources(
+        &self,
+        name: &str,
+        pos: &XYScreen<f64>,
+        tolerance: f64,
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Result<Vec<u32>, Error> {
+        Ok(self.get_sources(name)?.pick(pos, tolerance, camera, proj))
+    }
+
+    /// Get the indices of the footprints of a catalog whose edges are located at less than
+    /// `tolerance` pixels from a screen position, sorted from the nearest to the farthest
+    ///
+    /// The indices refer to the order in which the footprints were given
+    pub fn pick_footprints(
+        &self,
+        name: &str,
+        pos: &XYScreen<f64>,
+        tolerance: f64,
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Result<Vec<u32>, Error> {
+        // A catalog without footprints has nothing to pick
+        self.get_sources(name)?;
+
+        Ok(self
+            .footprints
+            .get(name)
+            .map(|footprints| footprints.pick(pos, tolerance, camera, proj))
+            .unwrap_or_default())
+    }
+
+    /// Get the indices of the sources of a catalog lying inside a polygon
+    /// given in screen coordinates, sorted in increasing order
+    pub fn select_sources(
+        &self,
+        name: &str,
+        vertices: &[XYScreen<f64>],
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Result<Vec<u32>, Error> {
+        Ok(self
+            .get_sources(name)?
+            .select_in_polygon(vertices, camera, proj))
+    }
+
+    /// Compute the coverage at a given depth of the sources of a catalog
+    pub fn get_coverage(&self, name: &str, depth: u8) -> Result<HEALPixCoverage, Error> {
+        Ok(self.get_sources(name)?.coverage(depth))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.catalogs.is_empty() && self.markers.is_empty()
+    }
+
+    // Cells that are of depth > 7 are not handled by the index vector (limited to depth 7)
+    // For these cells, we draw all the sources lying in the ancestor cell of depth 7 containing
+    // this cell
+    fn cells_in_view(camera: &CameraViewPort) -> Vec<HEALPixCell> {
+        if camera.is_allsky() {
+            crate::healpix::cell::ALLSKY_HPX_CELLS_D0.to_vec()
         } else {
             let depth = camera.get_texture_depth().min(7);
-            let cells = camera.get_hpx_cells(depth, CooSystem::ICRS);
+            camera.get_hpx_cells(depth, CooSystem::ICRS)
+        }
+    }
 
-            for catalog in self.catalogs.values_mut() {
-                catalog.update(&cells);
-            }
+    pub fn update(&mut self, camera: &CameraViewPort) {
+        if self.is_empty() {
+            return;
+        }
+
+        // Render only the sources in the current field of view
+        let cells = Self::cells_in_view(camera);
+        for catalog in self.catalogs.values_mut() {
+            catalog.update(&cells);
+        }
+        for markers in self.markers.values_mut() {
+            markers.update(&cells, camera);
         }
     }
 
     pub fn draw(
         &self,
-        gl: &WebGlContext,
         shaders: &mut ShaderManager,
         camera: &CameraViewPort,
         colormaps: &Colormaps,
         fbo: Option<&FrameBufferObject>,
         projection: &ProjectionType,
     ) -> Result<(), JsValue> {
-        gl.enable(WebGl2RenderingContext::BLEND);
+        self.gl.enable(WebGl2RenderingContext::BLEND);
         for catalog in self.catalogs.values() {
-            catalog.draw(gl, shaders, self, camera, colormaps, fbo, projection)?;
+            catalog.draw(&self.gl, shaders, self, camera, colormaps, fbo, projection)?;
         }
-        gl.disable(WebGl2RenderingContext::BLEND);
+        for markers in self.markers.values() {
+            markers.draw(&self.gl, shaders, camera, colormaps, projection)?;
+        }
+        self.gl.blend_func_separate(
+            WebGl2RenderingContext::SRC_ALPHA,
+            WebGl2RenderingContext::ONE,
+            WebGl2RenderingContext::ONE,
+            WebGl2RenderingContext::ONE,
+        );
+        self.gl.disable(WebGl2RenderingContext::BLEND);
 
         Ok(())
     }
 }
 
-use crate::healpix::index_vector::IdxVec;
-use crate::LonLatT;
+use super::footprint::Footprints;
+use super::source::Sources;
+use crate::math::projection::coo_space::XYScreen;
 
 pub struct Catalog {
-    colormap: Colormap,
+    colormap: String,
     num_instances: i32,
-    index_vec: IdxVec,
     alpha: f32,
     strength: f32,
-    lonlat: Box<[LonLatT<f32>]>,
+    sources: Sources,
     vertex_array_object_catalog: VertexArrayObject,
 }
+use super::Markers;
+use crate::camera::CameraViewPort;
 use crate::healpix::cell::HEALPixCell;
-use crate::{camera::CameraViewPort, math::projection::Projection, utils};
+use crate::healpix::coverage::HEALPixCoverage;
 use al_core::SliceData;
 use cgmath::Vector2;
 
@@ -289,17 +412,10 @@ const MAX_SOURCES_PER_CATALOG: f32 = 50000.0;
 
 use crate::Abort;
 impl Catalog {
-    fn new<P: Projection>(
-        gl: &WebGlContext,
-        colormap: Colormap,
-        mut lonlat: Box<[LonLatT<f32>]>,
-    ) -> Catalog {
+    fn new(gl: &WebGlContext, colormap: String, sources: Sources) -> Catalog {
         let alpha = 1_f32;
         let strength = 1_f32;
-        let index_vec = IdxVec::from_coo(&mut lonlat);
-        let num_instances = lonlat.len() as i32;
-
-        //let sources = unsafe { utils::transmute_boxed_slice(sources) };
+        let num_instances = 0;
 
         let vertex_array_object_catalog = {
             #[cfg(feature = "webgl2")]
@@ -320,7 +436,6 @@ impl Catalog {
 
             let mut vao = VertexArrayObject::new(gl);
 
-            //let shader = Orthographic::get_catalog_shader(gl, shaders);
             #[cfg(feature = "webgl2")]
             vao.bind_for_update()
                 // Store the UV and the offsets of the billboard in a VBO
@@ -332,11 +447,11 @@ impl Catalog {
                     WebGl2RenderingContext::STATIC_DRAW,
                     SliceData(vertices.as_ref()),
                 )
-                // Store the cartesian position of the center of the source in the a instanced VBO
+                // Store the (lon, lat) ICRS position of the center of the source in an instanced VBO
                 .add_instanced_array_buffer(
                     "center",
-                    3 * std::mem::size_of::<f32>(),
-                    &[3],
+                    2 * std::mem::size_of::<f32>(),
+                    &[2],
                     &[0],
                     WebGl2RenderingContext::DYNAMIC_DRAW,
                     SliceData(&[]),
@@ -350,12 +465,6 @@ impl Catalog {
                 .unbind();
             #[cfg(feature = "webgl1")]
             vao.bind_for_update()
-                .add_instanced_array_buffer(
-                    3,
-                    "center",
-                    WebGl2RenderingContext::DYNAMIC_DRAW,
-                    SliceData(&[]),
-                )
                 // Store the UV and the offsets of the billboard in a VBO
                 .add_array_buffer(
                     2,
@@ -369,7 +478,13 @@ impl Catalog {
                     WebGl2RenderingContext::STATIC_DRAW,
                     SliceData(uv.as_ref()),
                 )
-                // Store the cartesian position of the center of the source in the a instanced VBO
+                // Store the (lon, lat) ICRS position of the center of the source in an instanced VBO
+                .add_instanced_array_buffer(
+                    2,
+                    "center",
+                    WebGl2RenderingContext::DYNAMIC_DRAW,
+                    SliceData(&[]),
+                )
                 // Set the element buffer
                 .add_element_buffer(
                     WebGl2RenderingContext::STATIC_DRAW,
@@ -385,8 +500,7 @@ impl Catalog {
             strength,
             colormap,
             num_instances,
-            index_vec,
-            lonlat,
+            sources,
 
             vertex_array_object_catalog,
         }
@@ -396,7 +510,7 @@ impl Catalog {
         self.strength = strength;
     }
 
-    pub fn set_colormap(&mut self, colormap: Colormap) {
+    pub fn set_colormap(&mut self, colormap: String) {
         self.colormap = colormap;
     }
 
@@ -404,12 +518,15 @@ impl Catalog {
         self.alpha = alpha;
     }
 
+    pub fn num_sources(&self) -> usize {
+        self.sources.len()
+    }
+
     fn get_total_num_sources_in_fov(&self, cells: &[HEALPixCell]) -> usize {
         let mut total_sources = 0;
 
         for cell in cells {
-            let sources_idx = self.index_vec.get_item_indices_inside_hpx_cell(cell);
-            total_sources += (sources_idx.end - sources_idx.start) as usize;
+            total_sources += self.sources.in_cell(cell).len();
         }
 
         total_sources
@@ -419,32 +536,36 @@ impl Catalog {
     fn update(&mut self, cells: &[HEALPixCell]) {
         let num_sources_in_fov = self.get_total_num_sources_in_fov(cells) as f32;
         // reset the sources in the frame
-        let mut sources: Vec<_> = vec![];
+        let mut sources: Vec<f32> = vec![];
         // depth < 7
         for cell in cells {
             let delta_depth = (7_i8 - cell.depth() as i8).max(0);
 
             for c in cell.get_children_cells(delta_depth as u8) {
                 // Define the total number of sources being in this kernel depth tile
-                let sources_in_cell = self.index_vec.get_item_indices_inside_hpx_cell(&c);
-                let num_sources_in_kernel_cell =
-                    (sources_in_cell.end - sources_in_cell.start) as usize;
+                let mut sources_in_kernel_cell = self.sources.in_cell(&c);
+                let num_sources_in_kernel_cell = sources_in_kernel_cell.len();
                 if num_sources_in_kernel_cell > 0 {
+                    // Keep the same proportion of sources in each cell so that
+                    // the density map is not biased by the subsampling
                     let num_sources = (((num_sources_in_kernel_cell as f32) / num_sources_in_fov)
-                        * MAX_SOURCES_PER_CATALOG) as usize;
+                        * MAX_SOURCES_PER_CATALOG)
+                        .ceil() as usize;
 
-                    let mut idx = self.index_vec.get_item_indices_inside_hpx_cell(&c);
-                    if num_sources < idx.end - idx.start {
+                    if num_sources < num_sources_in_kernel_cell {
                         // use a selection of num_sources items
-                        idx = idx.start..(idx.start + num_sources);
+                        sources_in_kernel_cell = &sources_in_kernel_cell[..num_sources];
                     }
 
-                    sources.extend(&self.lonlat[idx]);
+                    sources.extend(
+                        sources_in_kernel_cell
+                            .iter()
+                            .flat_map(|s| [s.lonlat.lon().0, s.lonlat.lat().0]),
+                    );
                 }
             }
         }
-        self.num_instances = sources.len() as i32;
-        let sources = unsafe { utils::transmute_vec::<LonLatT<f32>, f32>(sources).unwrap() };
+        self.num_instances = (sources.len() / 2) as i32;
 
         // Update the vertex buffer
         #[cfg(feature = "webgl1")]
@@ -462,6 +583,7 @@ impl Catalog {
             );
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn draw(
         &self,
         gl: &WebGlContext,
@@ -473,53 +595,40 @@ impl Catalog {
         projection: &ProjectionType,
     ) -> Result<(), JsValue> {
         // If the catalog is transparent, simply discard the draw
-        if self.alpha > 0_f32 {
-            // Render to the FRAMEBUFFER
-            // Render the scene
+        if self.alpha > 0_f32 && self.num_instances > 0 {
+            // Render the density map to the FRAMEBUFFER
+            // The kernels of the sources are summed up
+            gl.blend_func(WebGl2RenderingContext::ONE, WebGl2RenderingContext::ONE);
+
+            let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+            let view2world = camera.get_m2w();
+            let icrs2world = view2world * icrs2view;
+
+            let fov = camera.get_aperture().to_radians() as f32;
+
             manager.fbo.draw_onto(
                 || {
-                    gl.clear_color(0.0, 0.0, 0.0, 1.0);
-                    gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
-
-                    let shader = match projection {
-                        ProjectionType::Sin(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogOrtVS", "CatalogOrtFS")
-                        }
-                        ProjectionType::Ait(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogAitVS", "CatalogFS")
-                        }
-                        ProjectionType::Mer(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogMerVS", "CatalogFS")
-                        }
-                        ProjectionType::Mol(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogMolVS", "CatalogFS")
-                        }
-                        /*ProjectionType::Arc(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogArcVS", "CatalogFS")
-                        }*/
-                        ProjectionType::Tan(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogTanVS", "CatalogFS")
-                        }
-                        /*ProjectionType::Hpx(_) => {
-                            crate::shader::get_shader(gl, shaders, "CatalogHpxVS", "CatalogFS")
-                        }*/
-                        _ => todo!(),
-                    }?;
-                    let shader_bound = shader.bind(gl);
-
-                    shader_bound
-                        .attach_uniforms_from(camera)
-                        // Attach catalog specialized uniforms
-                        //.attach_uniform("kernel_texture", &manager.kernels["gaussian"]) // Gaussian kernel texture
-                        .attach_uniform("strength", &self.strength) // Strengh of the kernel
-                        .attach_uniform("current_time", &utils::get_current_time())
-                        .attach_uniform("kernel_size", &manager.kernel_size)
-                        .bind_vertex_array_object_ref(&self.vertex_array_object_catalog)
-                        .draw_elements_instanced_with_i32(
-                            WebGl2RenderingContext::TRIANGLES,
-                            0,
-                            self.num_instances as i32,
-                        );
+                    crate::shader::get_shader(
+                        gl,
+                        shaders,
+                        "catalogs_catalog.vert",
+                        "catalogs_catalog.frag",
+                    )?
+                    .bind(gl)
+                    .attach_uniforms_from(camera)
+                    .attach_uniform("u_2world", &icrs2world)
+                    .attach_uniform("u_proj", projection)
+                    // Attach catalog specialized uniforms
+                    .attach_uniform("kernel_texture", &manager.kernel) // Gaussian kernel texture
+                    .attach_uniform("strength", &self.strength) // Strengh of the kernel
+                    .attach_uniform("fov", &fov)
+                    .attach_uniform("kernel_size", &manager.kernel_size)
+                    .bind_vertex_array_object_ref(&self.vertex_array_object_catalog)
+                    .draw_elements_instanced_with_i32(
+                        WebGl2RenderingContext::TRIANGLES,
+                        0,
+                        self.num_instances,
+                    );
                     Ok(())
                 },
                 fbo,
@@ -530,28 +639,33 @@ impl Catalog {
                 // Set the camera
                 let size = camera.get_screen_size();
                 gl.viewport(0, 0, size.x as i32, size.y as i32);
+                gl.scissor(0, 0, size.x as i32, size.y as i32);
+
+                gl.blend_func(
+                    WebGl2RenderingContext::SRC_ALPHA,
+                    WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA,
+                );
 
-                let shader = crate::shader::get_shader(
+                let cmap = colormaps.get(&self.colormap);
+                crate::shader::get_shader(
                     gl,
                     shaders,
-                    "ColormapCatalogVS",
-                    "ColormapCatalogFS",
-                )?;
-                //self.colormap.get_shader(gl, shaders);
-                let shaderbound = shader.bind(gl);
-                shaderbound
-                    .attach_uniform("texture_fbo", &manager.fbo.texture) // FBO density texture computed just above
-                    .attach_uniform("alpha", &self.alpha) // Alpha channel
-                    .attach_uniforms_with_params_from(&self.colormap, colormaps)
-                    .attach_uniforms_from(colormaps)
-                    .attach_uniform("reversed", &0.0_f32)
-                    .bind_vertex_array_object_ref(&manager.vertex_array_object_screen)
-                    .draw_elements_with_i32(
-                        WebGl2RenderingContext::TRIANGLES,
-                        None,
-                        WebGl2RenderingContext::UNSIGNED_SHORT,
-                        0,
-                    );
+                    "colormaps_colormap.vert",
+                    "colormaps_colormap.frag",
+                )?
+                .bind(gl)
+                .attach_uniform("texture_fbo", &manager.fbo.texture) // FBO density texture computed just above
+                .attach_uniform("alpha", &self.alpha) // Alpha channel
+                .attach_uniforms_with_params_from(cmap, colormaps)
+                .attach_uniforms_from(colormaps)
+                .attach_uniform("reversed", &0.0_f32)
+                .bind_vertex_array_object_ref(&manager.vertex_array_object_screen)
+                .draw_elements_with_i32(
+                    WebGl2RenderingContext::TRIANGLES,
+                    None,
+                    WebGl2RenderingContext::UNSIGNED_SHORT,
+                    0,
+                );
             }
         }
 
@@ -0,0 +1,252 @@
+use al_api::catalog::{Catalog as CatalogCfg, Shape};
+use al_api::color::ColorRGB;
+use al_api::coo_system::CooSystem;
+
+use al_core::{Colormaps, SliceData, VecData, VertexArrayObject, WebGlContext};
+
+use web_sys::WebGl2RenderingContext;
+
+use wasm_bindgen::JsValue;
+
+use super::manager::Error;
+use super::source::Sources;
+use crate::camera::CameraViewPort;
+use crate::healpix::cell::HEALPixCell;
+use crate::math::angle::Angle;
+use crate::math::lonlat::LonLat;
+use crate::ProjectionType;
+use crate::ShaderManager;
+
+const DEFAULT_SIZE: f32 = 8.0;
+const DEFAULT_COLOR: ColorRGB = ColorRGB {
+    r: 1.0,
+    g: 0.0,
+    b: 0.0,
+};
+
+/// A catalog rendered with one marker per source
+///
+/// The markers are instanced billboards of constant size in pixels. Only the sources
+/// lying in the field of view are sent to the GPU.
+pub struct Markers {
+    sources: Sources,
+
+    shape: Shape,
+    color: ColorRGB,
+    opacity: f32,
+    // The colormap used when the color of the sources is mapped from a column
+    colormap: Option<String>,
+
+    num_instances: i32,
+    vao: VertexArrayObject,
+}
+
+impl Markers {
+    /// Create the markers of a catalog
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The rendering options
+    /// * `coo` - The (ra, dec) ICRS coordinates of the sources in degrees
+    /// * `size_column` - The values from which the marker sizes are mapped
+    /// * `color_column` - The values from which the marker colors are mapped
+    pub fn new(
+        gl: &WebGlContext,
+        cfg: CatalogCfg,
+        coo: &[f64],
+        size_column: Option<&[f32]>,
+        color_column: Option<&[f32]>,
+    ) -> Result<Self, Error> {
+        let num_sources = coo.len() / 2;
+        let check_len = |column: Option<&[f32]>, name: &str| -> Result<(), Error> {
+            match column {
+                Some(c) if c.len() != num_sources => Err(Error::InvalidData {
+                    message: format!(
+                        "The {} column has {} values whereas there are {} sources",
+                        name,
+                        c.len(),
+                        num_sources
+                    ),
+                }),
+                _ => Ok(()),
+            }
+        };
+        check_len(size_column, "size")?;
+        check_len(color_column, "color")?;
+
+        let CatalogCfg {
+            color,
+            opacity,
+            shape,
+            size,
+            size_mapping,
+            color_mapping,
+        } = cfg;
+        let size = size.unwrap_or(DEFAULT_SIZE);
+
+        let sources = Sources::from_radec(coo, |idx| {
+            let size = match (size_mapping.as_ref(), size_column) {
+                (Some(mapping), Some(column)) if column[idx].is_finite() => {
+                    mapping.size(column[idx])
+                }
+                _ => size,
+            };
+            let value = match (color_mapping.as_ref(), color_column) {
+                (Some(mapping), Some(column)) => mapping.normalize(column[idx]),
+                _ => 0.0,
+            };
+
+            (size, value)
+        })?;
+
+        let colormap = match (color_mapping, color_column) {
+            (Some(mapping), Some(_)) => Some(mapping.colormap),
+            _ => None,
+        };
+
+        let vao = {
+            let vertices = [
+                -0.5_f32, -0.5_f32, 0.0_f32, 0.0_f32, 0.5_f32, -0.5_f32, 1.0_f32, 0.0_f32, 0.5_f32,
+                0.5_f32, 1.0_f32, 1.0_f32, -0.5_f32, 0.5_f32, 0.0_f32, 1.0_f32,
+            ];
+            let indices = [0_u16, 1, 2, 0, 2, 3];
+
+            let mut vao = VertexArrayObject::new(gl);
+            vao.bind_for_update()
+                // Store the offsets and the UV of the billboard in a VBO
+                .add_array_buffer(
+                    "vertices",
+                    4 * std::mem::size_of::<f32>(),
+                    &[2, 2],
+                    &[0, 2 * std::mem::size_of::<f32>()],
+                    WebGl2RenderingContext::STATIC_DRAW,
+                    SliceData(vertices.as_ref()),
+                )
+                // Store the (lon, lat) ICRS center, the size and the color value of the sources in an instanced VBO
+                .add_instanced_array_buffer(
+                    "sources",
+                    4 * std::mem::size_of::<f32>(),
+                    &[2, 1, 1],
+                    &[
+                        0,
+                        2 * std::mem::size_of::<f32>(),
+                        3 * std::mem::size_of::<f32>(),
+                    ],
+                    WebGl2RenderingContext::DYNAMIC_DRAW,
+                    SliceData(&[]),
+                )
+                // Set the element buffer
+                .add_element_buffer(
+                    WebGl2RenderingContext::STATIC_DRAW,
+                    SliceData(indices.as_ref()),
+                )
+                // Unbind the buffer
+                .unbind();
+
+            vao
+        };
+
+        Ok(Self {
+            sources,
+
+            shape: shape.unwrap_or_default(),
+            color: color.unwrap_or(DEFAULT_COLOR),
+            opacity: opacity.unwrap_or(1.0),
+            colormap,
+
+            num_instances: 0,
+            vao,
+        })
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    pub fn num_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    pub(super) fn sources(&self) -> &Sources {
+        &self.sources
+    }
+
+    /// Send to the GPU the sources lying in the field of view
+    ///
+    /// The cells given are of depth <= 7
+    pub(super) fn update(&mut self, cells: &[HEALPixCell], camera: &CameraViewPort) {
+        let fov = camera.get_field_of_view();
+        let icrs2view = CooSystem::ICRS.to::<f64>(camera.get_coo_system());
+
+        let mut buf: Vec<f32> = vec![];
+        for cell in cells {
+            for s in self.sources.in_cell(cell) {
+                let in_fov = fov.is_allsky() || {
+                    let xyzw = crate::math::lonlat::radec_to_xyzw(
+                        Angle(s.lonlat.lon().0 as f64),
+                        Angle(s.lonlat.lat().0 as f64),
+                    );
+                    fov.contains(&(icrs2view * xyzw).lonlat())
+                };
+
+                if in_fov {
+                    buf.extend([s.lonlat.lon().0, s.lonlat.lat().0, s.size, s.value]);
+                }
+            }
+        }
+        self.num_instances = (buf.len() / 4) as i32;
+
+        self.vao.bind_for_update().update_instanced_array(
+            "sources",
+            WebGl2RenderingContext::DYNAMIC_DRAW,
+            VecData(&buf),
+        );
+    }
+
+    pub(super) fn draw(
+        &self,
+        gl: &WebGlContext,
+        shaders: &mut ShaderManager,
+        camera: &CameraViewPort,
+        colormaps: &Colormaps,
+        projection: &ProjectionType,
+    ) -> Result<(), JsValue> {
+        if self.opacity <= 0.0 || self.num_instances == 0 {
+            return Ok(());
+        }
+
+        gl.blend_func(
+            WebGl2RenderingContext::SRC_ALPHA,
+            WebGl2RenderingContext::ONE_MINUS_SRC_ALPHA,
+        );
+
+        let icrs2view = CooSystem::ICRS.to(camera.get_coo_system());
+        let view2world = camera.get_m2w();
+        let icrs2world = view2world * icrs2view;
+
+        // Marker sizes are given in css pixels
+        let screen_size = camera.get_screen_size() / camera.get_dpi();
+        let cmap = colormaps.get(self.colormap.as_deref().unwrap_or("grayscale"));
+
+        crate::shader::get_shader(gl, shaders, "catalogs_marker.vert", "catalogs_marker.frag")?
+            .bind(gl)
+            .attach_uniforms_from(camera)
+            .attach_uniform("u_2world", &icrs2world)
+            .attach_uniform("u_proj", projection)
+            .attach_uniform("u_screen_size", &screen_size)
+            .attach_uniform("u_shape", &(self.shape as i32))
+            .attach_uniform("u_color", &self.color)
+            .attach_uniform("u_opacity", &self.opacity)
+            .attach_uniform("u_colormapped", &self.colormap.is_some())
+            .attach_uniforms_with_params_from(cmap, colormaps)
+            .attach_uniforms_from(colormaps)
+            .bind_vertex_array_object_ref(&self.vao)
+            .draw_elements_instanced_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                0,
+                self.num_instances,
+            );
+
+        Ok(())
+    }
+}
@@ -1,2 +1,7 @@
+mod footprint;
 mod manager;
+mod markers;
+mod source;
+pub use footprint::Footprints;
 pub use manager::{Catalog, Manager};
+pub use markers::Markers;
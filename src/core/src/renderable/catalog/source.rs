@@ -0,0 +1,337 @@
+use al_api::coo_system::CooSystem;
+
+use cgmath::{InnerSpace, MetricSpace, Vector2, Vector4};
+
+use super::manager::Error;
+use crate::camera::CameraViewPort;
+use crate::coosys;
+use crate::healpix::cell::HEALPixCell;
+use crate::healpix::coverage::HEALPixCoverage;
+use crate::healpix::index_vector::IdxVec;
+use crate::math::angle::Angle;
+use crate::math::lonlat::{LonLat, LonLatT};
+use crate::math::projection::coo_space::{XYScreen, XYZWModel};
+use crate::ProjectionType;
+
+// Depth of the HEALPix cells indexing the sources
+const INDEX_DEPTH: u8 = 7;
+
+/// A catalog source with its rendering attributes
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub(super) struct Source {
+    pub lonlat: LonLatT<f32>,
+    // Index of the source in the table given by the user
+    pub idx: u32,
+    // Size of the marker in pixels
+    pub size: f32,
+    // Value of the color column normalized between 0 and 1
+    pub value: f32,
+}
+
+impl LonLat<f32> for Source {
+    #[inline]
+    fn lon(&self) -> Angle<f32> {
+        self.lonlat.lon()
+    }
+
+    #[inline]
+    fn lat(&self) -> Angle<f32> {
+        self.lonlat.lat()
+    }
+
+    #[inline]
+    fn lonlat(&self) -> LonLatT<f32> {
+        self.lonlat
+    }
+
+    #[inline]
+    fn from_lonlat(lonlat: &LonLatT<f32>) -> Self {
+        Source {
+            lonlat: *lonlat,
+            idx: 0,
+            size: 0.0,
+            value: 0.0,
+        }
+    }
+}
+
+impl Source {
+    fn xyzw(&self) -> XYZWModel<f64> {
+        crate::math::lonlat::radec_to_xyzw(
+            Angle(self.lonlat.lon().0 as f64),
+            Angle(self.lonlat.lat().0 as f64),
+        )
+    }
+}
+
+/// The sources of a catalog sorted and indexed by the HEALPix cell of depth 7
+/// they belong to
+pub(super) struct Sources {
+    sources: Box<[Source]>,
+    index_vec: IdxVec,
+}
+
+impl Sources {
+    /// Build the sources from a flat list of ICRS (ra, dec) coordinates in degrees
+    ///
+    /// Sources with non finite coordinates are discarded. `attrs` gives the (size, value)
+    /// of a source from its index in the list.
+    pub(super) fn from_radec(
+        coo: &[f64],
+        attrs: impl Fn(usize) -> (f32, f32),
+    ) -> Result<Self, Error> {
+        if coo.len() % 2 != 0 {
+            return Err(Error::InvalidData {
+                message: "The catalog sources must be given as a flat list of (ra, dec) pairs"
+                    .to_string(),
+            });
+        }
+
+        let mut sources = coo
+            .chunks_exact(2)
+            .enumerate()
+            .filter(|(_, radec)| radec[0].is_finite() && radec[1].is_finite())
+            .map(|(idx, radec)| {
+                let (size, value) = attrs(idx);
+
+                Source {
+                    lonlat: LonLatT::new(
+                        Angle((radec[0] as f32).to_radians()),
+                        Angle((radec[1] as f32).to_radians()),
+                    ),
+                    idx: idx as u32,
+                    size,
+                    value,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let index_vec = IdxVec::from_coo(&mut sources);
+
+        Ok(Self {
+            sources: sources.into_boxed_slice(),
+            index_vec,
+        })
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Get the sources lying in a cell
+    ///
+    /// For cells of depth > 7, the sources of the parent cell of depth 7 are returned
+    #[inline]
+    pub(super) fn in_cell(&self, cell: &HEALPixCell) -> &[Source] {
+        &self.sources[self.index_vec.get_item_indices_inside_hpx_cell(cell)]
+    }
+
+    /// Compute the coverage of the sources at a given depth
+    pub(super) fn coverage(&self, depth: u8) -> HEALPixCoverage {
+        let hpx_idx = self.sources.iter().map(|s| {
+            ::healpix::nested::hash(depth, s.lonlat.lon().0 as f64, s.lonlat.lat().0 as f64)
+        });
+
+        HEALPixCoverage::from_fixed_hpx_cells(depth, hpx_idx, Some(self.sources.len()))
+    }
+
+    /// Get the indices of the sources located at less than `tolerance` pixels from a screen position
+    ///
+    /// The indices are sorted from the nearest source to the farthest one
+    pub(super) fn pick(
+        &self,
+        pos: &XYScreen<f64>,
+        tolerance: f64,
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Vec<u32> {
+        let (center, rad) = if let Some(cone) = pick_cone(pos, tolerance, camera, proj) {
+            cone
+        } else {
+            return vec![];
+        };
+
+        let mut picked = self
+            .candidates(&center, rad, camera)
+            .filter_map(|s| {
+                let d = proj
+                    .icrs_celestial_to_screen_space(&s.xyzw(), camera)?
+                    .distance(*pos);
+
+                if d <= tolerance {
+                    Some((d, s.idx))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        picked.sort_unstable_by(|(d1, _), (d2, _)| d1.total_cmp(d2));
+
+        picked.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    /// Get the indices of the sources inside a polygon given in screen coordinates
+    ///
+    /// The indices are sorted in increasing order
+    pub(super) fn select_in_polygon(
+        &self,
+        vertices: &[XYScreen<f64>],
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> Vec<u32> {
+        if vertices.len() < 3 {
+            return vec![];
+        }
+
+        // Sample the edges of the polygon because straight edges on the screen
+        // can be curved on the sky
+        const NUM_SAMPLES_PER_EDGE: usize = 8;
+        let edges_model = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .flat_map(|(v1, v2)| {
+                (0..NUM_SAMPLES_PER_EDGE).map(move |i| {
+                    let t = (i as f64) / (NUM_SAMPLES_PER_EDGE as f64);
+                    v1 + (v2 - v1) * t
+                })
+            })
+            .map(|v| proj.screen_to_model_space(&v, camera))
+            .collect::<Option<Vec<_>>>();
+
+        let mut selected = if let Some(edges_model) = edges_model {
+            // Bounding cone of the polygon
+            let center = edges_model
+                .iter()
+                .fold(Vector4::new(0.0, 0.0, 0.0, 0.0), |acc, v| acc + v)
+                .truncate()
+                .normalize()
+                .extend(1.0);
+            let rad = edges_model
+                .iter()
+                .map(|v| ang_dist(&center, v))
+                .fold(0.0, f64::max);
+
+            self.candidates(&center, rad, camera)
+                .filter(|s| self.is_in_screen_polygon(s, vertices, camera, proj))
+                .map(|s| s.idx)
+                .collect::<Vec<_>>()
+        } else {
+            // The polygon exceeds the projection, look for the sources in the whole sky
+            self.sources
+                .iter()
+                .filter(|s| self.is_in_screen_polygon(s, vertices, camera, proj))
+                .map(|s| s.idx)
+                .collect::<Vec<_>>()
+        };
+        selected.sort_unstable();
+
+        selected
+    }
+
+    fn is_in_screen_polygon(
+        &self,
+        s: &Source,
+        vertices: &[XYScreen<f64>],
+        camera: &CameraViewPort,
+        proj: &ProjectionType,
+    ) -> bool {
+        proj.icrs_celestial_to_screen_space(&s.xyzw(), camera)
+            .map(|p| is_in_polygon(&p, vertices))
+            .unwrap_or(false)
+    }
+
+    // Get the sources lying in the HEALPix cells overlapping a cone
+    // whose center is given in the frame of the view
+    fn candidates<'a>(
+        &'a self,
+        center: &XYZWModel<f64>,
+        rad: f64,
+        camera: &CameraViewPort,
+    ) -> impl Iterator<Item = &'a Source> + 'a {
+        let center = coosys::apply_coo_system(camera.get_coo_system(), CooSystem::ICRS, center);
+        let cov = HEALPixCoverage::from_cone(&center.lonlat(), rad, INDEX_DEPTH);
+
+        cov.flatten_to_fixed_depth_cells()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(move |idx| self.in_cell(&HEALPixCell(INDEX_DEPTH, idx)))
+    }
+}
+
+/// Get the cone, in the frame of the view, containing the positions located
+/// at less than `tolerance` pixels from a screen position
+///
+/// Returns `None` if the screen position is out of the projection
+pub(super) fn pick_cone(
+    pos: &XYScreen<f64>,
+    tolerance: f64,
+    camera: &CameraViewPort,
+    proj: &ProjectionType,
+) -> Option<(XYZWModel<f64>, f64)> {
+    let center = proj.screen_to_model_space(pos, camera)?;
+
+    // Angular size of a pixel at the center of the view
+    let pixel_ang = camera.get_aperture().0 / (camera.get_screen_size().x as f64);
+    let offsets = [
+        Vector2::new(tolerance, 0.0),
+        Vector2::new(-tolerance, 0.0),
+        Vector2::new(0.0, tolerance),
+        Vector2::new(0.0, -tolerance),
+    ];
+    let rad = offsets
+        .iter()
+        .map(|off| {
+            proj.screen_to_model_space(&(pos + off), camera)
+                .map(|p| ang_dist(&center, &p))
+                // Near the border of the projection, convert the tolerance with the field of view
+                .unwrap_or(tolerance * pixel_ang)
+        })
+        .fold(0.0, f64::max);
+
+    Some((center, rad))
+}
+
+pub(super) fn ang_dist(v1: &XYZWModel<f64>, v2: &XYZWModel<f64>) -> f64 {
+    v1.truncate().angle(v2.truncate()).0
+}
+
+// Crossing number test
+fn is_in_polygon(p: &XYScreen<f64>, vertices: &[XYScreen<f64>]) -> bool {
+    let mut inside = false;
+    let mut j = vertices.len() - 1;
+    for i in 0..vertices.len() {
+        let (vi, vj) = (&vertices[i], &vertices[j]);
+        if (vi.y > p.y) != (vj.y > p.y) && p.x < (vj.x - vi.x) * (p.y - vi.y) / (vj.y - vi.y) + vi.x
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_in_polygon;
+    use cgmath::Vector2;
+
+    #[test]
+    fn point_in_polygon() {
+        // A concave polygon (lasso)
+        let vertices = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+            Vector2::new(10.0, 10.0),
+            Vector2::new(5.0, 5.0),
+            Vector2::new(0.0, 10.0),
+        ];
+
+        assert!(is_in_polygon(&Vector2::new(2.0, 2.0), &vertices));
+        assert!(is_in_polygon(&Vector2::new(9.0, 8.0), &vertices));
+        assert!(!is_in_polygon(&Vector2::new(5.0, 8.0), &vertices));
+        assert!(!is_in_polygon(&Vector2::new(-1.0, 5.0), &vertices));
+        assert!(!is_in_polygon(&Vector2::new(11.0, 5.0), &vertices));
+    }
+}
@@ -0,0 +1,171 @@
+use al_api::blend::{BlendCfg, BlendFactor, BlendFunc};
+use al_api::colormap::CmapLabel;
+use al_api::composite::{ChannelCfg, LuptonStretch, RGBCompositeCfg};
+use al_api::hips::{HiPSColor, ImageMetadata, TransferFunction};
+
+use al_core::webgl_ctx::GlWrapper;
+use al_core::{FrameBufferObject, VertexArrayObject, WebGlContext};
+
+use crate::camera::CameraViewPort;
+use crate::shader::ShaderManager;
+
+use wasm_bindgen::JsValue;
+use web_sys::WebGl2RenderingContext;
+
+/// A layer combining three single channel layers as its red, green and blue channels
+///
+/// Each channel layer is drawn offscreen with its own cuts, then the channels
+/// are combined with the asinh stretch of Lupton et al. (2004)
+pub struct RGBComposite {
+    channels: [ChannelCfg; 3],
+    stretch: LuptonStretch,
+
+    // One offscreen buffer per channel, of the size of the screen
+    fbos: [FrameBufferObject; 3],
+
+    gl: WebGlContext,
+}
+
+impl RGBComposite {
+    pub fn new(
+        gl: &WebGlContext,
+        cfg: &RGBCompositeCfg,
+        camera: &CameraViewPort,
+    ) -> Result<Self, JsValue> {
+        let (width, height) = (camera.get_width() as usize, camera.get_height() as usize);
+        let fbos = [
+            FrameBufferObject::new(gl, width, height)?,
+            FrameBufferObject::new(gl, width, height)?,
+            FrameBufferObject::new(gl, width, height)?,
+        ];
+
+        Ok(RGBComposite {
+            channels: [cfg.red.clone(), cfg.green.clone(), cfg.blue.clone()],
+            stretch: cfg.stretch,
+            fbos,
+            gl: gl.clone(),
+        })
+    }
+
+    pub fn set_cfg(&mut self, cfg: &RGBCompositeCfg) {
+        self.channels = [cfg.red.clone(), cfg.green.clone(), cfg.blue.clone()];
+        self.stretch = cfg.stretch;
+    }
+
+    pub fn get_channels(&self) -> &[ChannelCfg; 3] {
+        &self.channels
+    }
+
+    /// Draw the channel layers offscreen
+    ///
+    /// `draw_channel` draws the layer of a channel given the metadata to use.
+    /// The offscreen buffers are resized to the size of the screen given in pixels
+    pub fn draw_channels(
+        &mut self,
+        (width, height): (i32, i32),
+        channel_metas: [Option<ImageMetadata>; 3],
+        mut draw_channel: impl FnMut(&str, &ImageMetadata) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        for ((fbo, channel), meta) in self
+            .fbos
+            .iter_mut()
+            .zip(self.channels.iter())
+            .zip(channel_metas.iter())
+        {
+            fbo.resize(width as usize, height as usize);
+            fbo.bind();
+
+            // The pixels not covered by the channel layer are transparent
+            self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            self.gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
+
+            // The channel layer may have been removed
+            if let Some(meta) = meta {
+                draw_channel(&channel.layer, meta)?;
+            }
+        }
+
+        self.gl
+            .bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        self.gl.viewport(0, 0, width, height);
+        self.gl.scissor(0, 0, width, height);
+
+        Ok(())
+    }
+
+    /// Combine the channels drawn offscreen onto the screen
+    pub fn draw(
+        &self,
+        shaders: &mut ShaderManager,
+        screen_vao: &VertexArrayObject,
+        meta: &ImageMetadata,
+    ) -> Result<(), JsValue> {
+        let ImageMetadata {
+            color,
+            opacity,
+            blend_cfg,
+            ..
+        } = meta;
+
+        self.gl.enable(WebGl2RenderingContext::BLEND);
+        blend_cfg.enable(&self.gl, || {
+            crate::shader::get_shader(
+                &self.gl,
+                shaders,
+                "composite_lupton.vert",
+                "composite_lupton.frag",
+            )?
+            .bind(&self.gl)
+            .attach_uniform("tex_red", &self.fbos[0].texture)
+            .attach_uniform("tex_green", &self.fbos[1].texture)
+            .attach_uniform("tex_blue", &self.fbos[2].texture)
+            .attach_uniform("Q", &self.stretch.q)
+            .attach_uniform("alpha", &self.stretch.alpha)
+            .attach_uniform("opacity", opacity)
+            // the tonal corrections
+            .attach_uniforms_from(color)
+            .bind_vertex_array_object_ref(screen_vao)
+            .draw_elements_with_i32(
+                WebGl2RenderingContext::TRIANGLES,
+                None,
+                WebGl2RenderingContext::UNSIGNED_SHORT,
+                0,
+            );
+
+            Ok(())
+        })?;
+        self.gl.disable(WebGl2RenderingContext::BLEND);
+
+        Ok(())
+    }
+}
+
+/// The metadata used to draw a channel layer offscreen
+///
+/// The pixel values are normalized with the cuts of the channel and stored square rooted
+/// in a grayscale image, the layer being opaque and replacing what is behind
+pub fn channel_meta(channel: &ChannelCfg, layer_meta: &ImageMetadata) -> ImageMetadata {
+    let min_cut = channel.min_cut.or(layer_meta.color.min_cut);
+    let max_cut = channel.max_cut.or(layer_meta.color.max_cut);
+
+    ImageMetadata {
+        color: HiPSColor {
+            stretch: TransferFunction::Sqrt,
+            min_cut,
+            max_cut,
+            reversed: false,
+            cmap_name: CmapLabel::from("grayscale"),
+            k_gamma: 1.0,
+            k_saturation: 0.0,
+            k_contrast: 0.0,
+            k_brightness: 0.0,
+        },
+        blend_cfg: BlendCfg {
+            src_color_factor: BlendFactor::One,
+            dst_color_factor: BlendFactor::Zero,
+            func: BlendFunc::FuncAdd,
+        },
+        opacity: 1.0,
+        ..layer_meta.clone()
+    }
+}
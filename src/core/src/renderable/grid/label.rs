@@ -77,7 +77,12 @@ impl Label {
         }
 
         //let content = fmt.to_string(lon.to_angle());
-        let content = al_api::angle_fmt::Format::toSexagesimal(lon.to_degrees() / 15.0, 8, false);
+        // Right ascensions are given in hours, the longitudes of the other frames in degrees
+        let content = if camera.get_coo_system().is_equatorial() {
+            al_api::angle_fmt::Format::toSexagesimal(lon.to_degrees() / 15.0, 8, false)
+        } else {
+            al_api::angle_fmt::Format::toSexagesimal(lon.to_degrees(), 7, false)
+        };
 
         let position = if !fov.is_allsky() {
             d1 + OFF_TANGENT * dt - OFF_BI_TANGENT * db
@@ -8,11 +8,12 @@ use al_core::colormap::Colormap;
 use al_core::colormap::Colormaps;
 use al_core::image::format::ChannelType;
 
-use al_core::image::Image;
+use al_core::image::{Image, ImageType};
 
 use al_core::shader::Shader;
 use al_core::webgl_ctx::GlWrapper;
 
+use al_core::Texture2D;
 use al_core::VecData;
 use al_core::VertexArrayObject;
 use al_core::WebGlContext;
@@ -26,12 +27,16 @@ use crate::{math::lonlat::LonLatT, utils};
 use crate::{shader::ShaderManager, survey::config::HiPSConfig};
 
 use crate::downloader::request::allsky::Allsky;
-use crate::healpix::{cell::HEALPixCell, coverage::HEALPixCoverage};
+use crate::healpix::{
+    cell::{HEALPixCell, ALLSKY_HPX_CELLS_D0},
+    coverage::HEALPixCoverage,
+};
 use crate::math::lonlat::LonLat;
 use crate::renderable::utils::index_patch::DefaultPatchIndexIter;
 use crate::time::Time;
 
 use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
 // Recursively compute the number of subdivision needed for a cell
 // to not be too much skewed
@@ -526,6 +531,11 @@ impl HiPS {
         self.textures.set_format(&self.gl, ext)
     }
 
+    /// Resize the texture array, returns true if the textures have been emptied
+    pub fn set_num_slices(&mut self, num_slices: i32) -> Result<bool, JsValue> {
+        self.textures.set_num_slices(&self.gl, num_slices)
+    }
+
     pub fn is_allsky(&self) -> bool {
         self.textures.config().is_allsky
     }
@@ -568,6 +578,26 @@ impl HiPS {
         }
     }
 
+    /// Get the pixel values sampled from the tiles in the view, BSCALE and BZERO being applied
+    ///
+    /// Blank values are excluded. Only FITS tiles have values
+    pub fn get_visible_pixel_values(&self, camera: &CameraViewPort) -> Vec<f32> {
+        let cfg = self.textures.config();
+        let (scale, offset, blank) = (cfg.scale, cfg.offset, cfg.blank);
+
+        // The cells in view are not kept up to date by `update` when the raytracer renders
+        // the HiPS, they are asked to the camera
+        let depth = camera.get_texture_depth().min(cfg.get_max_depth_texture());
+        let cells_in_view = camera.get_hpx_cells(depth, cfg.get_frame());
+
+        self.textures
+            .get_tile_samples(&cells_in_view)
+            .into_iter()
+            .filter(|&v| !v.is_nan() && v != blank)
+            .map(|v| v * scale + offset)
+            .collect()
+    }
+
     pub fn recompute_vertices(&mut self, camera: &mut CameraViewPort, projection: &ProjectionType) {
         self.position.clear();
         self.uv_start.clear();
@@ -823,10 +853,32 @@ impl HiPS {
         self.textures.push(&cell, image, time_request)
     }
 
+    pub fn add_cube_tile(
+        &mut self,
+        cell: &HEALPixCell,
+        image: Arc<Mutex<Option<ImageType>>>,
+        frame: u32,
+        time_request: Time,
+    ) -> Result<(), JsValue> {
+        self.textures
+            .push_cube_tile(cell, image, frame, time_request)
+    }
+
     pub fn add_allsky(&mut self, allsky: Allsky) -> Result<(), JsValue> {
         self.textures.push_allsky(allsky)
     }
 
+    pub fn set_cube_frame(&mut self, frame: u32) -> Result<(), JsValue> {
+        self.textures.set_cube_frame(frame)
+    }
+
+    // Tell whether all the root textures are loaded
+    pub fn is_base_loaded(&self) -> bool {
+        ALLSKY_HPX_CELLS_D0
+            .iter()
+            .all(|cell| self.textures.contains(cell))
+    }
+
     /* Accessors */
     #[inline]
     pub fn get_config(&self) -> &HiPSConfig {
@@ -851,6 +903,7 @@ impl HiPS {
         raytracer: &RayTracer,
         cfg: &ImageMetadata,
         proj: &ProjectionType,
+        cdf: &Texture2D,
     ) -> Result<(), JsValue> {
         // Get the coo system transformation matrix
         let selected_frame = camera.get_coo_system();
@@ -889,6 +942,7 @@ impl HiPS {
                     // send the cmap appart from the color config
                     .attach_uniforms_with_params_from(cmap, colormaps)
                     .attach_uniforms_from(color)
+                    .attach_uniform("cdf", cdf)
                     .attach_uniform("model", &w2v)
                     .attach_uniform("current_time", &utils::get_current_time())
                     .attach_uniform("opacity", opacity)
@@ -917,6 +971,7 @@ impl HiPS {
                     // send the cmap appart from the color config
                     .attach_uniforms_with_params_from(cmap, colormaps)
                     .attach_uniforms_from(color)
+                    .attach_uniform("cdf", cdf)
                     .attach_uniforms_from(camera)
                     .attach_uniform("inv_model", &v2w)
                     .attach_uniform("current_time", &utils::get_current_time())
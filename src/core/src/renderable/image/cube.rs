@@ -0,0 +1,176 @@
+use fitsrs::hdu::header::{extension, Header};
+use serde::Serialize;
+
+/// The WCS of the third axis of a data cube
+///
+/// Only linear axes are supported, i.e. the world coordinate of a slice
+/// is given by CRVAL3 + (k - CRPIX3) * CDELT3 with k the 1-based slice index
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CubeAxis {
+    pub num_slices: u64,
+    /// The type of the axis (e.g. WAVE, FREQ, VRAD)
+    pub ctype: Option<String>,
+    /// The unit of the axis world coordinates
+    pub cunit: Option<String>,
+    crval: f64,
+    cdelt: f64,
+    crpix: f64,
+}
+
+impl CubeAxis {
+    /// Parse the third axis from a FITS header
+    ///
+    /// Returns `None` if the HDU is not a cube, i.e. NAXIS3 is not given or equals 1
+    pub fn from_header(header: &Header<extension::image::Image>) -> Option<Self> {
+        let num_slices = *header.get_xtension().get_naxisn(3)?;
+        if num_slices <= 1 {
+            return None;
+        }
+
+        let get_f64 = |key: &[u8; 8]| header.get_parsed::<f64>(key).and_then(|v| v.ok());
+        let get_string = |key: &[u8; 8]| {
+            header
+                .get_parsed::<String>(key)
+                .and_then(|v| v.ok())
+                .map(|s| s.trim().to_string())
+        };
+
+        Some(Self {
+            num_slices,
+            ctype: get_string(b"CTYPE3  "),
+            cunit: get_string(b"CUNIT3  "),
+            crval: get_f64(b"CRVAL3  ").unwrap_or(0.0),
+            cdelt: get_f64(b"CDELT3  ")
+                .or_else(|| get_f64(b"CD3_3   "))
+                .unwrap_or(1.0),
+            crpix: get_f64(b"CRPIX3  ").unwrap_or(1.0),
+        })
+    }
+
+    /// The world coordinate of a 0-based slice index
+    pub fn value(&self, slice: u64) -> f64 {
+        self.crval + ((slice + 1) as f64 - self.crpix) * self.cdelt
+    }
+}
+
+/// The planes of a data cube
+///
+/// All the planes are kept in memory, only the displayed one is sent to the GPU
+pub struct Cube {
+    // The bytes of the planes in the texture format of the image
+    data: Vec<u8>,
+    plane_num_bytes: usize,
+    slice: u64,
+    axis: CubeAxis,
+}
+
+impl Cube {
+    pub fn new(data: Vec<u8>, plane_num_bytes: usize, axis: CubeAxis) -> Self {
+        Self {
+            data,
+            plane_num_bytes,
+            slice: 0,
+            axis,
+        }
+    }
+
+    pub fn get_plane(&self, slice: u64) -> Option<&[u8]> {
+        let start = (slice as usize) * self.plane_num_bytes;
+        self.data.get(start..(start + self.plane_num_bytes))
+    }
+
+    pub fn get_slice(&self) -> u64 {
+        self.slice
+    }
+
+    pub fn set_slice(&mut self, slice: u64) {
+        self.slice = slice;
+    }
+
+    pub fn get_axis(&self) -> &CubeAxis {
+        &self.axis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CubeAxis;
+    use fitsrs::fits::Fits;
+    use std::io::Cursor;
+
+    // Parse the third axis of a 2x2xN cube of bytes having the given header cards
+    fn parse_axis(num_slices: u64, cards: &[&str]) -> Option<CubeAxis> {
+        let naxis3 = format!("NAXIS3  = {:>20}", num_slices);
+        let mut header = vec![
+            "SIMPLE  =                    T",
+            "BITPIX  =                    8",
+            "NAXIS   =                    3",
+            "NAXIS1  =                    2",
+            "NAXIS2  =                    2",
+            &naxis3,
+        ];
+        header.extend_from_slice(cards);
+        header.push("END");
+
+        let mut bytes = header
+            .iter()
+            .flat_map(|card| format!("{:<80}", card).into_bytes())
+            .collect::<Vec<_>>();
+        bytes.resize(2880, b' ');
+        // the data block
+        bytes.resize(2 * 2880, 0);
+
+        let mut reader = Cursor::new(&bytes[..]);
+        let Fits { hdu } = Fits::from_reader(&mut reader).unwrap();
+
+        CubeAxis::from_header(hdu.get_header())
+    }
+
+    #[test]
+    fn axis_with_unit() {
+        let axis = parse_axis(
+            3,
+            &[
+                "CTYPE3  = 'FREQ    '",
+                "CUNIT3  = 'Hz      '",
+                "CRVAL3  =         1400000000.0",
+                "CDELT3  =            1000000.0",
+                "CRPIX3  =                  2.0",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(axis.num_slices, 3);
+        assert_eq!(axis.ctype.as_deref(), Some("FREQ"));
+        assert_eq!(axis.cunit.as_deref(), Some("Hz"));
+        // CRPIX3 is 1-based
+        assert_eq!(axis.value(0), 1_399_000_000.0);
+        assert_eq!(axis.value(1), 1_400_000_000.0);
+        assert_eq!(axis.value(2), 1_401_000_000.0);
+    }
+
+    #[test]
+    fn axis_without_unit() {
+        // CD3_3 is used when CDELT3 is not given, CRPIX3 defaults to 1
+        let axis = parse_axis(
+            4,
+            &[
+                "CTYPE3  = 'VRAD    '",
+                "CRVAL3  =                 10.0",
+                "CD3_3   =                  0.5",
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(axis.num_slices, 4);
+        assert_eq!(axis.cunit, None);
+        assert_eq!(axis.value(0), 10.0);
+        assert_eq!(axis.value(3), 11.5);
+    }
+
+    #[test]
+    fn not_a_cube() {
+        assert!(parse_axis(1, &["CRVAL3  =                 10.0"]).is_none());
+    }
+}
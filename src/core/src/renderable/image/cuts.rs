@@ -1,12 +1,56 @@
+use al_api::image::CutAlgorithm;
 use std::cmp::Ordering;
 use std::ops::Range;
-pub fn first_and_last_percent<T>(slice: &mut [T], first_percent: i32, last_percent: i32) -> Range<T>
+
+/// Maximum number of pixel values kept by an image to recompute its cuts
+pub const MAX_NUM_SAMPLES: usize = 250_000;
+
+/// Keep at most `MAX_NUM_SAMPLES` values regularly spaced
+pub fn subsample(samples: &[f32]) -> Vec<f32> {
+    let step = samples.len().div_ceil(MAX_NUM_SAMPLES).max(1);
+    samples.iter().step_by(step).copied().collect()
+}
+
+/// Compute the cuts of pixel values (NaN and blank values excluded)
+///
+/// The order of the samples is not preserved
+pub fn compute(algorithm: &CutAlgorithm, samples: &mut [f32]) -> Range<f32> {
+    if samples.is_empty() {
+        return 0.0..1.0;
+    }
+
+    match *algorithm {
+        CutAlgorithm::Percentile { low, high } => first_and_last_percent(samples, low, high),
+        CutAlgorithm::ZScale {
+            contrast,
+            num_samples,
+            max_reject,
+            min_npixels,
+            krej,
+            max_iterations,
+        } => zscale(
+            samples,
+            contrast,
+            num_samples as usize,
+            max_reject,
+            min_npixels as usize,
+            krej,
+            max_iterations,
+        ),
+        CutAlgorithm::SigmaClip {
+            sigma,
+            max_iterations,
+        } => sigma_clip(samples, sigma, max_iterations),
+    }
+}
+
+pub fn first_and_last_percent<T>(slice: &mut [T], first_percent: f32, last_percent: f32) -> Range<T>
 where
     T: PartialOrd + Copy,
 {
     let n = slice.len();
-    let first_pct_idx = ((first_percent as f32) * 0.01 * (n as f32)) as usize;
-    let last_pct_idx = ((last_percent as f32) * 0.01 * (n as f32)) as usize;
+    let first_pct_idx = ((first_percent * (n as f32) / 100.0) as usize).min(n - 1);
+    let last_pct_idx = ((last_percent * (n as f32) / 100.0) as usize).min(n - 1);
 
     let min_val = {
         let (_, min_val, _) = slice.select_nth_unstable_by(first_pct_idx, |a, b| {
@@ -23,3 +67,269 @@ where
 
     min_val..max_val
 }
+
+// Median of a non empty slice, reordering it
+fn median(values: &mut [f32]) -> f32 {
+    let n = values.len();
+    let (lower, mid, _) = values.select_nth_unstable_by(n / 2, |a, b| a.total_cmp(b));
+    let mid = *mid;
+
+    if n % 2 == 0 {
+        let prev = lower.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        0.5 * (prev + mid)
+    } else {
+        mid
+    }
+}
+
+/// IRAF zscale algorithm
+pub fn zscale(
+    values: &[f32],
+    contrast: f32,
+    num_samples: usize,
+    max_reject: f32,
+    min_npixels: usize,
+    krej: f32,
+    max_iterations: u32,
+) -> Range<f32> {
+    // Sample the values
+    let stride = ((values.len() as f32) / (num_samples.max(1) as f32)).max(1.0) as usize;
+    let mut samples = values
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .step_by(stride)
+        .take(num_samples.max(1))
+        .collect::<Vec<_>>();
+    if samples.is_empty() {
+        return 0.0..1.0;
+    }
+    samples.sort_unstable_by(|a, b| a.total_cmp(b));
+
+    let npix = samples.len();
+    let (mut vmin, mut vmax) = (samples[0], samples[npix - 1]);
+
+    // Fit a line to the sorted samples with an iterative k-sigma rejection
+    let min_pix = min_npixels.max(((npix as f32) * max_reject) as usize);
+    // Rejected pixels are grown by this number of pixels
+    let ngrow = 1.max(((npix as f32) * 0.01) as usize);
+
+    let mut badpix = vec![false; npix];
+    let mut num_good_pix = npix;
+    let mut last_num_good_pix = npix + 1;
+    let mut slope = None;
+
+    for _ in 0..max_iterations {
+        if num_good_pix >= last_num_good_pix || num_good_pix < min_pix {
+            break;
+        }
+
+        let (s, intercept) = fit_line(&samples, &badpix);
+        let flat = samples
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (v as f64) - (intercept + s * (i as f64)))
+            .collect::<Vec<_>>();
+
+        // k-sigma rejection threshold
+        let good = flat
+            .iter()
+            .zip(badpix.iter())
+            .filter(|(_, &bad)| !bad)
+            .map(|(&f, _)| f);
+        let n = num_good_pix as f64;
+        let mean = good.clone().sum::<f64>() / n;
+        let std = (good.map(|f| (f - mean) * (f - mean)).sum::<f64>() / n).sqrt();
+        let threshold = (krej as f64) * std;
+
+        for (bad, &f) in badpix.iter_mut().zip(flat.iter()) {
+            if f < -threshold || f > threshold {
+                *bad = true;
+            }
+        }
+        badpix = grow(&badpix, ngrow);
+
+        last_num_good_pix = num_good_pix;
+        num_good_pix = badpix.iter().filter(|&&bad| !bad).count();
+        slope = Some(s);
+    }
+
+    if let Some(slope) = slope.filter(|_| num_good_pix >= min_pix) {
+        let slope = if contrast > 0.0 {
+            slope / (contrast as f64)
+        } else {
+            slope
+        };
+        let center_pixel = (npix - 1) / 2;
+        let median = median(&mut samples) as f64;
+
+        vmin = vmin.max((median - ((center_pixel as f64) - 1.0) * slope) as f32);
+        vmax = vmax.min((median + ((npix - center_pixel) as f64) * slope) as f32);
+    }
+
+    vmin..vmax
+}
+
+// Least squares fit of a line on the good samples as a function of their index
+//
+// Returns the slope and the intercept
+fn fit_line(samples: &[f32], badpix: &[bool]) -> (f64, f64) {
+    let (mut n, mut sx, mut sy, mut sxx, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (i, (&v, _)) in samples
+        .iter()
+        .zip(badpix.iter())
+        .enumerate()
+        .filter(|(_, (_, &bad))| !bad)
+    {
+        let (x, y) = (i as f64, v as f64);
+        n += 1.0;
+        sx += x;
+        sy += y;
+        sxx += x * x;
+        sxy += x * y;
+    }
+
+    let denom = n * sxx - sx * sx;
+    if denom == 0.0 {
+        (0.0, if n > 0.0 { sy / n } else { 0.0 })
+    } else {
+        let slope = (n * sxy - sx * sy) / denom;
+        (slope, (sy - slope * sx) / n)
+    }
+}
+
+// Dilate the rejected pixels mask with a kernel of `ngrow` pixels
+fn grow(badpix: &[bool], ngrow: usize) -> Vec<bool> {
+    let n = badpix.len() as isize;
+    let half = (ngrow / 2) as isize;
+
+    (0..n)
+        .map(|i| {
+            let start = (i - half).max(0);
+            let end = (i - half + ngrow as isize).min(n);
+            (start..end).any(|j| badpix[j as usize])
+        })
+        .collect()
+}
+
+/// Iterative sigma clipping, sigma being estimated from the median absolute deviation
+pub fn sigma_clip(values: &[f32], sigma: f32, max_iterations: u32) -> Range<f32> {
+    // Scale factor between the median absolute deviation and the standard deviation
+    // of a normal distribution
+    const MAD_TO_STD: f32 = 1.4826;
+
+    let mut kept = values
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .collect::<Vec<_>>();
+    if kept.is_empty() {
+        return 0.0..1.0;
+    }
+
+    let mut deviations = Vec::with_capacity(kept.len());
+    for _ in 0..max_iterations {
+        let median = median(&mut kept);
+
+        deviations.clear();
+        deviations.extend(kept.iter().map(|v| (v - median).abs()));
+        let std = MAD_TO_STD * self::median(&mut deviations);
+        if std <= 0.0 {
+            break;
+        }
+
+        let (low, high) = (median - sigma * std, median + sigma * std);
+        let num_kept = kept.len();
+        kept.retain(|&v| low <= v && v <= high);
+
+        if kept.len() == num_kept {
+            break;
+        }
+    }
+
+    let min = kept.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = kept.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+    min..max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute, first_and_last_percent, sigma_clip, zscale};
+    use al_api::image::CutAlgorithm;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // Gaussian noise of mean 100 and std 10 with a few bright outliers (e.g. stars)
+    fn noisy_image() -> Vec<f32> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..10000)
+            .map(|i| {
+                if i % 500 == 0 {
+                    50000.0
+                } else {
+                    // Box-Muller transform
+                    let (u1, u2): (f32, f32) = (rng.gen_range(1e-6..1.0), rng.gen());
+                    100.0 + 10.0 * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn percentile() {
+        let mut values = (0..1000).map(|v| v as f32).collect::<Vec<_>>();
+        assert_eq!(first_and_last_percent(&mut values, 1.0, 99.0), 10.0..990.0);
+        assert_eq!(first_and_last_percent(&mut values, 0.0, 100.0), 0.0..999.0);
+    }
+
+    #[test]
+    fn zscale_ramp() {
+        // A linear ramp is entirely kept
+        let values = (0..1000).map(|v| v as f32).collect::<Vec<_>>();
+        assert_eq!(zscale(&values, 0.25, 1000, 0.5, 5, 2.5, 5), 0.0..999.0);
+
+        // The range around the median is widened by the inverse of the contrast
+        let values = (0..1000).map(|v| v as f32).collect::<Vec<_>>();
+        let cuts = zscale(&values, 2.0, 1000, 0.5, 5, 2.5, 5);
+        assert!((cuts.start - (499.5 - 498.0 / 2.0)).abs() < 1e-2);
+        assert!((cuts.end - (499.5 + 501.0 / 2.0)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn zscale_rejects_outliers() {
+        let values = noisy_image();
+        let cuts = zscale(&values, 0.25, 1000, 0.5, 5, 2.5, 5);
+
+        assert!(cuts.start > 0.0 && cuts.start < 100.0);
+        assert!(cuts.end > 100.0 && cuts.end < 1000.0);
+    }
+
+    #[test]
+    fn zscale_constant() {
+        let values = vec![3.0; 100];
+        assert_eq!(zscale(&values, 0.25, 1000, 0.5, 5, 2.5, 5), 3.0..3.0);
+    }
+
+    #[test]
+    fn sigma_clip_rejects_outliers() {
+        let values = noisy_image();
+        let cuts = sigma_clip(&values, 3.0, 5);
+
+        assert!(cuts.start > 50.0 && cuts.start < 80.0);
+        assert!(cuts.end > 120.0 && cuts.end < 150.0);
+    }
+
+    #[test]
+    fn algorithm_dispatch() {
+        let mut empty: Vec<f32> = vec![];
+        assert_eq!(compute(&CutAlgorithm::default(), &mut empty), 0.0..1.0);
+
+        let mut values = vec![1.0, 2.0, f32::NAN, 1000.0, 2.0, 1.0, 2.0, 1.0];
+        let algorithm = CutAlgorithm::SigmaClip {
+            sigma: 3.0,
+            max_iterations: 5,
+        };
+        assert_eq!(compute(&algorithm, &mut values), 1.0..2.0);
+    }
+}
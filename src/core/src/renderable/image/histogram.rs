@@ -0,0 +1,186 @@
+use al_api::image::{Histogram, HistogramParams};
+
+/// Compute the histogram of pixel values
+///
+/// The values must already be scaled (BSCALE/BZERO) and blank values excluded.
+/// Non finite values and those outside the range of the bins are not counted.
+pub fn compute(values: &[f32], params: &HistogramParams) -> Histogram {
+    let num_bins = params.num_bins.max(1) as usize;
+    let log = params.log;
+    let is_valid = |v: f32| v.is_finite() && (!log || v > 0.0);
+
+    // Range of the bins
+    let (data_min, data_max) = values
+        .iter()
+        .copied()
+        .filter(|&v| is_valid(v))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    let min = params.min.filter(|&v| is_valid(v)).unwrap_or(data_min);
+    let max = params.max.filter(|&v| is_valid(v)).unwrap_or(data_max);
+
+    let (start, end) = if min <= max {
+        if log {
+            ((min as f64).ln(), (max as f64).ln())
+        } else {
+            (min as f64, max as f64)
+        }
+    } else {
+        // No values to count
+        (0.0, 1.0)
+    };
+    let width = end - start;
+
+    let edges = (0..=num_bins)
+        .map(|i| {
+            let edge = start + width * (i as f64) / (num_bins as f64);
+            if log && min <= max {
+                edge.exp() as f32
+            } else {
+                edge as f32
+            }
+        })
+        .collect();
+
+    let mut counts = vec![0; num_bins];
+    let mut num_values = 0;
+    if min <= max {
+        for v in values
+            .iter()
+            .copied()
+            .filter(|&v| is_valid(v) && min <= v && v <= max)
+        {
+            let x = if log { (v as f64).ln() } else { v as f64 };
+            let idx = if width > 0.0 {
+                (((x - start) / width * (num_bins as f64)) as usize).min(num_bins - 1)
+            } else {
+                0
+            };
+
+            counts[idx] += 1;
+            num_values += 1;
+        }
+    }
+
+    Histogram {
+        counts,
+        edges,
+        num_values,
+    }
+}
+
+/// Number of samples of the cumulative distributions used by the histogram equalization
+pub const CDF_NUM_SAMPLES: usize = 1024;
+
+/// Compute the cumulative distribution of the values between the cuts
+///
+/// The cuts are divided into `num_samples` bins and the `i`-th sample is the fraction
+/// of the values lower than the upper edge of the `i`-th bin. Values outside
+/// the cuts are clamped to them. The identity ramp is returned if there are no values.
+pub fn cdf(values: &[f32], min: f32, max: f32, num_samples: usize) -> Vec<f32> {
+    let num_samples = num_samples.max(1);
+    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+
+    let params = HistogramParams {
+        num_bins: num_samples as u32,
+        log: false,
+        min: Some(min),
+        max: Some(max),
+    };
+    let clamped = values
+        .iter()
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(min, max))
+        .collect::<Vec<_>>();
+    let Histogram {
+        counts, num_values, ..
+    } = compute(&clamped, &params);
+
+    if num_values == 0 {
+        return (1..=num_samples)
+            .map(|i| i as f32 / num_samples as f32)
+            .collect();
+    }
+
+    counts
+        .iter()
+        .scan(0, |cumulated, &count| {
+            *cumulated += count;
+            Some(*cumulated as f32 / num_values as f32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cdf, compute};
+    use al_api::image::HistogramParams;
+
+    #[test]
+    fn linear_bins() {
+        let values = [0.0, 0.5, 1.0, 1.5, 2.0, f32::NAN, 3.5, 4.0];
+        let params = HistogramParams {
+            num_bins: 4,
+            ..Default::default()
+        };
+
+        let hist = compute(&values, &params);
+        assert_eq!(hist.edges, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        // The last bin includes the maximum
+        assert_eq!(hist.counts, vec![2, 2, 1, 2]);
+        assert_eq!(hist.num_values, 7);
+    }
+
+    #[test]
+    fn given_range() {
+        let values = [-1.0, 0.0, 0.2, 0.7, 1.0, 2.0];
+        let params = HistogramParams {
+            num_bins: 2,
+            min: Some(0.0),
+            max: Some(1.0),
+            ..Default::default()
+        };
+
+        let hist = compute(&values, &params);
+        assert_eq!(hist.counts, vec![2, 2]);
+        assert_eq!(hist.num_values, 4);
+    }
+
+    #[test]
+    fn log_bins() {
+        let values = [-5.0, 0.0, 1.0, 5.0, 20.0, 50.0, 100.0];
+        let params = HistogramParams {
+            num_bins: 2,
+            log: true,
+            ..Default::default()
+        };
+
+        let hist = compute(&values, &params);
+        assert!((hist.edges[1] - 10.0).abs() < 1e-4);
+        assert_eq!(hist.counts, vec![2, 3]);
+    }
+
+    #[test]
+    fn constant_and_empty() {
+        let hist = compute(&[3.0; 10], &HistogramParams::default());
+        assert_eq!(hist.counts[0], 10);
+        assert_eq!(hist.num_values, 10);
+
+        let hist = compute(&[], &HistogramParams::default());
+        assert_eq!(hist.counts.len(), 256);
+        assert_eq!(hist.num_values, 0);
+    }
+
+    #[test]
+    fn cumulative_distribution() {
+        let values = [0.0, 0.1, 0.2, 0.3, 0.9, -1.0, 5.0, f32::NAN];
+
+        let distribution = cdf(&values, 0.0, 1.0, 4);
+        // -1.0 and 5.0 are clamped to the cuts, NaN is excluded
+        assert_eq!(distribution, vec![4.0 / 7.0, 5.0 / 7.0, 5.0 / 7.0, 1.0]);
+
+        // Without any value, the stretch is linear
+        assert_eq!(cdf(&[], 0.0, 1.0, 4), vec![0.25, 0.5, 0.75, 1.0]);
+    }
+}
@@ -1,5 +1,7 @@
+pub mod cube;
 pub mod cuts;
 pub mod grid;
+pub mod histogram;
 pub mod subdivide_texture;
 
 use std::fmt::Debug;
@@ -20,6 +22,7 @@ use wcs::{ImgXY, WCS};
 
 use al_api::fov::CenteredFoV;
 use al_api::hips::ImageMetadata;
+use al_api::image::CutAlgorithm;
 
 use al_core::image::format::*;
 use al_core::webgl_ctx::GlWrapper;
@@ -28,10 +31,13 @@ use al_core::WebGlContext;
 use al_core::{Texture2D, VertexArrayObject};
 
 use crate::camera::CameraViewPort;
+use crate::healpix::coverage::HEALPixCoverage;
+use crate::math::lonlat::{LonLat, LonLatT};
 use crate::math::sph_geom::region::Region;
 use crate::Colormaps;
 use crate::ProjectionType;
 use crate::ShaderManager;
+use cube::{Cube, CubeAxis};
 
 use std::ops::Range;
 
@@ -52,6 +58,9 @@ pub struct Image {
     scale: f32,
     offset: f32,
     cuts: Range<f32>,
+    /// Pixel values of the displayed plane from which the cuts are recomputed
+    samples: Vec<f32>,
+    cut_algorithm: CutAlgorithm,
     /// The center of the fits
     centered_fov: CenteredFoV,
 
@@ -68,6 +77,9 @@ pub struct Image {
     reg: Region,
     // The coo system in which the polygonal region has been defined
     coo_sys: CooSystem,
+
+    // The planes of the image if it is a data cube
+    cube: Option<Cube>,
 }
 use al_core::pixel::Pixel;
 use al_core::texture::TEX_PARAMS;
@@ -75,16 +87,20 @@ use fitsrs::hdu::header::extension;
 use fitsrs::hdu::AsyncHDU;
 use futures::io::BufReader;
 use futures::AsyncReadExt;
+use futures::FutureExt;
 impl Image {
+    #[allow(clippy::too_many_arguments)]
     pub async fn from_reader_and_wcs<R, F>(
         gl: &WebGlContext,
-        mut reader: R,
+        reader: R,
         wcs: WCS,
         scale: Option<f32>,
         offset: Option<f32>,
         blank: Option<f32>,
         // Coo sys of the view
         coo_sys: CooSystem,
+        // Algorithm computing the cuts of grayscale images
+        cut_algorithm: CutAlgorithm,
     ) -> Result<Self, JsValue>
     where
         F: ImageFormat,
@@ -92,87 +108,13 @@ impl Image {
     {
         let (width, height) = wcs.img_dimensions();
 
-        let max_tex_size =
-            WebGl2RenderingContext::get_parameter(gl, WebGl2RenderingContext::MAX_TEXTURE_SIZE)?
-                .as_f64()
-                .unwrap_or(4096.0) as usize;
-
-        let mut max_tex_size_x = max_tex_size;
-        let mut max_tex_size_y = max_tex_size;
-
         // apply bscale to the cuts
         let offset = offset.unwrap_or(0.0);
         let scale = scale.unwrap_or(1.0);
         let blank = blank.unwrap_or(std::f32::NAN);
 
-        let (textures, mut cuts) = if width <= max_tex_size as u64 && height <= max_tex_size as u64
-        {
-            max_tex_size_x = width as usize;
-            max_tex_size_y = height as usize;
-            // can fit in one texture
-
-            let num_pixels_to_read = (width as usize) * (height as usize);
-            let num_bytes_to_read =
-                num_pixels_to_read * std::mem::size_of::<<F::P as Pixel>::Item>() * F::NUM_CHANNELS;
-            let mut buf = vec![0; num_bytes_to_read];
-
-            let _ = reader
-                .read_exact(&mut buf[..num_bytes_to_read])
-                .await
-                .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
-
-            // bytes aligned
-            unsafe {
-                let slice = std::slice::from_raw_parts(
-                    buf[..].as_ptr() as *const <F::P as Pixel>::Item,
-                    (num_pixels_to_read as usize) * F::NUM_CHANNELS,
-                );
-
-                let cuts = if F::NUM_CHANNELS == 1 {
-                    let mut samples = slice
-                        .iter()
-                        .filter_map(|item| {
-                            let t: f32 =
-                                <<F::P as Pixel>::Item as al_core::convert::Cast<f32>>::cast(*item);
-                            if t.is_nan() || t == blank {
-                                None
-                            } else {
-                                Some(t)
-                            }
-                        })
-                        .collect::<Vec<_>>();
-
-                    cuts::first_and_last_percent(&mut samples, 1, 99)
-                } else {
-                    0.0..1.0
-                };
-
-                let texture = Texture2D::create_from_raw_pixels::<F>(
-                    gl,
-                    width as i32,
-                    height as i32,
-                    TEX_PARAMS,
-                    Some(slice),
-                )?;
-
-                (vec![texture], cuts)
-            }
-        } else {
-            subdivide_texture::crop_image::<F, R>(
-                gl,
-                width,
-                height,
-                reader,
-                max_tex_size as u64,
-                blank,
-            )
-            .await?
-        };
-
-        let start = cuts.start * scale + offset;
-        let end = cuts.end * scale + offset;
-
-        cuts = start..end;
+        let (textures, samples, max_tex_size_x, max_tex_size_y) =
+            Self::create_textures::<F, R>(gl, width, height, reader, blank).await?;
 
         let num_indices = vec![];
         let indices = vec![];
@@ -253,7 +195,7 @@ impl Image {
 
         let idx_tex = (0..textures.len()).collect();
 
-        Ok(Image {
+        let mut image = Image {
             gl,
 
             // The positions
@@ -276,7 +218,10 @@ impl Image {
             // Texture parameters
             channel: F::CHANNEL_TYPE,
             textures,
-            cuts,
+            // Computed from the samples below
+            cuts: 0.0..1.0,
+            samples: vec![],
+            cut_algorithm,
             max_tex_size_x,
             max_tex_size_y,
             // Indices of textures that must be drawn
@@ -285,17 +230,147 @@ impl Image {
             reg,
             // The coo system in which the polygonal region has been defined
             coo_sys,
-        })
+
+            cube: None,
+        };
+        image.set_cuts_from_samples(samples);
+
+        Ok(image)
+    }
+
+    // Create the textures of an image plane
+    //
+    // The image is subdivided into several textures if it exceeds the maximum
+    // texture size supported by the GPU. The pixel values sampled to compute the cuts
+    // are returned along with the textures, grayscale images only being sampled
+    async fn create_textures<F, R>(
+        gl: &WebGlContext,
+        width: u64,
+        height: u64,
+        mut reader: R,
+        blank: f32,
+    ) -> Result<(Vec<Texture2D>, Vec<f32>, usize, usize), JsValue>
+    where
+        F: ImageFormat,
+        R: AsyncReadExt + Unpin,
+    {
+        let max_tex_size =
+            WebGl2RenderingContext::get_parameter(gl, WebGl2RenderingContext::MAX_TEXTURE_SIZE)?
+                .as_f64()
+                .unwrap_or(4096.0) as usize;
+
+        let mut max_tex_size_x = max_tex_size;
+        let mut max_tex_size_y = max_tex_size;
+
+        let (textures, samples) = if width <= max_tex_size as u64 && height <= max_tex_size as u64 {
+            max_tex_size_x = width as usize;
+            max_tex_size_y = height as usize;
+            // can fit in one texture
+
+            let num_pixels_to_read = (width as usize) * (height as usize);
+            let num_bytes_to_read =
+                num_pixels_to_read * std::mem::size_of::<<F::P as Pixel>::Item>() * F::NUM_CHANNELS;
+            let mut buf = vec![0; num_bytes_to_read];
+
+            let _ = reader
+                .read_exact(&mut buf[..num_bytes_to_read])
+                .await
+                .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
+
+            // bytes aligned
+            unsafe {
+                let slice = std::slice::from_raw_parts(
+                    buf[..].as_ptr() as *const <F::P as Pixel>::Item,
+                    (num_pixels_to_read as usize) * F::NUM_CHANNELS,
+                );
+
+                let samples = if F::NUM_CHANNELS == 1 {
+                    slice
+                        .iter()
+                        .filter_map(|item| {
+                            let t: f32 =
+                                <<F::P as Pixel>::Item as al_core::convert::Cast<f32>>::cast(*item);
+                            if t.is_nan() || t == blank {
+                                None
+                            } else {
+                                Some(t)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                } else {
+                    vec![]
+                };
+
+                let texture = Texture2D::create_from_raw_pixels::<F>(
+                    gl,
+                    width as i32,
+                    height as i32,
+                    TEX_PARAMS,
+                    Some(slice),
+                )?;
+
+                (vec![texture], samples)
+            }
+        } else {
+            subdivide_texture::crop_image::<F, R>(
+                gl,
+                width,
+                height,
+                reader,
+                max_tex_size as u64,
+                blank,
+            )
+            .await?
+        };
+
+        Ok((textures, samples, max_tex_size_x, max_tex_size_y))
     }
 
     pub fn get_cuts(&self) -> &Range<f32> {
         &self.cuts
     }
 
+    // Keep a subsample of the pixel values of the displayed plane so that the cuts
+    // can be recomputed later
+    fn set_samples(&mut self, samples: &[f32]) {
+        self.samples = cuts::subsample(samples);
+    }
+
+    // Compute the cuts from the pixel values of the displayed plane
+    fn set_cuts_from_samples(&mut self, mut samples: Vec<f32>) {
+        self.set_samples(&samples);
+
+        let cuts = cuts::compute(&self.cut_algorithm, &mut samples);
+        // apply bscale to the cuts
+        self.cuts = (cuts.start * self.scale + self.offset)..(cuts.end * self.scale + self.offset);
+    }
+
+    /// The pixel values sampled from the displayed plane, BSCALE and BZERO being applied
+    ///
+    /// Blank values are excluded. Color images do not have any values
+    pub fn get_sampled_values(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples
+            .iter()
+            .map(move |v| v * self.scale + self.offset)
+    }
+
+    /// Recompute the cuts with another algorithm
+    ///
+    /// The algorithm is kept for the other slices of a data cube
+    pub fn set_cut_algorithm(&mut self, cut_algorithm: CutAlgorithm) -> &Range<f32> {
+        self.cut_algorithm = cut_algorithm;
+
+        let samples = self.samples.clone();
+        self.set_cuts_from_samples(samples);
+
+        &self.cuts
+    }
+
     pub async fn from_fits_hdu_async<'a, R>(
         gl: &WebGlContext,
         hdu: &mut AsyncHDU<'a, BufReader<R>, extension::image::Image>,
         coo_sys: CooSystem,
+        cut_algorithm: CutAlgorithm,
         //reader: &'a mut BufReader<R>,
     ) -> Result<Self, JsValue>
     where
@@ -317,6 +392,9 @@ impl Image {
             .unwrap_or(Ok(std::f64::NAN))
             .unwrap() as f32;
 
+        // The third axis of a data cube
+        let axis = CubeAxis::from_header(header);
+
         // Create a WCS from a specific header unit
         let wcs = WCS::from_fits_header(header)
             .map_err(|e| JsValue::from_str(&format!("WCS parsing error: reason: {}", e)))?;
@@ -327,7 +405,7 @@ impl Image {
             stream::Data::U8(data) => {
                 let reader = data.map_ok(|v| v[0].to_le_bytes()).into_async_read();
 
-                Self::from_reader_and_wcs::<_, R8UI>(
+                Self::from_fits_reader::<_, R8UI>(
                     gl,
                     reader,
                     wcs,
@@ -335,13 +413,15 @@ impl Image {
                     Some(offset),
                     Some(blank),
                     coo_sys,
+                    axis,
+                    cut_algorithm,
                 )
                 .await
             }
             stream::Data::I16(data) => {
                 let reader = data.map_ok(|v| v[0].to_le_bytes()).into_async_read();
 
-                Self::from_reader_and_wcs::<_, R16I>(
+                Self::from_fits_reader::<_, R16I>(
                     gl,
                     reader,
                     wcs,
@@ -349,13 +429,15 @@ impl Image {
                     Some(offset),
                     Some(blank),
                     coo_sys,
+                    axis,
+                    cut_algorithm,
                 )
                 .await
             }
             stream::Data::I32(data) => {
                 let reader = data.map_ok(|v| v[0].to_le_bytes()).into_async_read();
 
-                Self::from_reader_and_wcs::<_, R32I>(
+                Self::from_fits_reader::<_, R32I>(
                     gl,
                     reader,
                     wcs,
@@ -363,6 +445,8 @@ impl Image {
                     Some(offset),
                     Some(blank),
                     coo_sys,
+                    axis,
+                    cut_algorithm,
                 )
                 .await
             }
@@ -374,7 +458,7 @@ impl Image {
                     })
                     .into_async_read();
 
-                Self::from_reader_and_wcs::<_, R32I>(
+                Self::from_fits_reader::<_, R32I>(
                     gl,
                     reader,
                     wcs,
@@ -382,13 +466,15 @@ impl Image {
                     Some(offset),
                     Some(blank),
                     coo_sys,
+                    axis,
+                    cut_algorithm,
                 )
                 .await
             }
             stream::Data::F32(data) => {
                 let reader = data.map_ok(|v| v[0].to_le_bytes()).into_async_read();
 
-                Self::from_reader_and_wcs::<_, R32F>(
+                Self::from_fits_reader::<_, R32F>(
                     gl,
                     reader,
                     wcs,
@@ -396,6 +482,8 @@ impl Image {
                     Some(offset),
                     Some(blank),
                     coo_sys,
+                    axis,
+                    cut_algorithm,
                 )
                 .await
             }
@@ -407,7 +495,7 @@ impl Image {
                     })
                     .into_async_read();
 
-                Self::from_reader_and_wcs::<_, R32F>(
+                Self::from_fits_reader::<_, R32F>(
                     gl,
                     reader,
                     wcs,
@@ -415,12 +503,132 @@ impl Image {
                     Some(offset),
                     Some(blank),
                     coo_sys,
+                    axis,
+                    cut_algorithm,
                 )
                 .await
             }
         }
     }
 
+    // Create an image from the data of a FITS HDU
+    //
+    // The planes of a data cube are all read and kept in memory, the first one being displayed
+    #[allow(clippy::too_many_arguments)]
+    async fn from_fits_reader<R, F>(
+        gl: &WebGlContext,
+        mut reader: R,
+        wcs: WCS,
+        scale: Option<f32>,
+        offset: Option<f32>,
+        blank: Option<f32>,
+        coo_sys: CooSystem,
+        axis: Option<CubeAxis>,
+        cut_algorithm: CutAlgorithm,
+    ) -> Result<Self, JsValue>
+    where
+        F: ImageFormat,
+        R: AsyncReadExt + Unpin,
+    {
+        if let Some(axis) = axis {
+            let (width, height) = wcs.img_dimensions();
+            let plane_num_bytes = (width as usize)
+                * (height as usize)
+                * std::mem::size_of::<<F::P as Pixel>::Item>()
+                * F::NUM_CHANNELS;
+
+            let mut data = vec![0; plane_num_bytes * (axis.num_slices as usize)];
+            reader.read_exact(&mut data[..]).await.map_err(|_| {
+                JsValue::from_str("invalid data with respect to the NAXIS3 given in the header")
+            })?;
+
+            let mut image = Self::from_reader_and_wcs::<_, F>(
+                gl,
+                futures::io::Cursor::new(&data[..plane_num_bytes]),
+                wcs,
+                scale,
+                offset,
+                blank,
+                coo_sys,
+                cut_algorithm,
+            )
+            .await?;
+            image.cube = Some(Cube::new(data, plane_num_bytes, axis));
+
+            Ok(image)
+        } else {
+            Self::from_reader_and_wcs::<_, F>(
+                gl,
+                reader,
+                wcs,
+                scale,
+                offset,
+                blank,
+                coo_sys,
+                cut_algorithm,
+            )
+            .await
+        }
+    }
+
+    #[inline]
+    pub fn get_cube(&self) -> Option<&Cube> {
+        self.cube.as_ref()
+    }
+
+    /// Display a slice of a data cube
+    ///
+    /// The plane of the slice is sent to the GPU, replacing the one currently displayed.
+    /// The cuts of the previous slice are kept
+    pub fn set_slice(&mut self, slice: u64) -> Result<(), JsValue> {
+        let cube = self
+            .cube
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("The image is not a data cube"))?;
+        let plane = cube.get_plane(slice).ok_or_else(|| {
+            JsValue::from_str(&format!(
+                "Slice {} out of range, the cube has {} slices",
+                slice,
+                cube.get_axis().num_slices
+            ))
+        })?;
+
+        let (width, height) = self.wcs.img_dimensions();
+        let reader = futures::io::Cursor::new(plane);
+        let gl = &self.gl;
+        let blank = self.blank;
+        // The plane is already in memory so the textures are created without waiting
+        let textures = match self.channel {
+            ChannelType::R8UI => {
+                Self::create_textures::<R8UI, _>(gl, width, height, reader, blank).now_or_never()
+            }
+            ChannelType::R16I => {
+                Self::create_textures::<R16I, _>(gl, width, height, reader, blank).now_or_never()
+            }
+            ChannelType::R32I => {
+                Self::create_textures::<R32I, _>(gl, width, height, reader, blank).now_or_never()
+            }
+            ChannelType::R32F => {
+                Self::create_textures::<R32F, _>(gl, width, height, reader, blank).now_or_never()
+            }
+            _ => None,
+        };
+        let (textures, samples, max_tex_size_x, max_tex_size_y) =
+            textures.ok_or_else(|| JsValue::from_str("The slice plane could not be loaded"))??;
+
+        self.textures = textures;
+        // The cuts are kept, the user recomputes them from the new plane with `set_cut_algorithm`
+        self.set_samples(&samples);
+        self.max_tex_size_x = max_tex_size_x;
+        self.max_tex_size_y = max_tex_size_y;
+
+        if let Some(cube) = self.cube.as_mut() {
+            cube.set_slice(slice);
+        }
+
+        Ok(())
+    }
+
     pub fn recompute_vertices(
         &mut self,
         camera: &CameraViewPort,
@@ -593,6 +801,7 @@ impl Image {
         cfg: &ImageMetadata,
         camera: &CameraViewPort,
         projection: &ProjectionType,
+        cdf: &Texture2D,
     ) -> Result<(), JsValue> {
         if self.coo_sys != camera.get_coo_system() {
             self.coo_sys = camera.get_coo_system();
@@ -707,6 +916,7 @@ impl Image {
                     .attach_uniforms_with_params_from(color, colormaps)
                     .attach_uniform("opacity", opacity)
                     .attach_uniform("tex", texture)
+                    .attach_uniform("cdf", cdf)
                     .attach_uniform("scale", &self.scale)
                     .attach_uniform("offset", &self.offset)
                     .attach_uniform("blank", &self.blank)
@@ -735,4 +945,130 @@ impl Image {
     pub fn get_centered_fov(&self) -> &CenteredFoV {
         &self.centered_fov
     }
+
+    /// Compute the ICRS coverage of the image footprint
+    ///
+    /// The image borders are sampled so that the footprint follows their
+    /// curvature on the sky for non zenithal projections
+    pub fn get_coverage(&self, depth: u8) -> Result<HEALPixCoverage, JsValue> {
+        const NUM_SAMPLES_PER_BORDER: usize = 8;
+
+        let (width, height) = self.wcs.img_dimensions();
+        let (w, h) = (width as f64 - 1.0, height as f64 - 1.0);
+        let corners = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)];
+
+        let to_xyzw = |x: f64, y: f64| -> Option<Vector4<f64>> {
+            let xyz = self.wcs.unproj_lonlat(&ImgXY::new(x, y))?.to_xyz();
+            Some(Vector4::new(xyz.y(), xyz.z(), xyz.x(), 1.0))
+        };
+
+        let vertices = corners
+            .iter()
+            .zip(corners.iter().cycle().skip(1))
+            .flat_map(|(&(x1, y1), &(x2, y2))| {
+                (0..NUM_SAMPLES_PER_BORDER).map(move |i| {
+                    let t = (i as f64) / (NUM_SAMPLES_PER_BORDER as f64);
+                    (x1 + (x2 - x1) * t, y1 + (y2 - y1) * t)
+                })
+            })
+            .map(|(x, y)| to_xyzw(x, y))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(JsValue::from_str(
+                "The image border does not lie in the sky",
+            ))?;
+        let inside = to_xyzw(width as f64 / 2.0, height as f64 / 2.0)
+            .ok_or(JsValue::from_str("(w / 2, h / 2) px cannot be unprojected"))?;
+
+        Ok(HEALPixCoverage::from_3d_coos(
+            depth,
+            vertices.into_iter(),
+            &inside,
+        ))
+    }
+
+    /// Read the value of the image pixel located at a sky position
+    ///
+    /// The position is given in the coo system of the camera.
+    /// Returns `None` if the position does not lie in the image.
+    /// Otherwise the returned object contains:
+    /// * `x`, `y` - The pixel coordinates in the image
+    /// * `value` - The physical value (i.e. BSCALE/BZERO applied) of the pixel
+    /// for FITS images, `null` if the pixel is BLANK. For JPG/PNG images,
+    /// an array of 4 items (rgba) is given.
+    pub fn read_pixel(
+        &self,
+        pos: &LonLatT<f64>,
+        camera: &CameraViewPort,
+    ) -> Result<Option<JsValue>, JsValue> {
+        // 1. Convert the position to ICRS, the frame in which the wcs is defined
+        let xyzw = crate::coosys::apply_coo_system(
+            camera.get_coo_system(),
+            CooSystem::ICRS,
+            &pos.vector(),
+        );
+        let lonlat = xyzw.lonlat();
+
+        // 2. Find the pixel through the WCS
+        let xy = if let Some(xy) = self.wcs.proj_lonlat(&wcs::LonLat::new(
+            lonlat.lon().to_radians(),
+            lonlat.lat().to_radians(),
+        )) {
+            xy
+        } else {
+            return Ok(None);
+        };
+
+        let (width, height) = self.wcs.img_dimensions();
+        let (x, y) = (xy.x().floor(), xy.y().floor());
+        if x < 0.0 || y < 0.0 || x >= width as f64 || y >= height as f64 {
+            return Ok(None);
+        }
+        let (x, y) = (x as u64, y as u64);
+
+        // 3. Find the texture chunk containing that pixel
+        // (see subdivide_texture::crop_image for the chunks ordering)
+        let max_tex_size_x = self.max_tex_size_x as u64;
+        let max_tex_size_y = self.max_tex_size_y as u64;
+
+        let num_texture_y = if self.textures.len() == 1 {
+            1
+        } else {
+            (height / max_tex_size_y) + 1
+        };
+        let id_tx = x / max_tex_size_x;
+        let id_ty = y / max_tex_size_y;
+        let id_t = (id_ty + id_tx * num_texture_y) as usize;
+
+        let texture = self
+            .textures
+            .get(id_t)
+            .ok_or_else(|| JsValue::from_str("Texture chunk not found"))?;
+
+        let raw = texture.read_pixel(
+            (x - id_tx * max_tex_size_x) as i32,
+            (y - id_ty * max_tex_size_y) as i32,
+        )?;
+
+        // 4. Apply BSCALE/BZERO and check for BLANK
+        let value = if self.channel == ChannelType::RGBA8U {
+            raw
+        } else {
+            let raw = raw
+                .as_f64()
+                .ok_or_else(|| JsValue::from_str("Error unwraping the pixel read value."))?;
+
+            if raw.is_nan() || raw as f32 == self.blank {
+                JsValue::NULL
+            } else {
+                JsValue::from_f64(raw * (self.scale as f64) + (self.offset as f64))
+            }
+        };
+
+        let pixel = js_sys::Object::new();
+        js_sys::Reflect::set(&pixel, &"x".into(), &JsValue::from_f64(x as f64))?;
+        js_sys::Reflect::set(&pixel, &"y".into(), &JsValue::from_f64(y as f64))?;
+        js_sys::Reflect::set(&pixel, &"value".into(), &value)?;
+
+        Ok(Some(pixel.into()))
+    }
 }
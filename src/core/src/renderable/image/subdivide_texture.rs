@@ -2,13 +2,11 @@ use wasm_bindgen::JsValue;
 
 use futures::AsyncReadExt;
 
-use super::cuts;
 use al_core::image::format::ImageFormat;
 use al_core::texture::pixel::Pixel;
 use al_core::texture::TEX_PARAMS;
 use al_core::Texture2D;
 use al_core::WebGlContext;
-use std::ops::Range;
 
 pub async fn crop_image<'a, F, R>(
     gl: &WebGlContext,
@@ -17,7 +15,7 @@ pub async fn crop_image<'a, F, R>(
     mut reader: R,
     max_tex_size: u64,
     blank: f32,
-) -> Result<(Vec<Texture2D>, Range<f32>), JsValue>
+) -> Result<(Vec<Texture2D>, Vec<f32>), JsValue>
 where
     F: ImageFormat,
     R: AsyncReadExt + Unpin,
@@ -85,7 +83,7 @@ where
                     (num_pixels_to_read as usize) * F::NUM_CHANNELS,
                 );
 
-                // compute the cuts if the pixel is grayscale
+                // sample the values to compute the cuts if the pixel is grayscale
                 if F::NUM_CHANNELS == 1 {
                     // fill the samples buffer
                     if (pixels_written / width) % (step_cut as u64) == 0 {
@@ -130,11 +128,5 @@ where
         }
     }
 
-    let cuts = if F::NUM_CHANNELS == 1 {
-        cuts::first_and_last_percent(&mut samples, 1, 99)
-    } else {
-        0.0..1.0
-    };
-
-    Ok((tex_chunks, cuts))
+    Ok((tex_chunks, samples))
 }
@@ -92,6 +92,16 @@ impl MOCRenderer {
         }
     }
 
+    /// Replace the coverage of a MOC, keeping its configuration
+    pub fn set_hpx_coverage(&mut self, cfg: &Cfg, moc: HEALPixCoverage) -> Option<()> {
+        let name = cfg.get_uuid();
+
+        let idx = self.cfgs.iter().position(|cfg| cfg.get_uuid() == name)?;
+        self.mocs[idx] = MOCHierarchy::from_full_res_moc(self.gl.clone(), moc, &self.cfgs[idx]);
+
+        Some(())
+    }
+
     pub fn remove(
         &mut self,
         cfg: &Cfg,
@@ -1,4 +1,5 @@
 pub mod catalog;
+pub mod composite;
 pub mod final_pass;
 pub mod grid;
 pub mod hips;
@@ -9,6 +10,7 @@ pub mod shape;
 pub mod text;
 pub mod utils;
 
+use crate::renderable::composite::RGBComposite;
 use crate::renderable::image::Image;
 use crate::tile_fetcher::TileFetcherQueue;
 
@@ -19,17 +21,23 @@ pub use hips::HiPS;
 pub use catalog::Manager;
 
 use al_api::color::ColorRGB;
+use al_api::composite::RGBCompositeCfg;
 use al_api::hips::HiPSCfg;
+use al_api::hips::ImageExt;
 use al_api::hips::ImageMetadata;
+use al_api::hips::TransferFunction;
 use al_api::image::ImageParams;
 
 use al_core::colormap::Colormaps;
 
+use al_core::image::format::{ImageFormat, R32F};
 use al_core::shader::Shader;
-use al_core::VertexArrayObject;
+use al_core::texture::TEX_PARAMS;
 use al_core::WebGlContext;
+use al_core::{Texture2D, VertexArrayObject};
 
 use crate::camera::CameraViewPort;
+use crate::downloader::fetch::FetchOptions;
 use crate::shader::ShaderId;
 use crate::Abort;
 use crate::ProjectionType;
@@ -59,12 +67,18 @@ pub struct Layers {
     surveys: HashMap<CreatorDid, HiPS>,
     images: HashMap<Id, Vec<Image>>, // an url can contain multiple images i.e. a fits file can contain
     // multiple image extensions
+    // RGB composites of other layers, indexed by their layer name
+    composites: HashMap<LayerId, RGBComposite>,
     // The meta data associated with a layer
     meta: HashMap<LayerId, ImageMetadata>,
     // Hashmap between FITS image urls/HiPS creatorDid and layers
     ids: HashMap<LayerId, String>,
     // Layers given in a specific order to draw
     layers: Vec<LayerId>,
+    // Cumulative distributions of the layer values used by the histogram equalization
+    cdfs: HashMap<LayerId, Texture2D>,
+    // The identity distribution, bound for the layers having none
+    default_cdf: Texture2D,
 
     raytracer: RayTracer,
     // A vao that takes all the screen
@@ -72,9 +86,16 @@ pub struct Layers {
 
     background_color: ColorRGB,
 
+    // The GPU memory in bytes shared by the textures of the HiPS
+    gpu_memory_budget: usize,
+    // The maximum number of slices of a texture array
+    max_num_layers: i32,
+
     gl: WebGlContext,
 }
 
+const DEFAULT_GPU_MEMORY_BUDGET: usize = 512 * 1024 * 1024;
+
 const DEFAULT_BACKGROUND_COLOR: ColorRGB = ColorRGB {
     r: 0.05,
     g: 0.05,
@@ -96,6 +117,10 @@ fn get_backgroundcolor_shader<'a>(
         .map_err(|e| e.into())
 }
 
+fn create_cdf_texture(gl: &WebGlContext, cdf: &[f32]) -> Result<Texture2D, JsValue> {
+    Texture2D::create_from_raw_pixels::<R32F>(gl, cdf.len() as i32, 1, TEX_PARAMS, Some(cdf))
+}
+
 pub struct ImageLayer {
     /// Layer name
     pub layer: String,
@@ -122,9 +147,15 @@ impl Layers {
     pub fn new(gl: &WebGlContext, projection: &ProjectionType) -> Result<Self, JsValue> {
         let surveys = HashMap::new();
         let images = HashMap::new();
+        let composites = HashMap::new();
         let meta = HashMap::new();
         let ids = HashMap::new();
         let layers = Vec::new();
+        let cdfs = HashMap::new();
+        let default_cdf = create_cdf_texture(
+            gl,
+            &image::histogram::cdf(&[], 0.0, 1.0, image::histogram::CDF_NUM_SAMPLES),
+        )?;
 
         // - The raytracer is a mesh covering the view. Each pixel of this mesh
         //   is unprojected to get its (ra, dec). Then we query ang2pix to get
@@ -153,23 +184,86 @@ impl Layers {
             .unbind();
 
         let background_color = DEFAULT_BACKGROUND_COLOR;
+        let max_num_layers = WebGl2RenderingContext::get_parameter(
+            &gl,
+            WebGl2RenderingContext::MAX_ARRAY_TEXTURE_LAYERS,
+        )?
+        .as_f64()
+        .unwrap_or(256.0) as i32;
+
         Ok(Layers {
             surveys,
             images,
+            composites,
 
             meta,
             ids,
             layers,
+            cdfs,
+            default_cdf,
 
             raytracer,
 
             background_color,
             screen_vao,
 
+            gpu_memory_budget: DEFAULT_GPU_MEMORY_BUDGET,
+            max_num_layers,
+
             gl,
         })
     }
 
+    /// Set the GPU memory in bytes shared by the textures of the HiPS
+    pub fn set_gpu_memory_budget(&mut self, budget: usize) {
+        self.gpu_memory_budget = budget;
+    }
+
+    // The number of slices of the texture array of a HiPS sharing
+    // the GPU memory with `num_surveys` HiPS
+    fn num_slices(&self, cfg: &HiPSConfig, num_surveys: usize, camera: &CameraViewPort) -> i32 {
+        let num_pixels = (camera.get_width() as f64) * (camera.get_height() as f64);
+
+        crate::survey::config::compute_num_slices(
+            cfg.num_slices(),
+            cfg.get_texture_size(),
+            cfg.get_format().get_channel().bytes_per_pixel(),
+            num_pixels,
+            self.max_num_layers,
+            self.gpu_memory_budget / num_surveys.max(1),
+        )
+    }
+
+    /// Size the texture arrays of the HiPS from the size of the view and the GPU memory budget
+    ///
+    /// Returns the creator dids of the HiPS whose textures have been emptied,
+    /// their tiles have to be requested again
+    pub fn resize_hips_textures(
+        &mut self,
+        camera: &CameraViewPort,
+    ) -> Result<Vec<CreatorDid>, JsValue> {
+        let num_surveys = self.surveys.len();
+        let sizes = self
+            .surveys
+            .iter()
+            .map(|(cdid, hips)| {
+                let num_slices = self.num_slices(hips.get_config(), num_surveys, camera);
+                (cdid.clone(), num_slices)
+            })
+            .collect::<Vec<_>>();
+
+        let mut emptied = vec![];
+        for (cdid, num_slices) in sizes {
+            if let Some(hips) = self.surveys.get_mut(&cdid) {
+                if hips.set_num_slices(num_slices)? {
+                    emptied.push(cdid);
+                }
+            }
+        }
+
+        Ok(emptied)
+    }
+
     pub fn set_survey_url(&mut self, cdid: &CreatorDid, new_url: String) -> Result<(), JsValue> {
         if let Some(survey) = self.surveys.get_mut(cdid) {
             // update the root_url
@@ -287,16 +381,56 @@ impl Layers {
             if draw_opt.visible() {
                 // 1. Update the survey if necessary
                 let id = self.ids.get(layer).expect("Url should be found");
+                let cdf = self.cdfs.get(layer).unwrap_or(&self.default_cdf);
                 if let Some(survey) = self.surveys.get_mut(id) {
                     survey.update(camera, projection);
 
                     // 2. Draw it if its opacity is not null
-                    survey.draw(shaders, colormaps, camera, raytracer, draw_opt, projection)?;
+                    survey.draw(
+                        shaders, colormaps, camera, raytracer, draw_opt, projection, cdf,
+                    )?;
                 } else if let Some(images) = self.images.get_mut(id) {
                     // 2. Draw it if its opacity is not null
                     for image in images {
-                        image.draw(shaders, colormaps, draw_opt, camera, projection)?;
+                        image.draw(shaders, colormaps, draw_opt, camera, projection, cdf)?;
                     }
+                } else if let Some(composite) = self.composites.get_mut(id) {
+                    // 2. Draw its channel layers offscreen, whether they are visible or not
+                    let [red, green, blue] = composite.get_channels();
+                    let metas = &self.meta;
+                    let channel_metas = [red, green, blue].map(|channel| {
+                        metas
+                            .get(&channel.layer)
+                            .map(|meta| composite::channel_meta(channel, meta))
+                    });
+
+                    let screen_size = (camera.get_width() as i32, camera.get_height() as i32);
+                    let (surveys, images, ids) = (&mut self.surveys, &mut self.images, &self.ids);
+                    // The channels are not equalized
+                    let cdf = &self.default_cdf;
+                    composite.draw_channels(
+                        screen_size,
+                        channel_metas,
+                        |channel_layer, meta| {
+                            let id = ids.get(channel_layer).expect("Url should be found");
+                            if let Some(survey) = surveys.get_mut(id) {
+                                survey.update(camera, projection);
+                                survey.draw(
+                                    shaders, colormaps, camera, raytracer, meta, projection, cdf,
+                                )?;
+                            } else if let Some(images) = images.get_mut(id) {
+                                for image in images {
+                                    image
+                                        .draw(shaders, colormaps, meta, camera, projection, cdf)?;
+                                }
+                            }
+
+                            Ok(())
+                        },
+                    )?;
+
+                    // 3. Combine them
+                    composite.draw(shaders, &self.screen_vao, draw_opt)?;
                 }
             }
         }
@@ -326,6 +460,7 @@ impl Layers {
         // Color configs, and urls are indexed by layer
         self.meta.remove(layer).ok_or(err_layer_not_found.clone())?;
         let id = self.ids.remove(layer).ok_or(err_layer_not_found.clone())?;
+        self.cdfs.remove(layer);
         // layer from layers does also need to be removed
         let id_layer = self
             .layers
@@ -360,6 +495,9 @@ impl Layers {
             } else if let Some(_) = self.images.remove(&id) {
                 // A FITS image has been found and removed
                 Ok(id_layer)
+            } else if self.composites.remove(&id).is_some() {
+                // A RGB composite has been found and removed
+                Ok(id_layer)
             } else {
                 Err(JsValue::from_str(&format!(
                     "Url found {:?} is associated to no surveys.",
@@ -389,7 +527,46 @@ impl Layers {
 
         // Add the new
         self.meta.insert(new_layer.to_string(), meta);
-        self.ids.insert(new_layer.to_string(), id);
+        if let Some(cdf) = self.cdfs.remove(layer) {
+            self.cdfs.insert(new_layer.to_string(), cdf);
+        }
+        // RGB composites are indexed by their layer name
+        if let Some(composite) = self.composites.remove(&id) {
+            self.composites.insert(new_layer.to_string(), composite);
+            self.ids
+                .insert(new_layer.to_string(), new_layer.to_string());
+        } else {
+            self.ids.insert(new_layer.to_string(), id);
+        }
+
+        Ok(())
+    }
+
+    /// Set the cumulative distribution of the values of a layer
+    ///
+    /// It is used by the histogram equalization stretch. The distribution is
+    /// removed if `cdf` is `None`. The texture of a layer is allocated once,
+    /// only its content is updated afterwards.
+    pub fn set_layer_cdf(&mut self, layer: &str, cdf: Option<&[f32]>) -> Result<(), JsValue> {
+        if let Some(cdf) = cdf {
+            if let Some(texture) = self.cdfs.get(layer) {
+                let view = unsafe { R32F::view(cdf) };
+                texture
+                    .bind()
+                    .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                        0,
+                        0,
+                        cdf.len() as i32,
+                        1,
+                        Some(view.as_ref()),
+                    );
+            } else {
+                let texture = create_cdf_texture(&self.gl, cdf)?;
+                self.cdfs.insert(layer.to_string(), texture);
+            }
+        } else {
+            self.cdfs.remove(layer);
+        }
 
         Ok(())
     }
@@ -428,11 +605,21 @@ impl Layers {
         let HiPSCfg {
             layer,
             properties,
-            meta,
+            mut meta,
+            request_options,
         } = hips;
 
         let img_ext = meta.img_format;
 
+        // The cuts of the properties are taken by default for FITS tiles
+        if img_ext == ImageExt::Fits && meta.color.min_cut.is_none() && meta.color.max_cut.is_none()
+        {
+            if let Some((min_cut, max_cut)) = properties.get_cutouts() {
+                meta.color.min_cut = Some(min_cut);
+                meta.color.max_cut = Some(max_cut);
+            }
+        }
+
         // 1. Add the layer name
         let layer_already_found = self.layers.iter().any(|l| l == &layer);
 
@@ -465,7 +652,11 @@ impl Layers {
 
         if !cdid_already_found {
             // The url is not processed yet
-            let cfg = HiPSConfig::new(&properties, img_ext)?;
+            let mut cfg = HiPSConfig::new(&properties, img_ext)?;
+            cfg.set_fetch_options(FetchOptions::new(&request_options));
+            // The GPU memory is shared with the new HiPS
+            let num_slices = self.num_slices(&cfg, self.surveys.len() + 1, camera);
+            cfg.set_num_slices(num_slices);
 
             /*if let Some(initial_ra) = properties.get_initial_ra() {
                 if let Some(initial_dec) = properties.get_initial_dec() {
@@ -482,6 +673,10 @@ impl Layers {
             // add the frame to the camera
 
             self.surveys.insert(creator_did.clone(), hips);
+        } else if let Some(hips) = self.surveys.get_mut(&creator_did) {
+            // The requests of the HiPS follow the options of the last layer added
+            hips.get_config_mut()
+                .set_fetch_options(FetchOptions::new(&request_options));
         }
 
         self.ids.insert(layer.clone(), creator_did.clone());
@@ -557,6 +752,45 @@ impl Layers {
         Ok(img.as_slice())
     }
 
+    pub fn add_rgb_composite(
+        &mut self,
+        cfg: RGBCompositeCfg,
+        camera: &mut CameraViewPort,
+        proj: &ProjectionType,
+        tile_fetcher: &mut TileFetcherQueue,
+    ) -> Result<(), JsValue> {
+        // The composite already exists, its config is updated
+        if let Some(composite) = self.composites.get_mut(&cfg.layer) {
+            composite.set_cfg(&cfg);
+            self.meta.insert(cfg.layer, cfg.meta);
+
+            return Ok(());
+        }
+
+        let composite = RGBComposite::new(&self.gl, &cfg, camera)?;
+        let RGBCompositeCfg { layer, meta, .. } = cfg;
+
+        // 1. Add the layer name
+        let layer_already_found = self.layers.iter().any(|s| s == &layer);
+
+        let idx = if layer_already_found {
+            self.remove_layer(&layer, camera, proj, tile_fetcher)?
+        } else {
+            self.layers.len()
+        };
+
+        self.layers.insert(idx, layer.clone());
+
+        // 2. Add the meta information of the layer
+        self.meta.insert(layer.clone(), meta);
+
+        // 3. Add the composite, indexed by its layer name
+        self.composites.insert(layer.clone(), composite);
+        self.ids.insert(layer.clone(), layer);
+
+        Ok(())
+    }
+
     pub fn get_layer_cfg(&self, layer: &str) -> Result<ImageMetadata, JsValue> {
         self.meta
             .get(layer)
@@ -615,6 +849,19 @@ impl Layers {
         Ok(())
     }
 
+    /// The layers stretched with the histogram equalization
+    pub fn get_histeq_layers(&self) -> Vec<LayerId> {
+        self.layers
+            .iter()
+            .filter(|layer| {
+                self.meta
+                    .get(*layer)
+                    .map_or(false, |meta| meta.color.stretch == TransferFunction::HistEq)
+            })
+            .cloned()
+            .collect()
+    }
+
     // Accessors
     // HiPSes getters
     pub fn get_hips_from_layer(&self, layer: &str) -> Option<&HiPS> {
@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
 use al_core::image::format::ChannelType;
 
@@ -12,7 +13,7 @@ use al_core::image::format::ImageFormat;
 #[cfg(feature = "webgl2")]
 use al_core::image::format::{R16I, R32I, R8UI};
 use al_core::image::format::{R32F, R64F, RGB8U, RGBA8U};
-use al_core::image::Image;
+use al_core::image::{Image, ImageType};
 use al_core::shader::{SendUniforms, ShaderBound};
 use al_core::texture::TEX_PARAMS;
 use al_core::Texture2DArray;
@@ -24,6 +25,7 @@ use super::texture::TextureUniforms;
 use crate::downloader::request::allsky::Allsky;
 use crate::healpix::cell::HEALPixCell;
 use crate::healpix::cell::NUM_HPX_TILES_DEPTH_ZERO;
+use crate::lru_cache::{ByteSize, Cache};
 use crate::math::lonlat::LonLatT;
 use crate::time::Time;
 use crate::Abort;
@@ -147,6 +149,44 @@ pub struct ImageSurveyTextures {
     available_tiles_during_frame: bool,
     //num_base_textures: usize,
     //exec: Rc<RefCell<TaskExecutor>>,
+
+    // The tiles received for the frames of a HiPS cube near the current one
+    frame_tiles: Cache<(u32, HEALPixCell), FrameTile>,
+    frame_allsky: HashMap<u32, Allsky>,
+
+    // Pixel values sampled from the tiles sent to the GPU so that the distribution
+    // of the values in the view can be computed without reading the textures back
+    tile_samples: HashMap<HEALPixCell, Vec<f32>>,
+}
+
+type TileImage = Arc<Mutex<Option<ImageType>>>;
+
+// A tile received for a frame of a HiPS cube
+struct FrameTile {
+    image: TileImage,
+    time_request: Time,
+}
+
+impl ByteSize for FrameTile {
+    fn byte_size(&self) -> usize {
+        self.image
+            .lock()
+            .unwrap_abort()
+            .as_ref()
+            .map_or(0, |image| image.byte_size())
+    }
+}
+
+// Number of frames before and after the current one whose tiles are kept in memory
+const NUM_CACHED_FRAMES_AROUND: u32 = 4;
+// Memory budget of the tiles kept for the frames of a HiPS cube
+const FRAME_TILES_BUDGET: usize = 64 * 1024 * 1024;
+
+// Number of pixel values sampled from a tile
+const NUM_SAMPLES_BY_TILE: usize = 1024;
+
+fn is_frame_near(frame: u32, cur_frame: u32) -> bool {
+    frame.max(cur_frame) - frame.min(cur_frame) <= NUM_CACHED_FRAMES_AROUND
 }
 
 // Define a set of textures compatible with the HEALPix tile format and size
@@ -166,6 +206,27 @@ fn create_texture_array<F: ImageFormat>(
     )
 }
 
+fn create_texture_array_from_config(
+    gl: &WebGlContext,
+    config: &HiPSConfig,
+) -> Result<Texture2DArray, JsValue> {
+    match config.get_format().get_channel() {
+        ChannelType::RGBA32F => unimplemented!(),
+        ChannelType::RGB32F => unimplemented!(),
+        ChannelType::RGBA8U => create_texture_array::<RGBA8U>(gl, config),
+        ChannelType::RGB8U => create_texture_array::<RGB8U>(gl, config),
+        ChannelType::R32F => create_texture_array::<R32F>(gl, config),
+        #[cfg(feature = "webgl2")]
+        ChannelType::R8UI => create_texture_array::<R8UI>(gl, config),
+        #[cfg(feature = "webgl2")]
+        ChannelType::R16I => create_texture_array::<R16I>(gl, config),
+        #[cfg(feature = "webgl2")]
+        ChannelType::R32I => create_texture_array::<R32I>(gl, config),
+        #[cfg(feature = "webgl2")]
+        ChannelType::R64F => create_texture_array::<R64F>(gl, config),
+    }
+}
+
 impl ImageSurveyTextures {
     pub fn new(gl: &WebGlContext, config: HiPSConfig) -> Result<ImageSurveyTextures, JsValue> {
         let size = config.num_textures() - NUM_HPX_TILES_DEPTH_ZERO;
@@ -190,23 +251,7 @@ impl ImageSurveyTextures {
             Texture::new(&HEALPixCell(0, 10), 10, now),
             Texture::new(&HEALPixCell(0, 11), 11, now),
         ];
-        let channel = config.get_format().get_channel();
-
-        let texture_2d_array = match channel {
-            ChannelType::RGBA32F => unimplemented!(),
-            ChannelType::RGB32F => unimplemented!(),
-            ChannelType::RGBA8U => create_texture_array::<RGBA8U>(gl, &config)?,
-            ChannelType::RGB8U => create_texture_array::<RGB8U>(gl, &config)?,
-            ChannelType::R32F => create_texture_array::<R32F>(gl, &config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R8UI => create_texture_array::<R8UI>(gl, &config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R16I => create_texture_array::<R16I>(gl, &config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R32I => create_texture_array::<R32I>(gl, &config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R64F => create_texture_array::<R64F>(gl, &config)?,
-        };
+        let texture_2d_array = create_texture_array_from_config(gl, &config)?;
         // The root textures have not been loaded
         //let ready = false;
         //let num_root_textures_available = 0;
@@ -227,30 +272,56 @@ impl ImageSurveyTextures {
 
             //ready,
             start_time,
+
+            frame_tiles: Cache::new(FRAME_TILES_BUDGET),
+            frame_allsky: HashMap::new(),
+
+            tile_samples: HashMap::new(),
         })
     }
 
     pub fn set_format(&mut self, gl: &WebGlContext, ext: ImageExt) -> Result<(), JsValue> {
         self.config.set_image_fmt(ext)?;
 
-        let channel = self.config.get_format().get_channel();
-
-        self.texture_2d_array = match channel {
-            ChannelType::RGBA32F => unimplemented!(),
-            ChannelType::RGB32F => unimplemented!(),
-            ChannelType::RGBA8U => create_texture_array::<RGBA8U>(gl, &self.config)?,
-            ChannelType::RGB8U => create_texture_array::<RGB8U>(gl, &self.config)?,
-            ChannelType::R32F => create_texture_array::<R32F>(gl, &self.config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R8UI => create_texture_array::<R8UI>(gl, &self.config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R16I => create_texture_array::<R16I>(gl, &self.config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R32I => create_texture_array::<R32I>(gl, &self.config)?,
-            #[cfg(feature = "webgl2")]
-            ChannelType::R64F => create_texture_array::<R64F>(gl, &self.config)?,
-        };
+        self.texture_2d_array = create_texture_array_from_config(gl, &self.config)?;
+
+        self.clear_textures();
+        // The tiles kept for the cube frames are in the previous format
+        self.frame_tiles.clear();
+        self.frame_allsky.clear();
+
+        Ok(())
+    }
+
+    /// Resize the texture array to `num_slices` slices
+    ///
+    /// When the array grows, the textures already loaded are copied into the new one.
+    /// Otherwise they are emptied and true is returned
+    pub fn set_num_slices(&mut self, gl: &WebGlContext, num_slices: i32) -> Result<bool, JsValue> {
+        let cur_num_slices = self.config.num_slices();
+        if cur_num_slices == num_slices {
+            return Ok(false);
+        }
+
+        self.config.set_num_slices(num_slices);
+        self.size = self.config.num_textures() - NUM_HPX_TILES_DEPTH_ZERO;
+
+        let texture_2d_array = create_texture_array_from_config(gl, &self.config)?;
+        // The indices of the textures are kept so that they keep their place in the new array
+        let copied = num_slices > cur_num_slices
+            && texture_2d_array
+                .copy_slices_from(&self.texture_2d_array)
+                .is_ok();
+        self.texture_2d_array = texture_2d_array;
 
+        if !copied {
+            self.clear_textures();
+        }
+
+        Ok(!copied)
+    }
+
+    fn clear_textures(&mut self) {
         let now = Time::now();
         self.base_textures = [
             Texture::new(&HEALPixCell(0, 0), 0, now),
@@ -269,35 +340,110 @@ impl ImageSurveyTextures {
 
         self.heap.clear();
         self.textures.clear();
+        self.tile_samples.clear();
         //self.ready = false;
         //self.num_root_textures_available = 0;
         self.available_tiles_during_frame = false;
         self.start_time = None;
+    }
+
+    /// Change the frame displayed of a HiPS cube
+    ///
+    /// The textures are emptied and refilled with the tiles already received for the new frame
+    pub fn set_cube_frame(&mut self, frame: u32) -> Result<(), JsValue> {
+        if self.config.get_cube_frame() == Some(frame) {
+            return Ok(());
+        }
+
+        self.config.set_cube_frame(frame)?;
+        self.clear_textures();
+
+        // Forget the frames too far from the new one
+        self.frame_tiles
+            .extract_where(|(f, _)| !is_frame_near(*f, frame));
+        self.frame_allsky.retain(|&f, _| is_frame_near(f, frame));
+
+        if let Some(allsky) = self.frame_allsky.remove(&frame) {
+            self.push_allsky(allsky)?;
+        }
+
+        let mut tiles = self.frame_tiles.extract_where(|(f, _)| *f == frame);
+        // Push the oldest requested tiles first so that they are the first to be removed
+        tiles.sort_unstable_by(|(_, t1), (_, t2)| {
+            t1.time_request
+                .partial_cmp(&t2.time_request)
+                .unwrap_abort()
+        });
+
+        for ((_, cell), FrameTile { image, time_request }) in tiles {
+            self.push_cube_tile(&cell, image, frame, time_request)?;
+        }
 
         Ok(())
     }
 
     pub fn push_allsky(&mut self, allsky: Allsky) -> Result<(), JsValue> {
-        let Allsky {
-            image,
-            time_req,
-            depth_tile,
-            ..
-        } = allsky;
-
         {
-            let mutex_locked = image.lock().unwrap_abort();
+            let mutex_locked = allsky.image.lock().unwrap_abort();
             let images = mutex_locked.as_ref().unwrap_abort();
             for (idx, image) in images.iter().enumerate() {
-                self.push(&HEALPixCell(depth_tile, idx as u64), image, time_req)?;
+                self.push(
+                    &HEALPixCell(allsky.depth_tile, idx as u64),
+                    image,
+                    allsky.time_req,
+                )?;
             }
         }
 
+        // Keep the allsky of a cube frame to not fetch it again when going back to it
+        if let Some(frame) = allsky.frame {
+            self.frame_allsky.insert(frame, allsky);
+        }
+
         //self.set_ready();
 
         Ok(())
     }
 
+    /// Push a tile of a HiPS cube frame
+    ///
+    /// The tile is kept in memory if its frame is near the current one and
+    /// is sent to the GPU only if it belongs to the current frame
+    pub fn push_cube_tile(
+        &mut self,
+        cell: &HEALPixCell,
+        image: TileImage,
+        frame: u32,
+        time_request: Time,
+    ) -> Result<(), JsValue> {
+        let cur_frame = if let Some(cur_frame) = self.config.get_cube_frame() {
+            cur_frame
+        } else {
+            return Err(JsValue::from_str("The HiPS is not a cube"));
+        };
+
+        if !is_frame_near(frame, cur_frame) || image.lock().unwrap_abort().is_none() {
+            return Ok(());
+        }
+
+        if frame == cur_frame {
+            let mutex_locked = image.lock().unwrap_abort();
+            if let Some(img) = &*mutex_locked {
+                self.push(cell, img, time_request)?;
+            }
+        }
+
+        self.frame_tiles.insert(
+            (frame, *cell),
+            FrameTile {
+                image,
+                time_request,
+            },
+        );
+
+        Ok(())
+    }
+
     /*pub fn set_ready(&mut self) {
         self.ready = true;
         // The survey is ready
@@ -332,6 +478,15 @@ impl ImageSurveyTextures {
                     let mut texture = self.textures.remove(&oldest_texture.cell).expect(
                         "Texture (oldest one) has not been found in the buffer of textures",
                     );
+                    // The tiles of the current cube frame kept in memory are those of the textures
+                    if let Some(frame) = self.config.get_cube_frame() {
+                        self.frame_tiles.extract_where(|(f, tile_cell)| {
+                            *f == frame && tile_cell.get_texture_cell(dd) == oldest_texture.cell
+                        });
+                    }
+                    self.tile_samples.retain(|tile_cell, _| {
+                        tile_cell.get_texture_cell(dd) != oldest_texture.cell
+                    });
                     // Clear and assign it to tex_cell
                     /*let idx = if tex_cell_is_root {
                         self.num_base_textures += 1;
@@ -381,14 +536,26 @@ impl ImageSurveyTextures {
                 &mut self.base_textures[idx as usize]
             };
 
+            // The pixel values of the FITS tiles are sampled for the histogram of the layer
+            let sample_step = if self.config.tex_storing_fits {
+                let tile_size = self.config.get_tile_size() as usize;
+                Some((tile_size * tile_size / NUM_SAMPLES_BY_TILE).max(1))
+            } else {
+                None
+            };
+
             //let missing = image.is_none();
-            send_to_gpu(
+            let values = send_to_gpu(
                 cell,
                 texture,
-                image,
+                &image,
                 &self.texture_2d_array,
                 &mut self.config,
+                sample_step,
             )?;
+            if let Some(values) = values {
+                self.tile_samples.insert(*cell, values);
+            }
 
             texture.append(
                 cell, // The tile cell
@@ -586,6 +753,30 @@ impl ImageSurveyTextures {
         }
     }
 
+    /// Get the pixel values sampled from the tiles of texture cells
+    ///
+    /// The nearest parent texture is taken for the cells whose texture has not been received yet.
+    /// The values are given as stored in the tiles, i.e. without BSCALE and BZERO applied
+    pub fn get_tile_samples(&self, texture_cells: &[HEALPixCell]) -> Vec<f32> {
+        let texture_cells = texture_cells
+            .iter()
+            .filter_map(|cell| {
+                if self.contains(cell) {
+                    Some(*cell)
+                } else {
+                    self.get_nearest_parent(cell)
+                }
+            })
+            .collect::<HashSet<_>>();
+
+        let dd = self.config.delta_depth();
+        self.tile_samples
+            .iter()
+            .filter(|(cell, _)| texture_cells.contains(&cell.get_texture_cell(dd)))
+            .flat_map(|(_, values)| values.iter().copied())
+            .collect()
+    }
+
     pub fn config(&self) -> &HiPSConfig {
         &self.config
     }
@@ -632,7 +823,9 @@ fn send_to_gpu<I: Image>(
     image: I,
     texture_array: &Texture2DArray,
     cfg: &mut HiPSConfig,
-) -> Result<(), JsValue> {
+    // Sample the pixel values of the image every `sample_step` pixels
+    sample_step: Option<usize>,
+) -> Result<Option<Vec<f32>>, JsValue> {
     // Index of the texture in the total set of textures
     let texture_idx = texture.idx();
     // Index of the slice of textures
@@ -663,9 +856,13 @@ fn send_to_gpu<I: Image>(
         idx_slice,
     );
 
-    image.tex_sub_image_3d(&texture_array, &offset)?;
+    if let Some(step) = sample_step {
+        image.tex_sub_image_3d_and_sample(&texture_array, &offset, step)
+    } else {
+        image.tex_sub_image_3d(&texture_array, &offset)?;
 
-    Ok(())
+        Ok(None)
+    }
 }
 
 impl SendUniforms for ImageSurveyTextures {
@@ -708,5 +905,8 @@ impl Drop for ImageSurveyTextures {
 
         // Cancel the tasks that have not been finished by the exec
         self.textures.clear();
+
+        self.frame_tiles.clear();
+        self.frame_allsky.clear();
     }
 }
@@ -133,6 +133,8 @@ fn create_black_tile(format: FormatImageType, width: i32, value: f32) -> TileArr
 #[derive(Debug)]
 pub struct HiPSConfig {
     pub root_url: String,
+    // The urls serving the HiPS, the root url among them
+    mirrors: Vec<String>,
     // HiPS image format
     // TODO: Make that independant of the HiPS but of the ImageFormat
     pub empty_image: EmptyTileImage,
@@ -150,6 +152,8 @@ pub struct HiPSConfig {
     // Max depth of the current HiPS tiles
     max_depth_texture: u8,
     max_depth_tile: u8,
+    // The number of slices of the texture array
+    num_slices: i32,
     num_textures: usize,
 
     pub is_allsky: bool,
@@ -173,16 +177,69 @@ pub struct HiPSConfig {
     //dataproduct_subtype: Option<Vec<String>>,
     //colored: bool,
     pub creator_did: String,
+
+    // Number of frames if the HiPS is a cube
+    cube_depth: Option<u32>,
+    // The frame currently displayed
+    cube_frame: u32,
+
+    // Whether the FITS tiles are tile-compressed
+    fits_compressed: bool,
+
+    // The options of the HTTP requests, shared by all of them
+    fetch_options: Rc<FetchOptions>,
 }
 
+use crate::downloader::fetch::FetchOptions;
+use crate::healpix::cell::NUM_HPX_TILES_DEPTH_ZERO;
 use crate::math;
 use crate::HiPSProperties;
 use al_api::coo_system::CooSystem;
+use std::rc::Rc;
 use wasm_bindgen::JsValue;
 
 const NUM_TEXTURES_BY_SIDE_SLICE: i32 = 8;
 const NUM_TEXTURES_BY_SLICE: i32 = NUM_TEXTURES_BY_SIDE_SLICE * NUM_TEXTURES_BY_SIDE_SLICE;
-const NUM_SLICES: i32 = 1;
+// The number of slices before the texture array is sized from the view
+const DEFAULT_NUM_SLICES: i32 = 1;
+// Ratio between the number of textures stored and the number of textures covering the view.
+// The cells are distorted by the projection and their parents are kept to blend the tiles
+const NUM_TEXTURES_BY_VIEW_TEXTURE: f64 = 16.0;
+
+/// The number of slices of a texture array of `num_slices` slices resized for a view
+/// of `num_pixels` pixels
+///
+/// It is bounded by the maximum number of layers of a texture array `max_num_layers` and by
+/// the memory `budget` given in bytes. The array grows as soon as more slices are needed but
+/// shrinks only when less than half of its slices are needed, so that resizing the view back
+/// and forth does not empty it every time.
+pub fn compute_num_slices(
+    num_slices: i32,
+    texture_size: i32,
+    bytes_per_pixel: usize,
+    num_pixels: f64,
+    max_num_layers: i32,
+    budget: usize,
+) -> i32 {
+    let texture_num_pixels = (texture_size as f64) * (texture_size as f64);
+
+    let num_textures = (NUM_HPX_TILES_DEPTH_ZERO as f64)
+        + NUM_TEXTURES_BY_VIEW_TEXTURE * num_pixels / texture_num_pixels;
+    let needed = (num_textures / (NUM_TEXTURES_BY_SLICE as f64)).ceil() as i32;
+
+    let slice_num_bytes =
+        texture_num_pixels * (NUM_TEXTURES_BY_SLICE as f64) * (bytes_per_pixel as f64);
+    let max_by_budget = ((budget as f64) / slice_num_bytes).floor() as i32;
+    // A slice is always kept for the root textures
+    let max = max_num_layers.min(max_by_budget).max(1);
+    let needed = needed.min(max);
+
+    if needed > num_slices || num_slices > max || 2 * needed <= num_slices {
+        needed
+    } else {
+        num_slices
+    }
+}
 
 impl HiPSConfig {
     /// Define a HiPS configuration
@@ -193,10 +250,17 @@ impl HiPSConfig {
     /// * `img_format` - Image format wanted by the user
     pub fn new(properties: &HiPSProperties, img_ext: ImageExt) -> Result<HiPSConfig, JsValue> {
         let root_url = properties.get_url();
+        let mut mirrors = vec![root_url.to_string()];
+        for mirror in properties.get_mirrors() {
+            if !mirrors.contains(mirror) {
+                mirrors.push(mirror.clone());
+            }
+        }
         let creator_did = properties.get_creator_did().to_string();
-        // Define the size of the 2d texture array depending on the
-        // characterics of the client
-        let num_textures = (NUM_TEXTURES_BY_SLICE * NUM_SLICES) as usize;
+        // The number of slices of the 2d texture array is then adapted to the view
+        // and the GPU by the layers
+        let num_slices = DEFAULT_NUM_SLICES;
+        let num_textures = (NUM_TEXTURES_BY_SLICE * num_slices) as usize;
 
         let max_depth_tile = properties.get_max_order();
         let tile_size = properties.get_tile_size();
@@ -308,10 +372,22 @@ impl HiPSConfig {
         } else {
             0
         };
+
+        // A HiPS cube with only one frame is considered as a classical HiPS
+        let cube_depth = properties.get_cube_depth().filter(|&depth| depth > 1);
+        let cube_frame = if let Some(cube_depth) = cube_depth {
+            properties
+                .get_cube_first_frame()
+                .unwrap_or(0)
+                .min(cube_depth - 1)
+        } else {
+            0
+        };
         let hips_config = HiPSConfig {
             creator_did,
             // HiPS name
             root_url: root_url.to_string(),
+            mirrors,
             // Tile size & blank tile data
             empty_image,
             // Texture config
@@ -326,6 +402,7 @@ impl HiPSConfig {
             max_depth_tile,
             min_depth_texture,
             min_depth_tile,
+            num_slices,
             num_textures,
 
             is_allsky,
@@ -346,6 +423,10 @@ impl HiPSConfig {
             tile_size,
             //dataproduct_subtype,
             //colored,
+            cube_depth,
+            cube_frame,
+            fits_compressed: properties.is_fits_compressed(),
+            fetch_options: Rc::new(FetchOptions::default()),
         };
 
         Ok(hips_config)
@@ -442,8 +523,18 @@ impl HiPSConfig {
         &self.root_url
     }
 
+    /// The urls the tiles can be requested from
+    #[inline(always)]
+    pub fn get_mirrors(&self) -> &[String] {
+        &self.mirrors
+    }
+
     #[inline(always)]
     pub fn set_root_url(&mut self, root_url: String) {
+        if !self.mirrors.contains(&root_url) {
+            self.mirrors.push(root_url.clone());
+        }
+
         self.root_url = root_url;
     }
 
@@ -485,6 +576,15 @@ impl HiPSConfig {
         &self.creator_did
     }
 
+    #[inline(always)]
+    pub fn get_fetch_options(&self) -> &Rc<FetchOptions> {
+        &self.fetch_options
+    }
+
+    pub fn set_fetch_options(&mut self, options: FetchOptions) {
+        self.fetch_options = Rc::new(options);
+    }
+
     #[inline(always)]
     pub fn get_tile_size(&self) -> i32 {
         self.tile_size
@@ -520,9 +620,15 @@ impl HiPSConfig {
         NUM_TEXTURES_BY_SLICE
     }
 
+    /// Set the number of slices of the texture array
+    pub fn set_num_slices(&mut self, num_slices: i32) {
+        self.num_slices = num_slices;
+        self.num_textures = (NUM_TEXTURES_BY_SLICE * num_slices) as usize;
+    }
+
     #[inline(always)]
     pub fn num_slices(&self) -> i32 {
-        NUM_SLICES
+        self.num_slices
     }
 
     #[inline(always)]
@@ -530,6 +636,45 @@ impl HiPSConfig {
         self.format
     }
 
+    /// The extension of the tile files
+    ///
+    /// FITS tiles can be stored tile-compressed in `fits.fz` files
+    pub fn get_tile_ext(&self) -> String {
+        let ext = self.format.get_ext_file();
+        if self.fits_compressed && *ext == ImageExt::Fits {
+            format!("{}.fz", ext)
+        } else {
+            ext.to_string()
+        }
+    }
+
+    #[inline(always)]
+    pub fn get_cube_depth(&self) -> Option<u32> {
+        self.cube_depth
+    }
+
+    /// The frame currently displayed, `None` if the HiPS is not a cube
+    #[inline(always)]
+    pub fn get_cube_frame(&self) -> Option<u32> {
+        self.cube_depth.map(|_| self.cube_frame)
+    }
+
+    pub fn set_cube_frame(&mut self, frame: u32) -> Result<(), JsValue> {
+        let cube_depth = self
+            .cube_depth
+            .ok_or_else(|| JsValue::from_str("The HiPS is not a cube"))?;
+
+        if frame >= cube_depth {
+            return Err(JsValue::from_str(&format!(
+                "Frame {} out of the cube, it must be < {}",
+                frame, cube_depth
+            )));
+        }
+        self.cube_frame = frame;
+
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn is_colored(&self) -> bool {
         self.format.is_colored()
@@ -557,3 +702,40 @@ impl SendUniforms for HiPSConfig {
         shader
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::compute_num_slices;
+
+    const MIB: usize = 1024 * 1024;
+    const GIB: usize = 1024 * MIB;
+
+    // The number of pixels of the view
+    const XGA: f64 = 1024.0 * 768.0;
+    const QHD_PLUS: f64 = 3200.0 * 1800.0;
+    const UHD: f64 = 3840.0 * 2160.0;
+
+    #[test]
+    fn grows_with_the_view() {
+        assert_eq!(compute_num_slices(1, 512, 4, XGA, 2048, GIB), 1);
+        assert_eq!(compute_num_slices(1, 512, 4, QHD_PLUS, 2048, GIB), 6);
+        assert_eq!(compute_num_slices(6, 512, 4, UHD, 2048, GIB), 9);
+    }
+
+    #[test]
+    fn is_bounded_by_the_gpu() {
+        // A slice of 8x8 RGBA textures of 512x512 pixels takes 64MiB
+        assert_eq!(compute_num_slices(1, 512, 4, UHD, 2048, 512 * MIB), 8);
+        assert_eq!(compute_num_slices(1, 512, 4, UHD, 4, GIB), 4);
+        // A slice is always kept for the root textures
+        assert_eq!(compute_num_slices(1, 512, 4, UHD, 2048, MIB), 1);
+    }
+
+    #[test]
+    fn shrinks_only_when_half_empty() {
+        assert_eq!(compute_num_slices(9, 512, 4, QHD_PLUS, 2048, GIB), 9);
+        assert_eq!(compute_num_slices(9, 512, 4, XGA, 2048, GIB), 1);
+        // Beyond the budget, the array shrinks right away
+        assert_eq!(compute_num_slices(9, 512, 4, UHD, 2048, 256 * MIB), 4);
+    }
+}
@@ -4,15 +4,68 @@ use crate::time::{DeltaTime, Time};
 use crate::Abort;
 
 use std::cell::RefCell;
-use std::collections::{HashMap, VecDeque};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 const MAX_NUM_TILE_FETCHING: usize = 8;
 const MAX_QUERY_QUEUE_LENGTH: usize = 100;
 
+// The priority of a tile query
+struct TilePriority {
+    // The coarsest tiles are fetched first
+    depth: u8,
+    // then the closest ones to the center of the view (in radians)
+    distance: f64,
+}
+
+impl TilePriority {
+    fn new(cell: &HEALPixCell, view_center: &Vector4<f64>) -> Self {
+        let (lon, lat) = cell.center();
+        let center = crate::math::lonlat::radec_to_xyzw(Angle(lon), Angle(lat));
+        let distance = center
+            .truncate()
+            .dot(view_center.truncate())
+            .clamp(-1.0, 1.0)
+            .acos();
+
+        Self {
+            depth: cell.depth(),
+            distance,
+        }
+    }
+
+    // Greater when the tile has to be fetched first
+    fn cmp_priority(&self, other: &Self) -> Ordering {
+        other.depth.cmp(&self.depth).then(
+            other
+                .distance
+                .partial_cmp(&self.distance)
+                .unwrap_or(Ordering::Equal),
+        )
+    }
+}
+
+// A tile query with its priority
+struct PrioritizedTile {
+    query: query::Tile,
+    priority: TilePriority,
+}
+
+// Sort a queue by increasing priority and discard the elements with the lowest priority
+// beyond MAX_QUERY_QUEUE_LENGTH
+fn sort_by_priority<T>(queue: &mut Vec<T>, priority: impl Fn(&T) -> &TilePriority) {
+    queue.sort_unstable_by(|a, b| priority(a).cmp_priority(priority(b)));
+
+    if queue.len() > MAX_QUERY_QUEUE_LENGTH {
+        let num_discarded = queue.len() - MAX_QUERY_QUEUE_LENGTH;
+        queue.drain(..num_discarded);
+    }
+}
+
 pub struct TileFetcherQueue {
-    // A stack of queries to fetch
-    queries: VecDeque<query::Tile>,
+    // The queries to fetch, sorted by priority when fetching
+    queries: Vec<PrioritizedTile>,
     base_tile_queries: Vec<query::Tile>,
     tiles_fetched_time: Time,
     num_tiles_fetched: usize,
@@ -27,10 +80,12 @@ pub struct HiPSLocalFiles {
     moc: web_sys::File,
 }
 
+use crate::math::angle::Angle;
 use crate::tile_fetcher::query::Tile;
 use crate::HEALPixCell;
 use al_api::hips::ImageExt;
 use al_core::image::format::ImageFormatType;
+use cgmath::{InnerSpace, Vector4};
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
 
@@ -84,7 +139,7 @@ impl HiPSLocalFiles {
 use crate::renderable::CreatorDid;
 impl TileFetcherQueue {
     pub fn new() -> Self {
-        let queries = VecDeque::new();
+        let queries = Vec::new();
         let base_tile_queries = Vec::new();
         let tiles_fetched_time = Time::now();
         let num_tiles_fetched = 0;
@@ -110,15 +165,13 @@ impl TileFetcherQueue {
         self.queries.clear();
     }
 
-    pub fn append(&mut self, query: query::Tile) {
-        // Check if the query has already been done
-        //if !self.query_set.contains(&query) {
-        // discard too old tile queries
-        // this may not be the best thing to do but
-        if self.queries.len() > MAX_QUERY_QUEUE_LENGTH {
-            self.queries.pop_front();
-        }
-        self.queries.push_back(query.clone());
+    /// Append a tile query to the queue
+    ///
+    /// `view_center` is the center of the view in the frame of the HiPS, the tiles
+    /// near it being fetched first for a given depth
+    pub fn append(&mut self, query: query::Tile, view_center: &Vector4<f64>) {
+        let priority = TilePriority::new(&query.cell, view_center);
+        self.queries.push(PrioritizedTile { query, priority });
     }
 
     // fetch the base tile
@@ -126,6 +179,48 @@ impl TileFetcherQueue {
         self.base_tile_queries.push(query);
     }
 
+    /// Switch the HiPS whose mirror is failing to another one of their mirrors
+    ///
+    /// Only the next tile requests go to the new mirror, the textures already
+    /// loaded are kept
+    pub fn switch_failing_mirrors<'a>(
+        &mut self,
+        hipses: impl Iterator<Item = &'a mut HiPS>,
+        downloader: &Rc<RefCell<Downloader>>,
+    ) {
+        for hips in hipses {
+            let cfg = hips.get_config_mut();
+            if cfg.get_mirrors().len() < 2 {
+                continue;
+            }
+
+            let hips_cdid = cfg.get_creator_did().to_string();
+            let mirror = downloader
+                .borrow_mut()
+                .switch_mirror(cfg.get_mirrors(), cfg.get_root_url());
+
+            if let Some(mirror) = mirror {
+                al_core::log::console_warn(format!(
+                    "The mirror {} of {} is failing, switching to {}",
+                    cfg.get_root_url(),
+                    hips_cdid,
+                    mirror
+                ));
+
+                let queries = self
+                    .queries
+                    .iter_mut()
+                    .map(|PrioritizedTile { query, .. }| query)
+                    .chain(self.base_tile_queries.iter_mut());
+                for query in queries.filter(|query| query.hips_cdid == hips_cdid) {
+                    query.redirect(&mirror);
+                }
+
+                cfg.set_root_url(mirror);
+            }
+        }
+    }
+
     pub fn notify(&mut self, downloader: Rc<RefCell<Downloader>>, dt: Option<DeltaTime>) {
         // notify all the x ms
         let now = Time::now();
@@ -173,9 +268,12 @@ impl TileFetcherQueue {
             }
         }
 
+        // The queries with the highest priority are at the end of the queue
+        sort_by_priority(&mut self.queries, |tile| &tile.priority);
+
         let mut num_fetched_tile = 0;
         while num_fetched_tile < MAX_NUM_TILE_FETCHING && !self.queries.is_empty() {
-            let query = self.queries.pop_back().unwrap_abort();
+            let PrioritizedTile { query, .. } = self.queries.pop().unwrap_abort();
 
             if let Ok(query) = self.check_in_file_list(query) {
                 if downloader.borrow_mut().fetch(query) {
@@ -215,6 +313,7 @@ impl TileFetcherQueue {
             moc_url,
             cfg.get_creator_did().to_string(),
             al_api::moc::MOC::default(),
+            cfg.get_fetch_options(),
         ));
 
         let tile_size = cfg.get_tile_size();
@@ -228,7 +327,10 @@ impl TileFetcherQueue {
                 let hips_cdid = cfg.get_creator_did().to_string();
                 let hips_url = cfg.get_root_url().to_string();
                 let hips_fmt = cfg.get_format();
+                let hips_ext = cfg.get_tile_ext();
                 let min_order = cfg.get_min_depth_texture();
+                let frame = cfg.get_cube_frame();
+                let options = cfg.get_fetch_options().clone();
 
                 for tile_cell in crate::healpix::cell::ALLSKY_HPX_CELLS_D0 {
                     if let Ok(query) = self.check_in_file_list(query::Tile::new(
@@ -236,6 +338,9 @@ impl TileFetcherQueue {
                         hips_cdid.clone(),
                         hips_url.clone(),
                         hips_fmt,
+                        &hips_ext,
+                        frame,
+                        &options,
                     )) {
                         let dl = downloader.clone();
 
@@ -251,3 +356,48 @@ impl TileFetcherQueue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{sort_by_priority, TilePriority, MAX_QUERY_QUEUE_LENGTH};
+
+    fn priority(depth: u8, distance: f64) -> TilePriority {
+        TilePriority { depth, distance }
+    }
+
+    #[test]
+    fn coarsest_then_closest_tiles_first() {
+        let mut queue = vec![
+            priority(4, 0.1),
+            priority(3, 0.5),
+            priority(4, 0.0),
+            priority(3, 0.2),
+        ];
+        sort_by_priority(&mut queue, |p| p);
+
+        // The tiles are popped from the end of the queue
+        let order = queue
+            .iter()
+            .rev()
+            .map(|p| (p.depth, p.distance))
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![(3, 0.2), (3, 0.5), (4, 0.0), (4, 0.1)]);
+    }
+
+    #[test]
+    fn discards_the_lowest_priorities() {
+        let mut queue = (0..(MAX_QUERY_QUEUE_LENGTH + 10))
+            .map(|i| priority(5, i as f64))
+            .collect::<Vec<_>>();
+        queue.push(priority(6, 0.0));
+        sort_by_priority(&mut queue, |p| p);
+
+        assert_eq!(queue.len(), MAX_QUERY_QUEUE_LENGTH);
+        // The deepest tile and the farthest ones are discarded
+        assert!(queue.iter().all(|p| p.depth == 5));
+        assert!(queue
+            .iter()
+            .all(|p| p.distance < MAX_QUERY_QUEUE_LENGTH as f64));
+        assert_eq!(queue.last().map(|p| p.distance), Some(0.0));
+    }
+}
@@ -0,0 +1,238 @@
+//! Decoding of the BINARY and BINARY2 serializations
+//!
+//! In both serializations, the rows are written one after the other
+//! and the cells of a row are written in the order of the fields, big endian.
+//! Variable size arrays are prefixed by their number of primitives, as a 4 bytes
+//! integer. BINARY2 prefixes each row by a bit mask telling which cells are null.
+
+use super::column::{Cell, Column};
+use super::field::{DataType, Field};
+use super::Error;
+
+/// Decode a base64 encoded stream, whitespaces being ignored
+pub fn decode_base64(s: &str) -> Result<Vec<u8>, Error> {
+    fn sextet(c: u8) -> Option<u8> {
+        match c {
+            b'A'..=b'Z' => Some(c - b'A'),
+            b'a'..=b'z' => Some(c - b'a' + 26),
+            b'0'..=b'9' => Some(c - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut out = Vec::with_capacity(s.len() * 3 / 4);
+    let mut acc = 0_u32;
+    let mut num_bits = 0;
+
+    for c in s.bytes() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if c == b'=' {
+            // Padding, the remaining bits are discarded
+            break;
+        }
+
+        let v = sextet(c).ok_or_else(|| Error::Format {
+            message: format!("Invalid base64 character: {:?}", c as char),
+        })?;
+
+        acc = (acc << 6) | (v as u32);
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            out.push((acc >> num_bits) as u8);
+            acc &= (1 << num_bits) - 1;
+        }
+    }
+
+    Ok(out)
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.pos + n > self.bytes.len() {
+            Err(Error::Format {
+                message: "Unexpected end of the binary stream".to_string(),
+            })
+        } else {
+            let b = &self.bytes[self.pos..(self.pos + n)];
+            self.pos += n;
+            Ok(b)
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+}
+
+/// Decode the rows of a binary stream and append them to the columns
+pub(crate) fn read_rows(
+    bytes: &[u8],
+    fields: &[Field],
+    nulls: &[Option<i64>],
+    columns: &mut [Column],
+    binary2: bool,
+) -> Result<(), Error> {
+    let mut cursor = Cursor { bytes, pos: 0 };
+    let num_flag_bytes = fields.len().div_ceil(8);
+
+    while !cursor.is_empty() {
+        let flags = if binary2 {
+            cursor.take(num_flag_bytes)?
+        } else {
+            &[]
+        };
+
+        for (idx, ((field, null), column)) in fields
+            .iter()
+            .zip(nulls.iter())
+            .zip(columns.iter_mut())
+            .enumerate()
+        {
+            let cell = read_cell(field, &mut cursor)?;
+
+            let is_null = binary2 && (flags[idx / 8] & (0x80 >> (idx % 8))) != 0;
+            if is_null {
+                column.push(Cell::Null, *null)?;
+            } else {
+                column.push(cell, *null)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_cell(field: &Field, cursor: &mut Cursor) -> Result<Cell, Error> {
+    let num_primitives = match &field.arraysize {
+        None => 1,
+        Some(arraysize) => {
+            if let Some(len) = arraysize.fixed_len() {
+                len
+            } else {
+                let len = cursor.take(4)?;
+                u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize
+            }
+        }
+    };
+
+    let is_array = field.is_array();
+    let cell = match field.datatype {
+        DataType::Char => {
+            let b = cursor.take(num_primitives)?;
+            // ASCII (latin-1) characters
+            let s = b
+                .iter()
+                .take_while(|c| **c != 0)
+                .map(|c| *c as char)
+                .collect::<String>();
+            Cell::Str(s)
+        }
+        DataType::UnicodeChar => {
+            let b = cursor.take(2 * num_primitives)?;
+            let utf16 = b
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .take_while(|c| *c != 0)
+                .collect::<Vec<_>>();
+            Cell::Str(String::from_utf16_lossy(&utf16))
+        }
+        DataType::Bit => {
+            let b = cursor.take(num_primitives.div_ceil(8))?;
+            let bits = (0..num_primitives)
+                .map(|i| (b[i / 8] & (0x80 >> (i % 8))) != 0)
+                .collect::<Vec<_>>();
+
+            if is_array {
+                Cell::Array(
+                    bits.into_iter()
+                        .map(|b| if b { 1.0 } else { 0.0 })
+                        .collect(),
+                )
+            } else {
+                Cell::Bool(bits[0])
+            }
+        }
+        DataType::Boolean => {
+            let b = cursor.take(num_primitives)?;
+            let mut values = b.iter().map(|c| match c {
+                b'T' | b't' | b'1' => Some(true),
+                b'F' | b'f' | b'0' => Some(false),
+                _ => None,
+            });
+
+            if is_array {
+                Cell::Array(
+                    values
+                        .map(|v| v.map(|b| if b { 1.0 } else { 0.0 }).unwrap_or(f64::NAN))
+                        .collect(),
+                )
+            } else {
+                values
+                    .next()
+                    .flatten()
+                    .map(Cell::Bool)
+                    .unwrap_or(Cell::Null)
+            }
+        }
+        _ => {
+            let num_values = if field.datatype.is_complex() {
+                2 * num_primitives
+            } else {
+                num_primitives
+            };
+            let size = field.datatype.byte_size() / if field.datatype.is_complex() { 2 } else { 1 };
+            let b = cursor.take(num_values * size)?;
+
+            let values = b.chunks_exact(size).map(|c| match field.datatype {
+                DataType::UnsignedByte => c[0] as f64,
+                DataType::Short => i16::from_be_bytes([c[0], c[1]]) as f64,
+                DataType::Int => i32::from_be_bytes([c[0], c[1], c[2], c[3]]) as f64,
+                DataType::Long => {
+                    i64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]) as f64
+                }
+                DataType::Float | DataType::FloatComplex => {
+                    f32::from_be_bytes([c[0], c[1], c[2], c[3]]) as f64
+                }
+                _ => f64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]),
+            });
+
+            if field.is_stored_as_array() {
+                Cell::Array(values.collect())
+            } else {
+                match field.datatype {
+                    // Read the integers apart so that longs do not lose precision
+                    DataType::UnsignedByte => Cell::Int(b[0] as i64),
+                    DataType::Short => Cell::Int(i16::from_be_bytes([b[0], b[1]]) as i64),
+                    DataType::Int => Cell::Int(i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as i64),
+                    DataType::Long => Cell::Int(i64::from_be_bytes([
+                        b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7],
+                    ])),
+                    _ => values.map(Cell::Float).next().unwrap_or(Cell::Null),
+                }
+            }
+        }
+    };
+
+    Ok(cell)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn base64() {
+        assert_eq!(super::decode_base64("TWFu").unwrap(), b"Man");
+        assert_eq!(super::decode_base64("TW\nE=").unwrap(), b"Ma");
+        assert_eq!(super::decode_base64("TQ==").unwrap(), b"M");
+        assert!(super::decode_base64("T*==").is_err());
+    }
+}
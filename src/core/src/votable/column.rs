@@ -0,0 +1,279 @@
+use super::field::{DataType, Field};
+use super::Error;
+
+/// The values of a table column
+///
+/// Null integers, booleans and strings are stored as `None`, null
+/// floating point values are stored as NaN (which is the VOTable convention).
+/// Arrays and complex numbers are stored as arrays of f64.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    Boolean(Vec<Option<bool>>),
+    UnsignedByte(Vec<Option<u8>>),
+    Short(Vec<Option<i16>>),
+    Int(Vec<Option<i32>>),
+    Long(Vec<Option<i64>>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    String(Vec<Option<String>>),
+    Array(Vec<Option<Vec<f64>>>),
+}
+
+/// A decoded cell, before being pushed into its column
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cell {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Array(Vec<f64>),
+}
+
+impl Column {
+    pub(crate) fn new(field: &Field) -> Self {
+        if field.is_stored_as_array() {
+            return Column::Array(vec![]);
+        }
+
+        match field.datatype {
+            DataType::Boolean | DataType::Bit => Column::Boolean(vec![]),
+            DataType::UnsignedByte => Column::UnsignedByte(vec![]),
+            DataType::Short => Column::Short(vec![]),
+            DataType::Int => Column::Int(vec![]),
+            DataType::Long => Column::Long(vec![]),
+            DataType::Float => Column::Float(vec![]),
+            DataType::Double => Column::Double(vec![]),
+            DataType::Char | DataType::UnicodeChar => Column::String(vec![]),
+            DataType::FloatComplex | DataType::DoubleComplex => Column::Array(vec![]),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Boolean(v) => v.len(),
+            Column::UnsignedByte(v) => v.len(),
+            Column::Short(v) => v.len(),
+            Column::Int(v) => v.len(),
+            Column::Long(v) => v.len(),
+            Column::Float(v) => v.len(),
+            Column::Double(v) => v.len(),
+            Column::String(v) => v.len(),
+            Column::Array(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the value of a numerical scalar cell as a f64
+    ///
+    /// Returns `None` for null values, strings and arrays
+    pub fn get_f64(&self, idx: usize) -> Option<f64> {
+        let v = match self {
+            Column::UnsignedByte(v) => v.get(idx).copied().flatten().map(|v| v as f64),
+            Column::Short(v) => v.get(idx).copied().flatten().map(|v| v as f64),
+            Column::Int(v) => v.get(idx).copied().flatten().map(|v| v as f64),
+            Column::Long(v) => v.get(idx).copied().flatten().map(|v| v as f64),
+            Column::Float(v) => v.get(idx).map(|v| *v as f64),
+            Column::Double(v) => v.get(idx).copied(),
+            _ => None,
+        };
+
+        v.filter(|v| !v.is_nan())
+    }
+
+    /// Convert a numerical scalar column into f64 values, nulls being NaN
+    ///
+    /// Returns `None` for boolean, string and array columns
+    pub fn to_f64(&self) -> Option<Vec<f64>> {
+        match self {
+            Column::Boolean(_) | Column::String(_) | Column::Array(_) => None,
+            _ => Some(
+                (0..self.len())
+                    .map(|idx| self.get_f64(idx).unwrap_or(f64::NAN))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub(crate) fn push(&mut self, cell: Cell, null: Option<i64>) -> Result<(), Error> {
+        let is_null_int = |v: i64| null.map(|n| n == v).unwrap_or(false);
+
+        match (self, cell) {
+            (Column::Boolean(v), Cell::Bool(b)) => v.push(Some(b)),
+            (Column::Boolean(v), Cell::Null) => v.push(None),
+
+            (Column::UnsignedByte(v), Cell::Int(i)) => {
+                v.push(Some(i as u8).filter(|_| !is_null_int(i)))
+            }
+            (Column::UnsignedByte(v), Cell::Null) => v.push(None),
+            (Column::Short(v), Cell::Int(i)) => v.push(Some(i as i16).filter(|_| !is_null_int(i))),
+            (Column::Short(v), Cell::Null) => v.push(None),
+            (Column::Int(v), Cell::Int(i)) => v.push(Some(i as i32).filter(|_| !is_null_int(i))),
+            (Column::Int(v), Cell::Null) => v.push(None),
+            (Column::Long(v), Cell::Int(i)) => v.push(Some(i).filter(|_| !is_null_int(i))),
+            (Column::Long(v), Cell::Null) => v.push(None),
+
+            (Column::Float(v), Cell::Float(f)) => v.push(f as f32),
+            (Column::Float(v), Cell::Null) => v.push(f32::NAN),
+            (Column::Double(v), Cell::Float(f)) => v.push(f),
+            (Column::Double(v), Cell::Null) => v.push(f64::NAN),
+
+            (Column::String(v), Cell::Str(s)) => v.push(Some(s).filter(|s| !s.is_empty())),
+            (Column::String(v), Cell::Null) => v.push(None),
+
+            (Column::Array(v), Cell::Array(mut a)) => {
+                if let Some(null) = null {
+                    for x in a.iter_mut() {
+                        if *x == null as f64 {
+                            *x = f64::NAN;
+                        }
+                    }
+                }
+                v.push(Some(a))
+            }
+            (Column::Array(v), Cell::Null) => v.push(None),
+
+            (col, cell) => {
+                return Err(Error::Format {
+                    message: format!(
+                        "Cannot push {:?} into a column of {} values",
+                        cell,
+                        col.type_name()
+                    ),
+                })
+            }
+        }
+
+        Ok(())
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Column::Boolean(_) => "boolean",
+            Column::UnsignedByte(_) => "unsignedByte",
+            Column::Short(_) => "short",
+            Column::Int(_) => "int",
+            Column::Long(_) => "long",
+            Column::Float(_) => "float",
+            Column::Double(_) => "double",
+            Column::String(_) => "string",
+            Column::Array(_) => "array",
+        }
+    }
+}
+
+/// Parse the content of a TABLEDATA <TD> element
+pub(crate) fn parse_text(field: &Field, text: &str) -> Result<Cell, Error> {
+    if field.datatype.is_char() {
+        return Ok(if text.is_empty() {
+            Cell::Null
+        } else {
+            Cell::Str(text.to_string())
+        });
+    }
+
+    let t = text.trim();
+    if t.is_empty() {
+        return Ok(Cell::Null);
+    }
+
+    if field.is_stored_as_array() {
+        let values = if field.datatype == DataType::Bit {
+            t.chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| match c {
+                    '0' => Ok(0.0),
+                    '1' => Ok(1.0),
+                    _ => Err(invalid_value(field, t)),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        } else if field.datatype == DataType::Boolean {
+            t.split_whitespace()
+                .map(|v| {
+                    parse_bool(v)
+                        .map(|b| b.map(|b| if b { 1.0 } else { 0.0 }).unwrap_or(f64::NAN))
+                        .ok_or_else(|| invalid_value(field, t))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            t.split_whitespace()
+                .map(|v| match field.datatype {
+                    DataType::UnsignedByte | DataType::Short | DataType::Int | DataType::Long => {
+                        parse_int(v).map(|i| i as f64)
+                    }
+                    _ => parse_float(v),
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid_value(field, t))?
+        };
+
+        return Ok(Cell::Array(values));
+    }
+
+    let cell = match field.datatype {
+        DataType::Boolean | DataType::Bit => match parse_bool(t) {
+            Some(Some(b)) => Cell::Bool(b),
+            Some(None) => Cell::Null,
+            None => return Err(invalid_value(field, t)),
+        },
+        DataType::UnsignedByte | DataType::Short | DataType::Int | DataType::Long => {
+            Cell::Int(parse_int(t).ok_or_else(|| invalid_value(field, t))?)
+        }
+        _ => Cell::Float(parse_float(t).ok_or_else(|| invalid_value(field, t))?),
+    };
+
+    Ok(cell)
+}
+
+fn invalid_value(field: &Field, text: &str) -> Error {
+    Error::Format {
+        message: format!(
+            "Invalid value {:?} for the {} field {}",
+            text,
+            field.datatype.as_str(),
+            field.name
+        ),
+    }
+}
+
+/// Parse a boolean, `Some(None)` being returned for the null values
+fn parse_bool(t: &str) -> Option<Option<bool>> {
+    match t {
+        "T" | "t" | "1" | "true" | "TRUE" | "True" => Some(Some(true)),
+        "F" | "f" | "0" | "false" | "FALSE" | "False" => Some(Some(false)),
+        "?" => Some(None),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_int(t: &str) -> Option<i64> {
+    let (neg, digits) = if let Some(d) = t.strip_prefix('-') {
+        (true, d)
+    } else {
+        (false, t.strip_prefix('+').unwrap_or(t))
+    };
+
+    let v = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        // Hexadecimal values are the raw bits of the integer
+        u64::from_str_radix(hex, 16).ok()? as i64
+    } else {
+        digits.parse::<i64>().ok()?
+    };
+
+    Some(if neg { -v } else { v })
+}
+
+fn parse_float(t: &str) -> Option<f64> {
+    match t {
+        "NaN" | "nan" | "NAN" => Some(f64::NAN),
+        "Inf" | "+Inf" | "inf" | "+inf" | "Infinity" | "+Infinity" => Some(f64::INFINITY),
+        "-Inf" | "-inf" | "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => t.parse::<f64>().ok(),
+    }
+}
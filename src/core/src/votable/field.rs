@@ -0,0 +1,235 @@
+use super::Error;
+
+/// The VOTable primitive datatypes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Boolean,
+    Bit,
+    UnsignedByte,
+    Short,
+    Int,
+    Long,
+    Char,
+    UnicodeChar,
+    Float,
+    Double,
+    FloatComplex,
+    DoubleComplex,
+}
+
+impl DataType {
+    /// Size in bytes of one primitive in the BINARY serializations
+    ///
+    /// Bits are packed so that case is handled apart
+    pub fn byte_size(&self) -> usize {
+        match self {
+            DataType::Boolean | DataType::Bit | DataType::UnsignedByte | DataType::Char => 1,
+            DataType::Short | DataType::UnicodeChar => 2,
+            DataType::Int | DataType::Float => 4,
+            DataType::Long | DataType::Double | DataType::FloatComplex => 8,
+            DataType::DoubleComplex => 16,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataType::Boolean => "boolean",
+            DataType::Bit => "bit",
+            DataType::UnsignedByte => "unsignedByte",
+            DataType::Short => "short",
+            DataType::Int => "int",
+            DataType::Long => "long",
+            DataType::Char => "char",
+            DataType::UnicodeChar => "unicodeChar",
+            DataType::Float => "float",
+            DataType::Double => "double",
+            DataType::FloatComplex => "floatComplex",
+            DataType::DoubleComplex => "doubleComplex",
+        }
+    }
+
+    pub fn is_char(&self) -> bool {
+        matches!(self, DataType::Char | DataType::UnicodeChar)
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self, DataType::FloatComplex | DataType::DoubleComplex)
+    }
+}
+
+impl std::str::FromStr for DataType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let t = match s {
+            "boolean" => DataType::Boolean,
+            "bit" => DataType::Bit,
+            "unsignedByte" => DataType::UnsignedByte,
+            "short" => DataType::Short,
+            "int" => DataType::Int,
+            "long" => DataType::Long,
+            "char" => DataType::Char,
+            "unicodeChar" => DataType::UnicodeChar,
+            "float" => DataType::Float,
+            "double" => DataType::Double,
+            "floatComplex" => DataType::FloatComplex,
+            "doubleComplex" => DataType::DoubleComplex,
+            _ => {
+                return Err(Error::Format {
+                    message: format!("Unknown datatype: {}", s),
+                })
+            }
+        };
+
+        Ok(t)
+    }
+}
+
+/// The arraysize attribute of a field, e.g. "*", "8", "3x2", "10*"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArraySize {
+    /// The dimensions as given in the arraysize attribute
+    pub dims: Vec<usize>,
+    /// Whether the last dimension is variable, i.e. the size is given
+    /// as an upper bound (or not given at all with "*")
+    pub variable: bool,
+}
+
+impl ArraySize {
+    /// Number of primitives for fixed size arrays
+    pub fn fixed_len(&self) -> Option<usize> {
+        if self.variable {
+            None
+        } else {
+            Some(self.dims.iter().product())
+        }
+    }
+}
+
+impl std::str::FromStr for ArraySize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut dims = vec![];
+        let mut variable = false;
+
+        for dim in s.trim().split('x') {
+            if variable {
+                // Only the last dimension can be variable
+                return Err(Error::Format {
+                    message: format!("Invalid arraysize: {}", s),
+                });
+            }
+
+            let d = if let Some(d) = dim.strip_suffix('*') {
+                variable = true;
+                d
+            } else {
+                dim
+            };
+
+            if d.is_empty() {
+                if !variable {
+                    return Err(Error::Format {
+                        message: format!("Invalid arraysize: {}", s),
+                    });
+                }
+            } else {
+                let d = d.parse::<usize>().map_err(|_| Error::Format {
+                    message: format!("Invalid arraysize: {}", s),
+                })?;
+                dims.push(d);
+            }
+        }
+
+        Ok(ArraySize { dims, variable })
+    }
+}
+
+impl std::fmt::Display for ArraySize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let dims = self
+            .dims
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join("x");
+        if self.variable {
+            if dims.is_empty() {
+                write!(f, "*")
+            } else {
+                write!(f, "{}*", dims)
+            }
+        } else {
+            write!(f, "{}", dims)
+        }
+    }
+}
+
+/// The metadata of a table column
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub id: Option<String>,
+    pub datatype: DataType,
+    /// `None` if the field holds a scalar
+    pub arraysize: Option<ArraySize>,
+    pub ucd: Option<String>,
+    pub unit: Option<String>,
+    pub utype: Option<String>,
+    pub width: Option<u32>,
+    pub precision: Option<String>,
+    pub description: Option<String>,
+    /// The null value given by the VALUES child element
+    pub null: Option<String>,
+}
+
+impl Field {
+    /// Whether a cell of this field contains several primitives
+    ///
+    /// Strings are considered as scalars
+    pub fn is_array(&self) -> bool {
+        match &self.arraysize {
+            None => false,
+            Some(arraysize) => {
+                if self.datatype.is_char() {
+                    // char arrays are strings, 2 dimensional arrays of
+                    // chars are considered as arrays of strings but are
+                    // flattened into one string
+                    false
+                } else {
+                    arraysize.variable || arraysize.dims.iter().product::<usize>() != 1
+                }
+            }
+        }
+    }
+
+    /// Whether the values of this field are stored in a `Column::Array`,
+    /// i.e. for arrays and complex numbers
+    pub fn is_stored_as_array(&self) -> bool {
+        self.is_array() || self.datatype.is_complex()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArraySize;
+
+    #[test]
+    fn parse_arraysize() {
+        let a: ArraySize = "*".parse().unwrap();
+        assert_eq!(a.dims, Vec::<usize>::new());
+        assert!(a.variable);
+        assert_eq!(a.to_string(), "*");
+
+        let a: ArraySize = "3x2".parse().unwrap();
+        assert_eq!(a.fixed_len(), Some(6));
+
+        let a: ArraySize = "3x10*".parse().unwrap();
+        assert_eq!(a.dims, vec![3, 10]);
+        assert!(a.variable);
+        assert_eq!(a.to_string(), "3x10*");
+
+        assert!("3*x2".parse::<ArraySize>().is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Conversion of the parsed VOTable into javascript objects
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use super::{Column, Error, Field, Table, VOTable};
+
+impl From<Error> for JsValue {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Xml { message } | Error::Format { message } | Error::Unsupported { message } => {
+                JsValue::from_str(&format!("Error parsing votable: {}", message))
+            }
+        }
+    }
+}
+
+fn set(obj: &Object, key: &str, value: &JsValue) -> Result<(), JsValue> {
+    Reflect::set(obj, &key.into(), value)?;
+    Ok(())
+}
+
+fn opt_str(s: &Option<String>) -> JsValue {
+    s.as_deref().map(JsValue::from_str).unwrap_or(JsValue::NULL)
+}
+
+fn opt_num<T: Into<f64> + Copy>(v: Option<T>) -> JsValue {
+    v.map(|v| JsValue::from_f64(v.into()))
+        .unwrap_or(JsValue::NULL)
+}
+
+impl VOTable {
+    /// Convert the votable into a javascript object of the form
+    /// `{tables: [{name, ID, description, fields: [...], columns: [...]}]}`
+    ///
+    /// Float columns are given as typed arrays, integer columns too if they do not
+    /// contain any null values. Other columns are arrays, null cells being `null`.
+    pub fn to_js(&self) -> Result<JsValue, JsValue> {
+        let tables = self
+            .tables
+            .iter()
+            .map(|t| t.to_js())
+            .collect::<Result<Array, _>>()?;
+
+        let votable = Object::new();
+        set(&votable, "tables", &tables)?;
+
+        Ok(votable.into())
+    }
+}
+
+impl Table {
+    fn to_js(&self) -> Result<JsValue, JsValue> {
+        let fields = self
+            .fields
+            .iter()
+            .map(|f| f.to_js())
+            .collect::<Result<Array, _>>()?;
+        let columns = self.columns.iter().map(|c| c.to_js()).collect::<Array>();
+
+        let table = Object::new();
+        set(&table, "name", &opt_str(&self.name))?;
+        set(&table, "ID", &opt_str(&self.id))?;
+        set(&table, "description", &opt_str(&self.description))?;
+        set(&table, "fields", &fields)?;
+        set(&table, "columns", &columns)?;
+
+        Ok(table.into())
+    }
+}
+
+impl Field {
+    fn to_js(&self) -> Result<JsValue, JsValue> {
+        let field = Object::new();
+        set(&field, "name", &JsValue::from_str(&self.name))?;
+        set(&field, "ID", &opt_str(&self.id))?;
+        set(
+            &field,
+            "datatype",
+            &JsValue::from_str(self.datatype.as_str()),
+        )?;
+        set(
+            &field,
+            "arraysize",
+            &opt_str(&self.arraysize.as_ref().map(|a| a.to_string())),
+        )?;
+        set(&field, "ucd", &opt_str(&self.ucd))?;
+        set(&field, "unit", &opt_str(&self.unit))?;
+        set(&field, "utype", &opt_str(&self.utype))?;
+        set(&field, "width", &opt_num(self.width))?;
+        set(&field, "precision", &opt_str(&self.precision))?;
+        set(&field, "description", &opt_str(&self.description))?;
+
+        Ok(field.into())
+    }
+}
+
+impl Column {
+    fn to_js(&self) -> JsValue {
+        match self {
+            Column::Float(v) => js_sys::Float32Array::from(&v[..]).into(),
+            Column::Double(v) => js_sys::Float64Array::from(&v[..]).into(),
+            Column::UnsignedByte(v) => {
+                if let Some(v) = v.iter().copied().collect::<Option<Vec<_>>>() {
+                    js_sys::Uint8Array::from(&v[..]).into()
+                } else {
+                    v.iter().map(|v| opt_num(*v)).collect::<Array>().into()
+                }
+            }
+            Column::Short(v) => {
+                if let Some(v) = v.iter().copied().collect::<Option<Vec<_>>>() {
+                    js_sys::Int16Array::from(&v[..]).into()
+                } else {
+                    v.iter().map(|v| opt_num(*v)).collect::<Array>().into()
+                }
+            }
+            Column::Int(v) => {
+                if let Some(v) = v.iter().copied().collect::<Option<Vec<_>>>() {
+                    js_sys::Int32Array::from(&v[..]).into()
+                } else {
+                    v.iter().map(|v| opt_num(*v)).collect::<Array>().into()
+                }
+            }
+            // Javascript numbers are f64
+            Column::Long(v) => v
+                .iter()
+                .map(|v| opt_num(v.map(|v| v as f64)))
+                .collect::<Array>()
+                .into(),
+            Column::Boolean(v) => v
+                .iter()
+                .map(|v| v.map(JsValue::from_bool).unwrap_or(JsValue::NULL))
+                .collect::<Array>()
+                .into(),
+            Column::String(v) => v.iter().map(opt_str).collect::<Array>().into(),
+            Column::Array(v) => v
+                .iter()
+                .map(|v| {
+                    v.as_ref()
+                        .map(|v| js_sys::Float64Array::from(&v[..]).into())
+                        .unwrap_or(JsValue::NULL)
+                })
+                .collect::<Array>()
+                .into(),
+        }
+    }
+}
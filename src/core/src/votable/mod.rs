@@ -0,0 +1,486 @@
+//! VOTable parser
+//!
+//! Supports the TABLEDATA, BINARY and BINARY2 serializations
+//! (see <https://www.ivoa.net/documents/VOTable/>).
+//! BINARY streams must be embedded in the document and base64 encoded.
+//! The FITS serialization is not supported.
+
+pub mod binary;
+pub mod column;
+pub mod field;
+mod js;
+mod xml;
+
+pub use column::Column;
+pub use field::{ArraySize, DataType, Field};
+
+use column::Cell;
+use xml::{Event, Reader};
+
+#[derive(Debug)]
+pub enum Error {
+    Xml { message: String },
+    Format { message: String },
+    Unsupported { message: String },
+}
+
+/// A table of a VOTable document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub name: Option<String>,
+    pub id: Option<String>,
+    pub description: Option<String>,
+    pub fields: Vec<Field>,
+    /// One column per field
+    pub columns: Vec<Column>,
+}
+
+impl Table {
+    pub fn num_rows(&self) -> usize {
+        self.columns.first().map(|c| c.len()).unwrap_or(0)
+    }
+
+    /// Get a column from its name or its ID
+    pub fn column(&self, name: &str) -> Option<(&Field, &Column)> {
+        self.fields
+            .iter()
+            .zip(self.columns.iter())
+            .find(|(f, _)| f.name == name || f.id.as_deref() == Some(name))
+    }
+
+    /// Get the first column having a specific UCD
+    pub fn column_by_ucd(&self, ucd: &str) -> Option<(&Field, &Column)> {
+        self.fields
+            .iter()
+            .zip(self.columns.iter())
+            .find(|(f, _)| f.ucd.as_deref() == Some(ucd))
+    }
+}
+
+/// The tables contained in a VOTable document, whatever the RESOURCE they belong to
+#[derive(Debug, Clone, PartialEq)]
+pub struct VOTable {
+    pub tables: Vec<Table>,
+}
+
+impl VOTable {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let mut reader = Reader::new(s);
+        let mut tables = vec![];
+
+        loop {
+            match reader.next_event()? {
+                Event::Start {
+                    name: "TABLE",
+                    attrs,
+                    empty,
+                } => {
+                    let mut table = Table {
+                        name: xml::attr(&attrs, "name").map(|s| s.to_string()),
+                        id: xml::attr(&attrs, "ID").map(|s| s.to_string()),
+                        description: None,
+                        fields: vec![],
+                        columns: vec![],
+                    };
+
+                    if !empty {
+                        parse_table(&mut reader, &mut table)?;
+                    }
+
+                    tables.push(table);
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+
+        Ok(VOTable { tables })
+    }
+}
+
+fn parse_table(reader: &mut Reader, table: &mut Table) -> Result<(), Error> {
+    loop {
+        match reader.next_event()? {
+            Event::Start { name, attrs, empty } => match name {
+                "FIELD" => {
+                    let field = parse_field(reader, &attrs, empty)?;
+                    table.fields.push(field);
+                }
+                "DESCRIPTION" if !empty => {
+                    table.description = Some(reader.read_text(name)?.trim().to_string());
+                }
+                "DATA" if !empty => {
+                    table.columns = table.fields.iter().map(Column::new).collect();
+                    parse_data(reader, &table.fields, &mut table.columns)?;
+                }
+                _ => {
+                    if !empty {
+                        reader.skip(name)?;
+                    }
+                }
+            },
+            Event::End { name: "TABLE" } => break,
+            Event::End { name } => return Err(xml::mismatched_tag("TABLE", name)),
+            Event::Text(_) => (),
+            Event::Eof => {
+                return Err(Error::Xml {
+                    message: "Unexpected end of document in <TABLE>".to_string(),
+                })
+            }
+        }
+    }
+
+    // A table without data
+    if table.columns.len() != table.fields.len() {
+        table.columns = table.fields.iter().map(Column::new).collect();
+    }
+
+    Ok(())
+}
+
+fn parse_field(reader: &mut Reader, attrs: &[(&str, String)], empty: bool) -> Result<Field, Error> {
+    let get = |key: &str| xml::attr(attrs, key).map(|s| s.to_string());
+
+    let id = get("ID");
+    let name = get("name")
+        .or_else(|| id.clone())
+        .ok_or_else(|| Error::Format {
+            message: "A FIELD has no name".to_string(),
+        })?;
+    let datatype = xml::attr(attrs, "datatype")
+        .ok_or_else(|| Error::Format {
+            message: format!("The FIELD {} has no datatype", name),
+        })?
+        .parse::<DataType>()?;
+    let arraysize = xml::attr(attrs, "arraysize")
+        .map(|a| a.parse::<ArraySize>())
+        .transpose()?;
+    let width = xml::attr(attrs, "width").and_then(|w| w.trim().parse::<u32>().ok());
+
+    let mut field = Field {
+        name,
+        id,
+        datatype,
+        arraysize,
+        ucd: get("ucd"),
+        unit: get("unit"),
+        utype: get("utype"),
+        width,
+        precision: get("precision"),
+        description: None,
+        null: None,
+    };
+
+    if !empty {
+        loop {
+            match reader.next_event()? {
+                Event::Start {
+                    name: "DESCRIPTION",
+                    empty: false,
+                    ..
+                } => {
+                    field.description = Some(reader.read_text("DESCRIPTION")?.trim().to_string());
+                }
+                Event::Start {
+                    name: "VALUES",
+                    attrs,
+                    empty,
+                } => {
+                    field.null = xml::attr(&attrs, "null").map(|s| s.to_string());
+                    if !empty {
+                        reader.skip("VALUES")?;
+                    }
+                }
+                Event::Start {
+                    name, empty: false, ..
+                } => reader.skip(name)?,
+                Event::End { name: "FIELD" } => break,
+                Event::End { name } => return Err(xml::mismatched_tag("FIELD", name)),
+                Event::Eof => {
+                    return Err(Error::Xml {
+                        message: "Unexpected end of document in <FIELD>".to_string(),
+                    })
+                }
+                _ => (),
+            }
+        }
+    }
+
+    Ok(field)
+}
+
+fn parse_data(reader: &mut Reader, fields: &[Field], columns: &mut [Column]) -> Result<(), Error> {
+    let nulls = fields
+        .iter()
+        .map(|f| f.null.as_deref().and_then(column::parse_int))
+        .collect::<Vec<_>>();
+
+    loop {
+        match reader.next_event()? {
+            Event::Start { name, empty, .. } => match name {
+                "TABLEDATA" if !empty => parse_tabledata(reader, fields, &nulls, columns)?,
+                "BINARY" | "BINARY2" if !empty => {
+                    parse_binary(reader, name, fields, &nulls, columns)?;
+                }
+                "FITS" => {
+                    return Err(Error::Unsupported {
+                        message: "The FITS serialization of VOTable is not supported".to_string(),
+                    })
+                }
+                _ => {
+                    if !empty {
+                        reader.skip(name)?;
+                    }
+                }
+            },
+            Event::End { name: "DATA" } => return Ok(()),
+            Event::End { name } => return Err(xml::mismatched_tag("DATA", name)),
+            Event::Text(_) => (),
+            Event::Eof => {
+                return Err(Error::Xml {
+                    message: "Unexpected end of document in <DATA>".to_string(),
+                })
+            }
+        }
+    }
+}
+
+fn parse_tabledata(
+    reader: &mut Reader,
+    fields: &[Field],
+    nulls: &[Option<i64>],
+    columns: &mut [Column],
+) -> Result<(), Error> {
+    loop {
+        match reader.next_event()? {
+            Event::Start {
+                name: "TR", empty, ..
+            } => {
+                let mut idx = 0;
+                if !empty {
+                    loop {
+                        match reader.next_event()? {
+                            Event::Start {
+                                name: "TD", empty, ..
+                            } => {
+                                let field = fields.get(idx).ok_or_else(|| Error::Format {
+                                    message: format!(
+                                        "A row has more cells than the {} fields",
+                                        fields.len()
+                                    ),
+                                })?;
+
+                                let cell = if empty {
+                                    Cell::Null
+                                } else {
+                                    let text = reader.read_text("TD")?;
+                                    column::parse_text(field, &text)?
+                                };
+
+                                columns[idx].push(cell, nulls[idx])?;
+                                idx += 1;
+                            }
+                            Event::End { name: "TR" } => break,
+                            Event::End { name } => return Err(xml::mismatched_tag("TR", name)),
+                            Event::Text(_) => (),
+                            e => {
+                                return Err(Error::Xml {
+                                    message: format!("Unexpected {:?} in <TR>", e),
+                                })
+                            }
+                        }
+                    }
+                }
+
+                // Missing cells are null
+                for (column, null) in columns[idx..].iter_mut().zip(nulls[idx..].iter()) {
+                    column.push(Cell::Null, *null)?;
+                }
+            }
+            Event::End { name: "TABLEDATA" } => return Ok(()),
+            Event::End { name } => return Err(xml::mismatched_tag("TABLEDATA", name)),
+            Event::Text(_) => (),
+            e => {
+                return Err(Error::Xml {
+                    message: format!("Unexpected {:?} in <TABLEDATA>", e),
+                })
+            }
+        }
+    }
+}
+
+fn parse_binary(
+    reader: &mut Reader,
+    serialization: &str,
+    fields: &[Field],
+    nulls: &[Option<i64>],
+    columns: &mut [Column],
+) -> Result<(), Error> {
+    loop {
+        match reader.next_event()? {
+            Event::Start {
+                name: "STREAM",
+                attrs,
+                empty,
+            } => {
+                if xml::attr(&attrs, "href").is_some() {
+                    return Err(Error::Unsupported {
+                        message: "Remote binary streams are not supported".to_string(),
+                    });
+                }
+
+                let encoding = xml::attr(&attrs, "encoding").unwrap_or("base64");
+                if encoding != "base64" {
+                    return Err(Error::Unsupported {
+                        message: format!("Binary stream encoding {} is not supported", encoding),
+                    });
+                }
+
+                if !empty {
+                    let text = reader.read_text("STREAM")?;
+                    let bytes = binary::decode_base64(&text)?;
+
+                    binary::read_rows(&bytes, fields, nulls, columns, serialization == "BINARY2")?;
+                }
+            }
+            Event::End { name } if name == serialization => return Ok(()),
+            Event::End { name } => return Err(xml::mismatched_tag(serialization, name)),
+            Event::Text(_) => (),
+            e => {
+                return Err(Error::Xml {
+                    message: format!("Unexpected {:?} in <{}>", e, serialization),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Column, DataType, VOTable};
+
+    const TABLEDATA: &str = include_str!("../../tests/data/votable/tabledata.vot");
+    const BINARY: &str = include_str!("../../tests/data/votable/binary.vot");
+    const BINARY2: &str = include_str!("../../tests/data/votable/binary2.vot");
+
+    #[test]
+    fn parse_tabledata() {
+        let votable = VOTable::parse(TABLEDATA).unwrap();
+        assert_eq!(votable.tables.len(), 1);
+
+        let table = &votable.tables[0];
+        assert_eq!(table.name.as_deref(), Some("sources"));
+        assert_eq!(table.num_rows(), 3);
+
+        let (ra, ra_values) = table.column_by_ucd("pos.eq.ra;meta.main").unwrap();
+        assert_eq!(ra.name, "RAJ2000");
+        assert_eq!(ra.unit.as_deref(), Some("deg"));
+        assert_eq!(ra.datatype, DataType::Double);
+        assert_eq!(ra_values, &Column::Double(vec![10.5, 83.63308, 201.365]));
+
+        let (_, mag) = table.column("Vmag").unwrap();
+        match mag {
+            Column::Float(v) => {
+                assert_eq!(v[0], 12.25);
+                assert!(v[1].is_nan());
+                assert!(v[2].is_nan());
+            }
+            _ => panic!("Vmag must be a float column"),
+        }
+
+        let (field, names) = table.column("name").unwrap();
+        assert_eq!(field.arraysize.as_ref().unwrap().to_string(), "*");
+        assert_eq!(
+            names,
+            &Column::String(vec![
+                Some("M 31".to_string()),
+                Some("Crab <SNR>".to_string()),
+                None
+            ])
+        );
+
+        // VALUES null="-1"
+        let (_, n) = table.column("nobs").unwrap();
+        assert_eq!(n, &Column::Int(vec![Some(4), None, Some(0x10)]));
+
+        let (_, flags) = table.column("flags").unwrap();
+        assert_eq!(
+            flags,
+            &Column::Array(vec![
+                Some(vec![1.0, 0.0, 1.0]),
+                Some(vec![0.0, 0.0, 0.0]),
+                None
+            ])
+        );
+
+        let (_, var) = table.column("variable").unwrap();
+        assert_eq!(var, &Column::Boolean(vec![Some(true), Some(false), None]));
+    }
+
+    #[test]
+    fn parse_binary() {
+        let votable = VOTable::parse(BINARY).unwrap();
+        let table = &votable.tables[0];
+        assert_eq!(table.num_rows(), 2);
+
+        assert_eq!(
+            table.column("id").unwrap().1,
+            &Column::Long(vec![Some(1), None])
+        );
+        assert_eq!(
+            table.column("ra").unwrap().1,
+            &Column::Double(vec![10.5, 83.63308])
+        );
+        assert_eq!(table.column("mag").unwrap().1.get_f64(0), Some(12.25));
+        assert_eq!(
+            table.column("name").unwrap().1,
+            &Column::String(vec![Some("M 31".to_string()), Some("Crab".to_string())])
+        );
+        assert_eq!(
+            table.column("pm").unwrap().1,
+            &Column::Array(vec![Some(vec![1.5, -2.0]), Some(vec![])])
+        );
+        assert_eq!(
+            table.column("flag").unwrap().1,
+            &Column::Boolean(vec![Some(true), None])
+        );
+    }
+
+    #[test]
+    fn parse_binary2() {
+        let votable = VOTable::parse(BINARY2).unwrap();
+        let table = &votable.tables[0];
+        assert_eq!(table.num_rows(), 2);
+
+        assert_eq!(
+            table.column("id").unwrap().1,
+            &Column::Long(vec![Some(1), None])
+        );
+        let ra = table.column("ra").unwrap().1.to_f64().unwrap();
+        assert_eq!(ra[0], 10.5);
+        assert!(ra[1].is_nan());
+        assert_eq!(
+            table.column("name").unwrap().1,
+            &Column::String(vec![None, Some("Crab".to_string())])
+        );
+    }
+
+    #[test]
+    fn parse_examples() {
+        // VOTables shipped with the examples
+        let votable = VOTable::parse(include_str!(
+            "../../../../examples/data/votable/IMCCE.with_namespace.vot"
+        ))
+        .unwrap();
+        assert!(!votable.tables.is_empty());
+        let table = &votable.tables[0];
+        assert!(table.num_rows() > 0);
+        assert!(table.columns.iter().all(|c| c.len() == table.num_rows()));
+
+        let votable =
+            VOTable::parse(include_str!("../../../../examples/data/votable/labels.vot")).unwrap();
+        let table = &votable.tables[0];
+        assert!(table.num_rows() > 0);
+        assert!(table.column("main_id").is_some());
+    }
+}
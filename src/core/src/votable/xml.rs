@@ -0,0 +1,314 @@
+//! A minimal pull XML tokenizer
+//!
+//! It only handles what is needed for parsing VOTable documents:
+//! elements, attributes, text and CDATA sections. Comments, processing
+//! instructions and DOCTYPE declarations are skipped. Namespace prefixes
+//! are stripped from element names.
+
+use super::Error;
+
+#[derive(Debug, PartialEq)]
+pub enum Event<'a> {
+    Start {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+        // Whether the tag is of the form <TAG/>
+        empty: bool,
+    },
+    End {
+        name: &'a str,
+    },
+    Text(String),
+    Eof,
+}
+
+pub struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    pub fn next_event(&mut self) -> Result<Event<'a>, Error> {
+        loop {
+            let rest = &self.src[self.pos..];
+            if rest.is_empty() {
+                return Ok(Event::Eof);
+            }
+
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                let text = &rest[..end];
+                self.pos += end;
+
+                return Ok(Event::Text(unescape(text)?));
+            }
+
+            if rest.starts_with("<!--") {
+                self.skip_after("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                let start = self.pos + "<![CDATA[".len();
+                let end = self.src[start..]
+                    .find("]]>")
+                    .ok_or_else(|| unexpected_eof("CDATA section"))?;
+                self.pos = start + end + "]]>".len();
+
+                return Ok(Event::Text(self.src[start..(start + end)].to_string()));
+            } else if rest.starts_with("<?") {
+                self.skip_after("?>")?;
+            } else if rest.starts_with("<!") {
+                // DOCTYPE declaration
+                self.skip_after(">")?;
+            } else if rest.starts_with("</") {
+                let end = rest.find('>').ok_or_else(|| unexpected_eof("end tag"))?;
+                let name = local_name(rest[2..end].trim());
+                self.pos += end + 1;
+
+                return Ok(Event::End { name });
+            } else {
+                return self.read_start_tag();
+            }
+        }
+    }
+
+    /// Read all the text until the end tag of the current element.
+    ///
+    /// Nested elements are not allowed.
+    pub fn read_text(&mut self, name: &str) -> Result<String, Error> {
+        let mut text = String::new();
+        loop {
+            match self.next_event()? {
+                Event::Text(t) => text.push_str(&t),
+                Event::End { name: n } if n == name => return Ok(text),
+                Event::Eof => return Err(unexpected_eof(name)),
+                e => {
+                    return Err(Error::Xml {
+                        message: format!("Unexpected {:?} in the <{}> element", e, name),
+                    })
+                }
+            }
+        }
+    }
+
+    /// Skip all the content of the current element, nested elements included
+    pub fn skip(&mut self, name: &str) -> Result<(), Error> {
+        let mut depth = 0;
+        loop {
+            match self.next_event()? {
+                Event::Start { empty: false, .. } => depth += 1,
+                Event::End { name: n } => {
+                    if depth == 0 {
+                        if n == name {
+                            return Ok(());
+                        } else {
+                            return Err(mismatched_tag(name, n));
+                        }
+                    }
+                    depth -= 1;
+                }
+                Event::Eof => return Err(unexpected_eof(name)),
+                _ => (),
+            }
+        }
+    }
+
+    fn skip_after(&mut self, pat: &str) -> Result<(), Error> {
+        let end = self.src[self.pos..]
+            .find(pat)
+            .ok_or_else(|| unexpected_eof(pat))?;
+        self.pos += end + pat.len();
+
+        Ok(())
+    }
+
+    fn read_start_tag(&mut self) -> Result<Event<'a>, Error> {
+        let src = self.src;
+        let bytes = src.as_bytes();
+        // Skip the '<'
+        let mut i = self.pos + 1;
+
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !matches!(bytes[i], b'>' | b'/')
+        {
+            i += 1;
+        }
+        let name = local_name(&src[name_start..i]);
+
+        let mut attrs = vec![];
+        loop {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+
+            match bytes.get(i) {
+                None => return Err(unexpected_eof(name)),
+                Some(b'>') => {
+                    self.pos = i + 1;
+                    return Ok(Event::Start {
+                        name,
+                        attrs,
+                        empty: false,
+                    });
+                }
+                Some(b'/') => {
+                    if bytes.get(i + 1) != Some(&b'>') {
+                        return Err(Error::Xml {
+                            message: format!("Malformed <{}> tag", name),
+                        });
+                    }
+                    self.pos = i + 2;
+                    return Ok(Event::Start {
+                        name,
+                        attrs,
+                        empty: true,
+                    });
+                }
+                Some(_) => {
+                    // Attribute name
+                    let key_start = i;
+                    while i < bytes.len() && bytes[i] != b'=' && !bytes[i].is_ascii_whitespace() {
+                        i += 1;
+                    }
+                    let key = &src[key_start..i];
+
+                    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                        i += 1;
+                    }
+                    if bytes.get(i) != Some(&b'=') {
+                        return Err(Error::Xml {
+                            message: format!("Attribute {} of <{}> has no value", key, name),
+                        });
+                    }
+                    i += 1;
+                    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                        i += 1;
+                    }
+
+                    // Attribute value
+                    let quote = match bytes.get(i) {
+                        Some(q @ b'"') | Some(q @ b'\'') => *q,
+                        _ => {
+                            return Err(Error::Xml {
+                                message: format!("Attribute {} of <{}> is not quoted", key, name),
+                            })
+                        }
+                    };
+                    i += 1;
+                    let value_start = i;
+                    while i < bytes.len() && bytes[i] != quote {
+                        i += 1;
+                    }
+                    if i == bytes.len() {
+                        return Err(unexpected_eof(name));
+                    }
+                    let value = unescape(&src[value_start..i])?;
+                    i += 1;
+
+                    attrs.push((local_name(key), value));
+                }
+            }
+        }
+    }
+}
+
+/// Get an attribute value from its name
+pub fn attr<'b>(attrs: &'b [(&str, String)], key: &str) -> Option<&'b str> {
+    attrs
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn unexpected_eof(ctx: &str) -> Error {
+    Error::Xml {
+        message: format!("Unexpected end of document while reading {}", ctx),
+    }
+}
+
+pub fn mismatched_tag(expected: &str, found: &str) -> Error {
+    Error::Xml {
+        message: format!("Expected </{}>, found </{}>", expected, found),
+    }
+}
+
+fn unescape(s: &str) -> Result<String, Error> {
+    if !s.contains('&') {
+        return Ok(s.to_string());
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let end = rest.find(';').ok_or_else(|| Error::Xml {
+            message: format!("Unterminated entity in {}", s),
+        })?;
+        let entity = &rest[1..end];
+        match entity {
+            "lt" => out.push('<'),
+            "gt" => out.push('>'),
+            "amp" => out.push('&'),
+            "quot" => out.push('"'),
+            "apos" => out.push('\''),
+            _ => {
+                let c = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(dec) = entity.strip_prefix('#') {
+                    dec.parse::<u32>().ok()
+                } else {
+                    None
+                }
+                .and_then(std::char::from_u32)
+                .ok_or_else(|| Error::Xml {
+                    message: format!("Unknown entity &{};", entity),
+                })?;
+
+                out.push(c);
+            }
+        }
+        rest = &rest[(end + 1)..];
+    }
+    out.push_str(rest);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, Reader};
+
+    #[test]
+    fn tokenize() {
+        let mut reader = Reader::new(
+            r#"<?xml version="1.0"?><!-- c --><vot:TD a='1 &lt; 2'>x &amp; <![CDATA[<y>]]></vot:TD><TD/>"#,
+        );
+
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Event::Start {
+                name: "TD",
+                attrs: vec![("a", "1 < 2".to_string())],
+                empty: false
+            }
+        );
+        assert_eq!(reader.read_text("TD").unwrap(), "x & <y>");
+        assert_eq!(
+            reader.next_event().unwrap(),
+            Event::Start {
+                name: "TD",
+                attrs: vec![],
+                empty: true
+            }
+        );
+        assert_eq!(reader.next_event().unwrap(), Event::Eof);
+    }
+}
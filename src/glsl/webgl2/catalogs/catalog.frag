@@ -7,10 +7,16 @@ in vec3 out_p;
 out vec4 color;
 
 uniform sampler2D kernel_texture;
-uniform float max_density; // max number of sources in a kernel sized HEALPix cell at the current depth
 uniform float fov;
 uniform float strength;
+uniform highp int u_proj;
+
 void main() {
+    // TAN, SIN and NCP only project the front hemisphere
+    if (out_p.z < 0.f && (u_proj == 0 || u_proj == 2 || u_proj == 11)) {
+        discard;
+    }
+
     color = texture(kernel_texture, out_uv) / max(log2(fov*100.0), 1.0);
     color.r *= strength;
-}
\ No newline at end of file
+}
@@ -0,0 +1,29 @@
+#version 300 es
+precision highp float;
+layout (location = 0) in vec2 offset;
+layout (location = 1) in vec2 uv;
+layout (location = 2) in vec2 center;
+
+uniform mat4 u_2world;
+uniform vec2 ndc_to_clip;
+uniform float czf;
+uniform vec2 kernel_size;
+
+out vec2 out_uv;
+out vec3 out_p;
+
+#include ../projection/projection.glsl;
+
+void main() {
+    // 1. Convert the (lon, lat) ICRS center of the source into (x, y, z) space coo.
+    vec3 p_xyz = lonlat2xyz(center);
+    // 2. Convert to the world coo system
+    vec3 p = vec3(u_2world * vec4(p_xyz, 1.0));
+    // 3. Process the projection
+    vec2 center_pos_clip_space = proj(p);
+
+    gl_Position = vec4((center_pos_clip_space / (ndc_to_clip * czf)) + offset * kernel_size, 0.f, 1.f);
+
+    out_uv = uv;
+    out_p = p;
+}
@@ -0,0 +1,77 @@
+#version 300 es
+precision highp float;
+precision lowp sampler2D;
+
+in vec2 out_uv;
+in float out_size;
+in float out_value;
+
+out vec4 color;
+
+uniform int u_shape;
+uniform vec3 u_color;
+uniform float u_opacity;
+uniform bool u_colormapped;
+
+#include ../colormaps/colormap.glsl;
+
+const float SQRT_2 = 1.41421356237309504880168872420969808;
+const float SQRT_3 = 1.73205080756887729352744634150587236;
+
+// Signed distance to the border of an equilateral triangle pointing upward
+float sd_triangle(vec2 p) {
+    p.x = abs(p.x) - 1.0;
+    p.y = p.y + 1.0/SQRT_3;
+    if (p.x + SQRT_3*p.y > 0.0) {
+        p = vec2(p.x - SQRT_3*p.y, -SQRT_3*p.x - p.y) / 2.0;
+    }
+    p.x -= clamp(p.x, -2.0, 0.0);
+    return -length(p) * sign(p.y);
+}
+
+void main() {
+    // Position in the billboard from -1 to 1
+    vec2 p = out_uv * 2.0 - 1.0;
+    // The markers are stroked with a 1 pixel width line
+    float w = 2.0 / out_size;
+    // Keep some space for the antialiasing
+    float r = 1.0 - w;
+
+    float d;
+    if (u_shape == 0) {
+        // circle
+        d = abs(length(p) - r);
+    } else if (u_shape == 1) {
+        // square
+        d = abs(max(abs(p.x), abs(p.y)) - r);
+    } else if (u_shape == 2) {
+        // plus
+        float d_h = abs(p.x) <= r ? abs(p.y) : 1.0;
+        float d_v = abs(p.y) <= r ? abs(p.x) : 1.0;
+        d = min(d_h, d_v);
+    } else if (u_shape == 3) {
+        // cross
+        d = max(abs(p.x), abs(p.y)) <= r ? min(abs(p.x - p.y), abs(p.x + p.y)) / SQRT_2 : 1.0;
+    } else if (u_shape == 4) {
+        // rhomb
+        d = abs(abs(p.x) + abs(p.y) - r) / SQRT_2;
+    } else {
+        // triangle, its bounding box being centered on the source
+        vec2 q = p + vec2(0.0, 0.5*r/SQRT_3);
+        d = abs(sd_triangle(q / r)) * r;
+    }
+
+    // Distance to the stroke in pixels
+    float d_px = d * out_size * 0.5;
+    float a = 1.0 - smoothstep(0.5, 1.5, d_px);
+    if (a <= 0.0) {
+        discard;
+    }
+
+    if (u_colormapped) {
+        color = colormap_f(out_value);
+    } else {
+        color = vec4(u_color, 1.0);
+    }
+    color.a *= a * u_opacity;
+}
@@ -0,0 +1,44 @@
+#version 300 es
+precision highp float;
+layout (location = 0) in vec2 offset;
+layout (location = 1) in vec2 uv;
+layout (location = 2) in vec2 center;
+layout (location = 3) in float size;
+layout (location = 4) in float value;
+
+uniform mat4 u_2world;
+uniform vec2 ndc_to_clip;
+uniform float czf;
+// Screen size in css pixels
+uniform vec2 u_screen_size;
+
+out vec2 out_uv;
+out float out_size;
+out float out_value;
+
+#include ../projection/projection.glsl;
+
+void main() {
+    // 1. Convert the (lon, lat) ICRS center of the source into (x, y, z) space coo.
+    vec3 p_xyz = lonlat2xyz(center);
+    // 2. Convert to the world coo system
+    vec3 p = vec3(u_2world * vec4(p_xyz, 1.0));
+
+    out_uv = uv;
+    out_size = size;
+    out_value = value;
+
+    // TAN, SIN and NCP only project the front hemisphere
+    if (p.z < 0.f && (u_proj == 0 || u_proj == 2 || u_proj == 11)) {
+        // Send the marker out of the clipping space
+        gl_Position = vec4(2.f, 2.f, 2.f, 1.f);
+        return;
+    }
+
+    // 3. Process the projection
+    vec2 center_pos_clip_space = proj(p);
+    vec2 center_pos_ndc = center_pos_clip_space / (ndc_to_clip * czf);
+
+    // The billboard has a constant size in pixels
+    gl_Position = vec4(center_pos_ndc + 2.f * offset * size / u_screen_size, 0.f, 1.f);
+}
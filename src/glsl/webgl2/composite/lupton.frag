@@ -0,0 +1,59 @@
+#version 300 es
+precision highp float;
+precision highp sampler2D;
+precision mediump int;
+
+in vec2 frag_uv;
+
+out vec4 out_frag_color;
+
+// The channels rendered offscreen. Their normalized values are
+// stored square rooted to keep precision in the faint parts
+uniform sampler2D tex_red;
+uniform sampler2D tex_green;
+uniform sampler2D tex_blue;
+
+uniform float Q;
+uniform float alpha;
+
+uniform float opacity;
+
+#include ../hips/tonal_corrections.glsl;
+
+float get_channel(sampler2D tex, out float a) {
+    vec4 c = texture(tex, frag_uv);
+    a = c.a;
+
+    float v = (c.r + c.g + c.b) / 3.0;
+    return v * v;
+}
+
+void main() {
+    float a_r, a_g, a_b;
+    vec3 rgb = vec3(
+        get_channel(tex_red, a_r),
+        get_channel(tex_green, a_g),
+        get_channel(tex_blue, a_b)
+    );
+    float a = max(a_r, max(a_g, a_b));
+
+    // Lupton et al. (2004) asinh stretch of the intensity, normalized
+    // so that an intensity of 1 is mapped to 1 when alpha = 1
+    float q = max(Q, 1e-4);
+    float I = (rgb.r + rgb.g + rgb.b) / 3.0;
+    float F = asinh(alpha * q * I) / asinh(q);
+    if (I > 0.0) {
+        rgb *= F / I;
+    } else {
+        rgb = vec3(0.0);
+    }
+
+    // Saturated pixels keep their color
+    float max_channel = max(rgb.r, max(rgb.g, rgb.b));
+    if (max_channel > 1.0) {
+        rgb /= max_channel;
+    }
+
+    vec4 color = apply_tonal(vec4(rgb, a));
+    out_frag_color = vec4(color.rgb, color.a * opacity);
+}
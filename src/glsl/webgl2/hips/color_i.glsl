@@ -1,8 +1,5 @@
-//const int MAX_NUM_TEX = 3;
-uniform isampler2D tex1;
-uniform isampler2D tex2;
-//uniform isampler2D tex3;
-uniform int num_tex;
+// The slices of the texture array storing the tiles
+uniform isampler2DArray tex;
 
 uniform float scale;
 uniform float offset;
@@ -22,19 +19,7 @@ uniform int tex_storing_fits;
 #include ./tonal_corrections.glsl;
 
 ivec4 get_pixels(vec3 uv) {
-    /*int idx_texture = int(uv.z);
-    if (idx_texture == 0) {
-        return texture(tex1, uv.xy);
-    } else if (idx_texture == 1) {
-        return texture(tex2, uv.xy);
-    } else if (idx_texture == 2) {
-        return texture(tex3, uv.xy);
-    } else {
-        return ivec4(0, 0, 0, 1);
-    }*/
-    //return texture(tex1, uv.xy);
-    int idx_texture = int(uv.z);
-    return ivec4(mix(vec4(texture(tex1, uv.xy)), vec4(texture(tex2, uv.xy)), float(idx_texture)));
+    return texture(tex, uv);
 }
 
 vec3 reverse_uv(vec3 uv) {
@@ -3,6 +3,9 @@ precision highp float;
 precision highp sampler2D;
 precision highp usampler2D;
 precision highp isampler2D;
+precision highp sampler2DArray;
+precision highp isampler2DArray;
+precision highp usampler2DArray;
 precision mediump int;
 
 in vec2 out_clip_pos;
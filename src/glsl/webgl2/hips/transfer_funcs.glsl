@@ -1,3 +1,6 @@
+uniform float stretch_param;
+uniform sampler2D cdf;
+
 float linear_f(float x, float min_value, float max_value) {
     return clamp((x - min_value)/(max_value - min_value), 0.0, 1.0);
 }
@@ -7,15 +10,14 @@ float sqrt_f(float x, float min_value, float max_value) {
     return sqrt(a);
 }
 
-float log_f(float x, float min_value, float max_value) {
+float log_f(float x, float min_value, float max_value, float a) {
     float y = linear_f(x, min_value, max_value);
-    float a = 1000.0;
-    return log(a*y + 1.0)/log(a);
+    return log(a*y + 1.0)/log(a + 1.0);
 }
 
-float asinh_f(float x, float min_value, float max_value) {
+float asinh_f(float x, float min_value, float max_value, float a) {
     float d = linear_f(x, min_value, max_value);
-    return asinh(10.0*d)/3.0;
+    return asinh(a*d)/asinh(a);
 }
 
 float pow2_f(float x, float min_value, float max_value) {
@@ -23,16 +25,38 @@ float pow2_f(float x, float min_value, float max_value) {
     return d*d;
 }
 
+float sinh_f(float x, float min_value, float max_value, float a) {
+    float d = linear_f(x, min_value, max_value);
+    return sinh(a*d)/sinh(a);
+}
+
+float power_f(float x, float min_value, float max_value, float exponent) {
+    float d = linear_f(x, min_value, max_value);
+    return pow(d, exponent);
+}
+
+// The cumulative distribution of the values between the cuts
+float histeq_f(float x, float min_value, float max_value) {
+    float d = linear_f(x, min_value, max_value);
+    return texture(cdf, vec2(d, 0.5)).r;
+}
+
 float transfer_func(int H, float x, float min_value, float max_value) {
     if (H == 0) {
         return linear_f(x, min_value, max_value);
     } else if (H == 1) {
         return sqrt_f(x, min_value, max_value);
     } else if (H == 2) {
-        return log_f(x, min_value, max_value);
+        return log_f(x, min_value, max_value, stretch_param);
     } else if (H == 3) {
-        return asinh_f(x, min_value, max_value);
-    } else {
+        return asinh_f(x, min_value, max_value, stretch_param);
+    } else if (H == 4) {
         return pow2_f(x, min_value, max_value);
+    } else if (H == 5) {
+        return sinh_f(x, min_value, max_value, stretch_param);
+    } else if (H == 6) {
+        return power_f(x, min_value, max_value, stretch_param);
+    } else {
+        return histeq_f(x, min_value, max_value);
     }
 }
\ No newline at end of file
@@ -0,0 +1,13 @@
+vec2 w2c_air(vec3 p) {
+    // Airy projection with theta_b = 90 deg:
+    // r = tan(xi) - 2 * ln(cos(xi)) / tan(xi), xi = theta / 2
+    vec2 q = vec2(-p.x, p.y);
+    float n = length(q);
+    if (n < 1e-6) {
+        return q / PI;
+    }
+    float xi = 0.5 * atan(n, p.z);
+    float txi = tan(xi);
+    float r = txi - 2.0 * log(cos(xi)) / txi;
+    return q * (r / (n * PI));
+}
@@ -0,0 +1,10 @@
+vec2 w2c_arc(vec3 p) {
+    // Zenithal equidistant, r = theta in [0, PI]
+    vec2 q = vec2(-p.x, p.y);
+    float n = length(q);
+    if (n < 1e-6) {
+        return q / PI;
+    }
+    float t = atan(n, p.z);
+    return q * (t / (n * PI));
+}
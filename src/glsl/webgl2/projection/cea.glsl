@@ -0,0 +1,4 @@
+vec2 w2c_cea(vec3 p) {
+    // lambda = 1, y = sin(lat)
+    return vec2(atan(-p.x, p.z) / PI, p.y);
+}
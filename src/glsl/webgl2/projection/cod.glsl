@@ -0,0 +1,21 @@
+// Conic equidistant projection with theta_a = 45 deg and eta = 0
+// x in [-3.356194490192345, 3.356194490192345]
+// y in [-2.356194490192345, 3.0328465566001492]
+const float COD_X_HALF_LEN = 3.356194490192345;
+const float COD_Y_HALF_LEN = 2.694520523396247;
+
+vec2 w2c_cod(vec3 p) {
+    // The projection is centered on (0, 45 deg)
+    float c = 0.5 * SQRT_2;
+    vec3 q = vec3(p.x, c * (p.y + p.z), c * (p.z - p.y));
+
+    float lon = atan(-q.x, q.z);
+    float lat = asin(q.y);
+
+    float r = 0.25 * PI - lat + 1.0;
+    float a = c * lon;
+    float x = r * sin(a);
+    float y = 1.0 - r * cos(a);
+
+    return vec2(x / COD_X_HALF_LEN, y / COD_Y_HALF_LEN);
+}
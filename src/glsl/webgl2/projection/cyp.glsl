@@ -0,0 +1,5 @@
+vec2 w2c_cyp(vec3 p) {
+    // mu = lambda = 1, y = 2 * sin(lat) / (1 + cos(lat)) in [-2, 2]
+    float c = length(p.zx);
+    return vec2(atan(-p.x, p.z) / PI, p.y / (1.0 + c));
+}
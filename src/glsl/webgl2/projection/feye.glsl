@@ -0,0 +1,11 @@
+vec2 w2c_feye(vec3 p) {
+    // Fish-eye projection, r = 4 * sin(theta / 4)
+    // normalized so that the whole sphere fits the unit disk
+    vec2 q = vec2(-p.x, p.y);
+    float n = length(q);
+    if (n < 1e-6) {
+        return q / (2.0 * SQRT_2);
+    }
+    float t = atan(n, p.z);
+    return q * (sin(0.25 * t) * SQRT_2 / n);
+}
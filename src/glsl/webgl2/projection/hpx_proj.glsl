@@ -0,0 +1,20 @@
+// HEALPix projection (H = 4, K = 3)
+// x in [-PI, PI], y in [-PI/2, PI/2]
+vec2 w2c_hpx(vec3 p) {
+    float lon = atan(-p.x, p.z);
+    float z = p.y;
+    float abs_z = abs(z);
+
+    float x = lon;
+    float y = 0.375 * PI * z;
+    if (abs_z > 2.0 / 3.0) {
+        // Polar caps
+        float sigma = sqrt(3.0 * (1.0 - abs_z));
+        // Center of the facet the longitude belongs to
+        float lon_c = (min(floor(2.0 * (lon + PI) / PI), 3.0) + 0.5) * 0.5 * PI - PI;
+        x = lon_c + (lon - lon_c) * sigma;
+        y = sign(z) * 0.25 * PI * (2.0 - sigma);
+    }
+
+    return vec2(x / PI, y * 2.0 / PI);
+}
@@ -0,0 +1,5 @@
+vec2 w2c_ncp(vec3 p) {
+    // Orthographic projection onto the plane of the equator of the native frame
+    vec2 q = vec2(-p.x, p.y);
+    return p.z >= 0.f ? q : normalize(q);
+}
@@ -0,0 +1,7 @@
+vec2 w2c_par(vec3 p) {
+    // Parabolic projection
+    float lon = atan(-p.x, p.z);
+    float lat = asin(p.y);
+    float x = lon * (2.0 * cos(lat * 2.0 / 3.0) - 1.0);
+    return vec2(x / PI, 2.0 * sin(lat / 3.0));
+}
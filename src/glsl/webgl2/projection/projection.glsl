@@ -9,6 +9,17 @@ const float SQRT_2 = 1.41421356237309504880168872420969808;
 #include ./stg.glsl;
 #include ./zea.glsl;
 #include ./mer.glsl;
+#include ./car.glsl;
+#include ./cea.glsl;
+#include ./cyp.glsl;
+#include ./arc.glsl;
+#include ./ncp.glsl;
+#include ./air.glsl;
+#include ./feye.glsl;
+#include ./par.glsl;
+#include ./sfl.glsl;
+#include ./cod.glsl;
+#include ./hpx_proj.glsl;
 
 vec3 lonlat2xyz(vec2 lonlat) {
     float t = lonlat.x;
@@ -44,9 +55,43 @@ vec2 proj(vec3 p) {
     } else if (u_proj == 5) {
         // MOL,      Mollweide                  */
         return w2c_mol(p);
-    } else {
+    } else if (u_proj == 6) {
         // Cylindrical projections
         // MER,      Mercator                   */
         return w2c_mer(p);
+    } else if (u_proj == 7) {
+        // CAR,                                 */
+        return w2c_car(p);
+    } else if (u_proj == 8) {
+        // CEA,                                 */
+        return w2c_cea(p);
+    } else if (u_proj == 9) {
+        // CYP,                                 */
+        return w2c_cyp(p);
+    } else if (u_proj == 10) {
+        /* ARC,                                 */
+        return w2c_arc(p);
+    } else if (u_proj == 11) {
+        /* NCP,                                 */
+        return w2c_ncp(p);
+    } else if (u_proj == 12) {
+        /* AIR,                                 */
+        return w2c_air(p);
+    } else if (u_proj == 13) {
+        /* FEYE,     Fish-eyes                  */
+        return w2c_feye(p);
+    } else if (u_proj == 14) {
+        // PAR,                                 */
+        return w2c_par(p);
+    } else if (u_proj == 15) {
+        // SFL,                                 */
+        return w2c_sfl(p);
+    } else if (u_proj == 16) {
+        // Conic projections
+        // COD,                                 */
+        return w2c_cod(p);
+    } else {
+        // HEALPix hybrid projection
+        return w2c_hpx(p);
     }
 }
\ No newline at end of file
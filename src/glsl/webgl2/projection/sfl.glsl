@@ -0,0 +1,6 @@
+vec2 w2c_sfl(vec3 p) {
+    // Sanson-Flamsteed projection
+    float lon = atan(-p.x, p.z);
+    float lat = asin(p.y);
+    return vec2(lon * cos(lat) / PI, lat * 2.0 / PI);
+}
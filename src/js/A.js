@@ -41,6 +41,7 @@ import { Source } from "./Source.js";
 import { Coo } from "./libs/astro/coo.js";
 import { URLBuilder } from "./URLBuilder.js";
 import { Footprint } from './Footprint.js';
+import { RGBComposite } from './RGBComposite.js';
 import { Aladin } from "./Aladin.js";
 import { ActionButton } from "./gui/Widgets/ActionButton.js";
 import { Box } from "./gui/Widgets/Box.js";
@@ -188,6 +189,26 @@ A.image = function (url, options) {
     return Aladin.createImageFITS(url, options, options.successCallback, options.errorCallback);
 }
 
+/**
+ * Creates a RGB composite of three single channel image layers
+ *
+ * @function
+ * @name A.rgbComposite
+ * @memberof A
+ * @param {RGBCompositeOptions} options - The red, green and blue channels and the stretch of the composite
+ * @returns {RGBComposite} - A RGB composite image layer object
+ * @example
+ *  aladin.setOverlayImageLayer(A.rgbComposite({
+ *      red: {layer: 'i', minCut: 0, maxCut: 5000},
+ *      green: {layer: 'r', minCut: 0, maxCut: 5000},
+ *      blue: {layer: 'g', minCut: 0, maxCut: 5000},
+ *      Q: 8,
+ *  }), 'gri');
+ */
+A.rgbComposite = function (options) {
+    return new RGBComposite(options);
+}
+
 /**
  * Creates a celestial source object with the given coordinates.
  *
@@ -141,7 +141,7 @@ import { Polyline } from "./shapes/Polyline";
  * @property {boolean} [gridOptions.showLabels=true] - Whether the grid has labels.
  * @property {number} [gridOptions.labelSize=15] - The font size of the labels.
  * 
- * @property {string} [projection="SIN"] - Projection type. Can be 'SIN' for orthographic, 'MOL' for mollweide, 'AIT' for hammer-aitoff, 'ZEA' for zenital equal-area or 'MER' for mercator
+ * @property {string} [projection="SIN"] - Projection type. Can be 'SIN' for orthographic, 'MOL' for mollweide, 'AIT' for hammer-aitoff, 'ZEA' for zenital equal-area, 'MER' for mercator, or any of 'TAN', 'STG', 'FEYE', 'AIR', 'ARC', 'NCP', 'CAR', 'CEA', 'CYP', 'PAR', 'SFL', 'COD' and 'HPX'
  * @property {boolean} [log=true] - Whether to log events.
  * @property {boolean} [samp=false] - Whether to enable SAMP (Simple Application Messaging Protocol).
  * @property {boolean} [realFullscreen=false] - Whether to use real fullscreen mode.
@@ -212,7 +212,7 @@ import { Polyline } from "./shapes/Polyline";
 
 /**
  * @typedef {string} CooFrame
- * String with possible values: 'equatorial', 'ICRS', 'ICRSd', 'j2000', 'gal, 'galactic'
+ * String with possible values: 'equatorial', 'ICRS', 'ICRSd', 'j2000', 'gal, 'galactic', 'ecliptic', 'eclipticd', 'supergalactic', 'fk4', 'B1950'
  */
 
 /**
@@ -927,9 +927,20 @@ export let Aladin = (function () {
      * <br>"STG" (Stereographic projection)
      * <br>"SIN" (Orthographic projection)
      * <br>"ZEA" (Zenital equal-area projection)
+     * <br>"FEYE" (Fish eye projection)
+     * <br>"AIR" (Airy projection)
+     * <br>"ARC" (Zenital equidistant projection)
+     * <br>"NCP" (North celestial pole projection)
      * <br>"MER" (Mercator projection)
+     * <br>"CAR" (Plate carrée projection)
+     * <br>"CEA" (Cylindrical equal area projection)
+     * <br>"CYP" (Cylindrical perspective projection)
      * <br>"AIT" (Hammer-Aitoff projection)
+     * <br>"PAR" (Parabolic projection)
+     * <br>"SFL" (Sanson-Flamsteed projection)
      * <br>"MOL" (Mollweide projection)
+     * <br>"COD" (Conic equidistant projection)
+     * <br>"HPX" (HEALPix projection)
      *
      * @example
      * // Set the projection to 'orthographic'
@@ -1508,7 +1519,7 @@ export let Aladin = (function () {
      * <li>A dict storing a local HiPS files. This object contains a tile file: hips[order][ipix] = File and refers to the properties file like so: hips["properties"] = File. </li>
      *     A javascript {@link FileList} pointing to the opened webkit directory is also accepted.
      * </ul>
-     * @param {string} [cooFrame] - Values accepted: 'equatorial', 'icrs', 'icrsd', 'j2000', 'gal', 'galactic'
+     * @param {string} [cooFrame] - Values accepted: 'equatorial', 'icrs', 'icrsd', 'j2000', 'gal', 'galactic', 'ecliptic', 'supergalactic', 'B1950'
      * @param {number} [maxOrder] - The maximum HEALPix order of the HiPS, i.e the HEALPix order of the most refined tile images of the HiPS.
      * @param {HiPSOptions} [options] - Options describing the survey
      * @returns {HiPS} A HiPS image object.
@@ -1545,7 +1556,7 @@ export let Aladin = (function () {
      * <li>A dict storing a local HiPS files. This object contains a tile file: hips[order][ipix] = File and refers to the properties file like so: hips["properties"] = File. </li>
      *     A javascript {@link FileList} pointing to the opened webkit directory is also accepted.
      * </ul>
-     * @param {string} [cooFrame] - Values accepted: 'equatorial', 'icrs', 'icrsd', 'j2000', 'gal', 'galactic'
+     * @param {string} [cooFrame] - Values accepted: 'equatorial', 'icrs', 'icrsd', 'j2000', 'gal', 'galactic', 'ecliptic', 'supergalactic', 'B1950'
      * @param {number} [maxOrder] - The maximum HEALPix order of the HiPS, i.e the HEALPix order of the most refined tile images of the HiPS.
      * @param {HiPSOptions} [options] - Options describing the survey
      * @returns {HiPS} A HiPS image object.
@@ -2394,7 +2405,7 @@ aladin.on("layerChanged", (layer, layerName, state) => {
         // the radecsys keyword
         if (radesys == "ICRS    ") WCS.RADESYS = radesys;
 
-        const isProjZenithal = ['TAN', 'SIN', 'STG', 'ZEA'].some((p) => p === projName)
+        const isProjZenithal = ['TAN', 'SIN', 'STG', 'ZEA', 'AIR', 'ARC', 'NCP'].some((p) => p === projName)
         if (isProjZenithal) {
             // zenithal projections
             // express the 3rd euler angle for zenithal projection
@@ -2471,11 +2482,11 @@ aladin.on("layerChanged", (layer, layerName, state) => {
     Aladin.prototype.pix2world = function (x, y, frame) {
         if (frame) {
             frame = CooFrameEnum.fromString(frame, CooFrameEnum.J2000);
-            if (frame.label == CooFrameEnum.SYSTEMS.GAL) {
-                frame = Aladin.wasmLibs.core.CooSystem.GAL;
+            if (frame.system == CooFrameEnum.SYSTEMS.J2000) {
+                frame = Aladin.wasmLibs.core.CooSystem.ICRS;
             }
             else {
-                frame = Aladin.wasmLibs.core.CooSystem.ICRS;
+                frame = Aladin.wasmLibs.core.CooSystem[frame.system];
             }
         }
 
@@ -2508,11 +2519,11 @@ aladin.on("layerChanged", (layer, layerName, state) => {
                 frame = CooFrameEnum.fromString(frame, CooFrameEnum.J2000);
             }
     
-            if (frame.label == CooFrameEnum.SYSTEMS.GAL) {
-                frame = Aladin.wasmLibs.core.CooSystem.GAL;
+            if (frame.system == CooFrameEnum.SYSTEMS.J2000) {
+                frame = Aladin.wasmLibs.core.CooSystem.ICRS;
             }
             else {
-                frame = Aladin.wasmLibs.core.CooSystem.ICRS;
+                frame = Aladin.wasmLibs.core.CooSystem[frame.system];
             }
         }
 
@@ -30,13 +30,17 @@
 
 export let CooFrameEnum = (function() {
 
-    var systems = {J2000: 'J2000', GAL: 'GAL'};
+    var systems = {J2000: 'J2000', GAL: 'GAL', ECL: 'ECL', ECLD: 'ECLD', SGAL: 'SGAL', FK4: 'FK4'};
     return {
         SYSTEMS: systems,
 
         J2000: {label: "ICRS", system: systems.J2000},
         J2000d: {label: "ICRSd", system: systems.J2000},
         GAL:  {label: "GAL", system: systems.GAL},
+        ECL:  {label: "ECL", system: systems.ECL},
+        ECLD: {label: "ECLD", system: systems.ECLD},
+        SGAL: {label: "SGAL", system: systems.SGAL},
+        FK4:  {label: "FK4", system: systems.FK4},
 
         fromString: function(str, defaultValue) {
             if (! str) {
@@ -54,6 +58,18 @@ export let CooFrameEnum = (function() {
             else if (str.indexOf('gal')==0) {
                 return CooFrameEnum.GAL;
             }
+            else if (str.indexOf('eclipticd')==0 || str.indexOf('ecld')==0) {
+                return CooFrameEnum.ECLD;
+            }
+            else if (str.indexOf('ecl')==0) {
+                return CooFrameEnum.ECL;
+            }
+            else if (str.indexOf('supergal')==0 || str.indexOf('sgal')==0) {
+                return CooFrameEnum.SGAL;
+            }
+            else if (str.indexOf('fk4')==0 || str.indexOf('b1950')==0) {
+                return CooFrameEnum.FK4;
+            }
             else {
                 return defaultValue ? defaultValue : null;
             }
@@ -77,8 +77,34 @@ PropertyParser.formats = function (properties) {
     let formats = (properties && properties.hips_tile_format) || "jpeg";
 
     formats = formats.split(" ").map((fmt) => fmt.toLowerCase());
+    // Tile-compressed FITS tiles are decompressed and handled as FITS ones
+    formats = formats.map((fmt) => (fmt === "fits.fz" ? "fits" : fmt));
 
-    return formats;
+    return [...new Set(formats)];
+};
+
+/// Whether the FITS tiles are only available tile-compressed (fits.fz)
+PropertyParser.fitsCompressed = function (properties) {
+    let formats = (properties && properties.hips_tile_format) || "";
+    formats = formats.split(" ").map((fmt) => fmt.toLowerCase());
+
+    return formats.indexOf("fits.fz") >= 0 && formats.indexOf("fits") < 0;
+};
+
+/// The urls serving the HiPS, given by the hips_service_url and hips_service_url_n properties
+PropertyParser.mirrors = function (properties) {
+    let mirrors = [];
+    if (properties && properties.hips_service_url) {
+        mirrors.push(properties.hips_service_url);
+    }
+
+    let k = 1;
+    while (properties && properties.hasOwnProperty("hips_service_url_" + k)) {
+        mirrors.push(properties["hips_service_url_" + k]);
+        k += 1;
+    }
+
+    return mirrors;
 };
 
 PropertyParser.initialFov = function (properties) {
@@ -104,10 +130,11 @@ PropertyParser.skyFraction = function (properties) {
 };
 
 PropertyParser.cutouts = function (properties) {
-    let cuts =
+    // hips_pixel_cut is preferred to hips_data_range which gives the whole range of the pixel values
+    const cutProperty =
         properties &&
-        properties.hips_pixel_cut &&
-        properties.hips_pixel_cut.split(" ");
+        (properties.hips_pixel_cut || properties.hips_data_range);
+    let cuts = cutProperty && cutProperty.trim().split(/\s+/);
 
     const minCutout = cuts && parseFloat(cuts[0]);
     const maxCutout = cuts && parseFloat(cuts[1]);
@@ -133,6 +160,8 @@ PropertyParser.isPlanetaryBody = function (properties) {
  * @property {string} [name] - The name of the survey to be displayed in the UI
  * @property {Function} [successCallback] - A callback executed when the HiPS has been loaded
  * @property {Function} [errorCallback] - A callback executed when the HiPS could not be loaded
+ * @property {HiPSRequestOptions} [requestOptions] - Options of the HTTP requests of the HiPS (properties, tiles, allsky and MOC), e.g. to access a private HiPS
+ * @property {string[]} [mirrors] - Other urls serving the HiPS, added to the ones of the properties file. The tiles are requested from another mirror when the current one starts failing
  * @property {string} [imgFormat] - Formats accepted 'webp', 'png', 'jpeg' or 'fits'. Will raise an error if the HiPS does not contain tiles in this format
  * @property {CooFrame} [cooFrame="J2000"] - Coordinate frame of the survey tiles
  * @property {number} [maxOrder] - The maximum HEALPix order of the HiPS, i.e the HEALPix order of the most refined tile images of the HiPS.
@@ -144,6 +173,8 @@ PropertyParser.isPlanetaryBody = function (properties) {
  * @property {number} [opacity=1.0] - Opacity of the survey or image (value between 0 and 1).
  * @property {string} [colormap="native"] - The colormap configuration for the survey or image.
  * @property {string} [stretch="linear"] - The stretch configuration for the survey or image.
+ * Possible values are 'linear', 'sqrt', 'pow2', 'log', 'asinh', 'sinh', 'power(n)' and 'histeq' (histogram equalization).
+ * The softening parameter of 'log', 'asinh' and 'sinh' can be given e.g. 'asinh(20)'.
  * @property {boolean} [reversed=false] - If true, the colormap is reversed; otherwise, it is not reversed.
  * @property {number} [minCut] - The minimum cut value for the color configuration. If not given, 0.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
  * @property {number} [maxCut] - The maximum cut value for the color configuration. If not given, 1.0 for JPEG/PNG surveys, the value of the property file for FITS surveys
@@ -154,6 +185,25 @@ PropertyParser.isPlanetaryBody = function (properties) {
  * @property {number} [contrast=0.0] - The contrast value for the color configuration.
  */
 
+/**
+ * The options of the HTTP requests of a HiPS
+ *
+ * @typedef {Object} HiPSRequestOptions
+ * @property {Object.<string, string>} [headers] - Extra headers sent with every request, e.g. <code>{Authorization: 'Bearer ...'}</code>.
+ * Tiles are then fetched instead of being loaded by an image element, the HiPS server must allow these headers with CORS.
+ * @property {string} [credentials="same-origin"] - The credentials mode of the requests: 'omit', 'same-origin' or 'include' (to send the cookies to another origin)
+ * @property {Function} [refreshToken] - A function giving a new token or a promise of it. It is called when the server answers HTTP 401,
+ * the token being sent as a bearer <code>Authorization</code> header and the request done again.
+ *
+ * @example
+ * let hips = A.HiPS('https://my.server/private/hips', {
+ *     requestOptions: {
+ *         headers: {Authorization: 'Bearer ' + token},
+ *         refreshToken: () => myAuth.getNewToken(),
+ *     }
+ * });
+ */
+
 /**
  * JS {@link https://developer.mozilla.org/fr/docs/Web/API/FileList| FileList} API type
  * 
@@ -250,6 +300,8 @@ export let HiPS = (function () {
         this.creatorDid = options.creatorDid;
         this.errorCallback = options.errorCallback;
         this.successCallback = options.successCallback;
+        this.requestOptions = options.requestOptions;
+        this.mirrors = options.mirrors || [];
 
         this.colorCfg = new ColorCfg(options);
     };
@@ -300,6 +352,10 @@ export let HiPS = (function () {
         // Tile formats
         self.formats =
             PropertyParser.formats(properties) || self.formats;
+        self.fitsCompressed = PropertyParser.fitsCompressed(properties);
+
+        // Mirrors
+        self.mirrors = [...new Set(self.mirrors.concat(PropertyParser.mirrors(properties)))];
 
         // min order
         self.minOrder =
@@ -323,6 +379,17 @@ export let HiPS = (function () {
             properties.hips_initial_dec &&
             +properties.hips_initial_dec;
 
+        // HiPS cube
+        self.cubeDepth =
+            properties &&
+            properties.hips_cube_depth &&
+            +properties.hips_cube_depth;
+        self.cubeFrame =
+            (properties &&
+                properties.hips_cube_firstframe &&
+                +properties.hips_cube_firstframe) ||
+            0;
+
         // Cutouts
         const cutoutFromProperties = PropertyParser.cutouts(properties);
         self.defaultFitsMinCut = cutoutFromProperties[0];
@@ -430,11 +497,17 @@ export let HiPS = (function () {
             self.cooFrame = "ICRS";
         } else if (self.cooFrame == "galactic" || self.cooFrame == "GAL") {
             self.cooFrame = "GAL";
+        } else if (self.cooFrame == "ecliptic" || self.cooFrame == "ECL") {
+            self.cooFrame = "ECL";
+        } else if (self.cooFrame == "supergalactic" || self.cooFrame == "SGAL") {
+            self.cooFrame = "SGAL";
+        } else if (self.cooFrame == "B1950" || self.cooFrame == "FK4") {
+            self.cooFrame = "FK4";
         } else {
             console.warn(
                 "Invalid cooframe given: " +
                     self.cooFrame +
-                    '. Coordinate systems supported: "ICRS", "ICRSd", "j2000" or "galactic". ICRS is chosen by default'
+                    '. Coordinate systems supported: "ICRS", "ICRSd", "j2000", "galactic", "ecliptic", "supergalactic" or "B1950". ICRS is chosen by default'
             );
             self.cooFrame = "ICRS";
 
@@ -505,7 +578,7 @@ export let HiPS = (function () {
                 // ID typed url
                 if (self.startUrl && isID) {
                     // First download the properties from the start url
-                    await HiPSProperties.fetchFromUrl(self.startUrl)
+                    await HiPSProperties.fetchFromUrl(self.startUrl, self.requestOptions)
                         .then((p) => {
                             self._parseProperties(p);
                         })
@@ -552,7 +625,7 @@ export let HiPS = (function () {
                         throw e;
                     }
                 } else {
-                    await HiPSProperties.fetchFromUrl(self.url)
+                    await HiPSProperties.fetchFromUrl(self.url, self.requestOptions)
                         .then((p) => {
                             self._parseProperties(p);
                         })
@@ -758,7 +831,7 @@ export let HiPS = (function () {
      * <br>"green"
      * <br>"blue"
      * @param {Object} [options] - Options for the colormap
-     * @param {string} [options.stretch] - Stretching function of the colormap. Possible values are 'linear', 'asinh', 'log', 'sqrt', 'pow'. If no given, will not change it.
+     * @param {string} [options.stretch] - Stretching function of the colormap. Possible values are 'linear', 'sqrt', 'pow2', 'log', 'asinh', 'sinh', 'power(n)' and 'histeq'. The softening parameter of 'log', 'asinh' and 'sinh' can be given e.g. 'asinh(20)'. If no given, will not change it.
      * @param {boolean} [options.reversed=false] - Reverse the colormap axis.
      */
     HiPS.prototype.setColormap = function (colormap, options) {
@@ -783,6 +856,27 @@ export let HiPS = (function () {
         return this.colorCfg.getCuts();
     };
 
+    /**
+     * Sets the cuts of a FITS HiPS from the pixel values of its tiles in the current view
+     *
+     * @memberof HiPS
+     *
+     * @param {Object|string} [cutAlgorithm] - The algorithm computing the cuts, see the cutAlgorithm option of {@link Image}.
+     * The 1st and 99th percentiles are taken if not given.
+     *
+     * @returns {number[]} The new cuts [minCut, maxCut]
+     */
+    HiPS.prototype.setCutsFromView = function (cutAlgorithm) {
+        if (typeof cutAlgorithm === 'string') {
+            cutAlgorithm = {algorithm: cutAlgorithm};
+        }
+
+        const [minCut, maxCut] = this.view.wasm.setHiPSCutsFromView(this.layer, cutAlgorithm);
+        this.setCuts(minCut, maxCut);
+
+        return [minCut, maxCut];
+    };
+
     /**
      * Sets the gamma correction factor for the HiPS.
      *
@@ -884,6 +978,12 @@ export let HiPS = (function () {
                 hipsInitialFov: self.initialFov,
                 hipsInitialRa: self.initialRa,
                 hipsInitialDec: self.initialDec,
+                hipsCubeDepth: self.cubeDepth,
+                hipsCubeFirstframe: self.cubeFrame,
+                fitsCompressed: self.fitsCompressed,
+                mirrors: self.mirrors,
+                minCutout: Number.isFinite(self.defaultFitsMinCut) ? self.defaultFitsMinCut : undefined,
+                maxCutout: Number.isFinite(self.defaultFitsMaxCut) ? self.defaultFitsMaxCut : undefined,
                 isPlanetaryBody: self.isPlanetaryBody(),
                 hipsBody: self.hipsBody,
             },
@@ -891,7 +991,8 @@ export let HiPS = (function () {
                 ...this.colorCfg.get(),
                 longitudeReversed: this.longitudeReversed,
                 imgFormat: this.imgFormat,
-            }
+            },
+            requestOptions: this.requestOptions,
         };
 
         let localFiles;
@@ -967,6 +1068,64 @@ export let HiPS = (function () {
 
     HiPS.prototype.getAlpha = HiPS.prototype.getOpacity;
 
+    /**
+     * Sets the frame displayed of a HiPS cube
+     *
+     * The tiles of the frames near the current one are kept in memory so that scrubbing
+     * through the frames does not fetch them again.
+     *
+     * @memberof HiPS
+     *
+     * @param {number} frame - The 0-based index of the frame. Must be lower than the hips_cube_depth property
+     */
+    HiPS.prototype.setCubeFrame = function (frame) {
+        let self = this;
+        self.query.then(() => {
+            if (!self.cubeDepth) {
+                throw self.id + " is not a HiPS cube";
+            }
+
+            if (self.added) {
+                self.view.wasm.setHiPSCubeFrame(self.layer, frame);
+                self.view.requestRedraw();
+            }
+
+            // Only kept once the frame has been accepted by the core
+            self.cubeFrame = frame;
+        });
+    };
+
+    /**
+     * Gets the frame displayed of a HiPS cube
+     *
+     * @memberof HiPS
+     *
+     * @returns {number|undefined} The 0-based index of the frame or undefined if the HiPS is not a cube
+     */
+    HiPS.prototype.getCubeFrame = function () {
+        return this.cubeDepth ? this.cubeFrame : undefined;
+    };
+
+    /**
+     * Computes the histogram of the pixel values of a FITS HiPS or image
+     *
+     * The values of a HiPS are sampled from its tiles in the view, those of an image when it has been loaded.
+     * BSCALE and BZERO are applied and blank values excluded.
+     *
+     * @memberof HiPS
+     *
+     * @param {Object} [options] - The histogram options
+     * @param {number} [options.numBins=256] - The number of bins
+     * @param {boolean} [options.log=false] - Logarithmically spaced bins, values lower or equal to 0 being excluded
+     * @param {number} [options.min] - The lower edge of the first bin, the minimum value if not given
+     * @param {number} [options.max] - The upper edge of the last bin, the maximum value if not given
+     *
+     * @returns {Object} An object {counts, edges, numValues}, edges being the numBins + 1 edges of the bins
+     */
+    HiPS.prototype.getHistogram = function (options) {
+        return this.view.wasm.getLayerHistogram(this.layer, options);
+    };
+
     // @api
     HiPS.prototype.readPixel = function (x, y) {
         return this.view.wasm.readPixel(x, y, this.layer);
@@ -69,7 +69,47 @@ HiPSProperties.fetchFromID = async function(ID) {
     }
 }
 
-HiPSProperties.fetchFromUrl = async function(urlOrId) {
+// Fetch a resource of a HiPS with its request options
+//
+// The headers of the request options are added to the ones of init.
+// On HTTP 401, the token is refreshed and stored in the headers so that the
+// tile requests done afterwards use it. If the refresh fails, the 401 response is returned
+HiPSProperties.fetchWithOptions = async function(url, init, requestOptions) {
+    if (!requestOptions) {
+        return fetch(url, init);
+    }
+
+    const withOptions = () => {
+        let headers = new Headers(init && init.headers);
+        for (const [name, value] of Object.entries(requestOptions.headers || {})) {
+            headers.set(name, value);
+        }
+
+        return fetch(url, {
+            ...init,
+            headers,
+            credentials: requestOptions.credentials || (init && init.credentials),
+        });
+    };
+
+    let response = await withOptions();
+    if (response.status == 401 && requestOptions.refreshToken) {
+        let token;
+        try {
+            token = await requestOptions.refreshToken();
+        } catch (e) {
+            console.error("The refresh of the token failed: ", e);
+            return response;
+        }
+        requestOptions.headers = {...requestOptions.headers, Authorization: 'Bearer ' + token};
+
+        response = await withOptions();
+    }
+
+    return response;
+}
+
+HiPSProperties.fetchFromUrl = async function(urlOrId, requestOptions) {
     let url;
 
     try {
@@ -106,10 +146,12 @@ HiPSProperties.fetchFromUrl = async function(urlOrId) {
         init = { mode: 'cors' };
     }
 
-    let result = fetch(url, init)
+    let result = HiPSProperties.fetchWithOptions(url, init, requestOptions)
         .then((response) => {
             if (response.status == 404) {
                 return Promise.reject("Url points to nothing")
+            } else if (response.status == 401) {
+                return Promise.reject("Unauthorized access to " + url + " (HTTP 401)")
             } else {
                 return response.text();
             }
@@ -70,6 +70,8 @@ import { HiPS } from "./HiPS.js";
  * @property {number} [opacity=1.0] - Opacity of the survey or image (value between 0 and 1).
  * @property {string} [colormap="native"] - The colormap configuration for the survey or image.
  * @property {string} [stretch="linear"] - The stretch configuration for the survey or image.
+ * Possible values are 'linear', 'sqrt', 'pow2', 'log', 'asinh', 'sinh', 'power(n)' and 'histeq' (histogram equalization).
+ * The softening parameter of 'log', 'asinh' and 'sinh' can be given e.g. 'asinh(20)'.
  * @property {boolean} [reversed=false] - If true, the colormap is reversed; otherwise, it is not reversed.
  * @property {number} [minCut=0.0] - The minimum cut value for the color configuration. If not given, 0.0 is chosen
  * @property {number} [maxCut=1.0] - The maximum cut value for the color configuration. If not given, 1.0 is chosen
@@ -80,6 +82,14 @@ import { HiPS } from "./HiPS.js";
  * @property {number} [contrast=0.0] - The contrast value for the color configuration.
  * @property {WCS} [wcs] - an object describing the WCS of the image. In case of a fits image
  * this property will be ignored as the WCS taken will be the one present in the fits file.
+ * @property {Object} [cutAlgorithm] - The algorithm computing the cuts of a FITS image when minCut and maxCut are not given.
+ * It is an object whose <code>algorithm</code> property is either:
+ * <ul>
+ * <li>'percentile' with <code>low</code> and <code>high</code> percents (defaults to 1 and 99)</li>
+ * <li>'zscale', the IRAF zscale algorithm with <code>contrast</code> (0.25), <code>numSamples</code> (1000), <code>maxReject</code> (0.5), <code>minNpixels</code> (5), <code>krej</code> (2.5) and <code>maxIterations</code> (5)</li>
+ * <li>'sigmaClip', an iterative sigma clipping with <code>sigma</code> (3) and <code>maxIterations</code> (5), sigma being estimated from the median absolute deviation</li>
+ * </ul>
+ * A string can also be given to use the default parameters of an algorithm, e.g. 'zscale'.
  * @property {string} [imgFormat] - Optional image format. Giving it will prevent the auto extension determination algorithm to be triggered. Possible values are 'jpeg', 'png' or 'fits'. tiff files are not supported. You can convert your tiff files to jpg ones by using the fantastic image magick suite.
  * 
  * @example
@@ -136,6 +146,7 @@ export let Image = (function () {
         this.id = url;
         this.name = (options && options.name) || this.url;
         this.imgFormat = options && options.imgFormat;
+        this.cutAlgorithm = Image._parseCutAlgorithm(options && options.cutAlgorithm);
         //this.formats = [this.imgFormat];
         // callbacks
         this.successCallback = options && options.successCallback;
@@ -200,6 +211,9 @@ export let Image = (function () {
         // @api
         readPixel: HiPS.prototype.readPixel,
 
+        // @api
+        getHistogram: HiPS.prototype.getHistogram,
+
         // Private method for updating the view with the new meta
         _updateMetadata: HiPS.prototype._updateMetadata,
 
@@ -222,7 +236,8 @@ export let Image = (function () {
                             longitudeReversed: this.longitudeReversed,
                             imgFormat: 'fits',
                         },
-                        layer
+                        layer,
+                        self.cutAlgorithm
                     )
                 },
                 error: (e) => {
@@ -240,7 +255,8 @@ export let Image = (function () {
                                     longitudeReversed: this.longitudeReversed,
                                     imgFormat: 'fits',
                                 },
-                                layer
+                                layer,
+                                self.cutAlgorithm
                             )
                         },
                     });
@@ -428,6 +444,27 @@ export let Image = (function () {
             return promise;
         },
 
+        /**
+         * Recompute the cuts of a FITS image with another algorithm and apply them
+         *
+         * @memberof Image
+         * @param {Object|string} cutAlgorithm - The algorithm, see the cutAlgorithm property of {@link ImageOptions}
+         * @returns {number[]} The new cuts [minCut, maxCut]
+         */
+        // @api
+        setCutAlgorithm: function (cutAlgorithm) {
+            this.cutAlgorithm = Image._parseCutAlgorithm(cutAlgorithm);
+
+            if (!this.added || this.imgFormat !== 'fits') {
+                return undefined;
+            }
+
+            const [minCut, maxCut] = this.view.wasm.setImageCutAlgorithm(this.layer, this.cutAlgorithm);
+            this.setCuts(minCut, maxCut);
+
+            return [minCut, maxCut];
+        },
+
         // FITS images does not mean to be used for storing planetary data
         isPlanetaryBody: function () {
             return false;
@@ -443,5 +480,13 @@ export let Image = (function () {
         },
     };
 
+    Image._parseCutAlgorithm = function (cutAlgorithm) {
+        if (typeof cutAlgorithm === 'string') {
+            return {algorithm: cutAlgorithm};
+        }
+
+        return cutAlgorithm;
+    };
+
     return Image;
 })();
@@ -223,9 +223,10 @@ export let MOC = (function() {
      * Serialize a MOC into different format
      *
      * @memberof MOC
-     * @param {string} [format='json'] - The output format. Only `json` is currently supported but `fits` could be added.
+     * @param {string} [format='json'] - The output format. `json` (Aladin JSON string), `ascii` (IVOA ASCII string) or `fits` (IVOA FITS bytes as a Uint8Array)
      */
     MOC.prototype.serialize = function(format) {
+        format = format || 'json';
         if (!this.ready) {
             throw this.name + " is not yet ready, either because it has not been downloaded yet or because it has not been added to the aladin instance."
         }
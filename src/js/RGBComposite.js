@@ -0,0 +1,260 @@
+// Copyright 2013 - UDS/CNRS
+// The Aladin Lite program is distributed under the terms
+// of the GNU General Public License version 3.
+//
+// This file is part of Aladin Lite.
+//
+//    Aladin Lite is free software: you can redistribute it and/or modify
+//    it under the terms of the GNU General Public License as published by
+//    the Free Software Foundation, version 3 of the License.
+//
+//    Aladin Lite is distributed in the hope that it will be useful,
+//    but WITHOUT ANY WARRANTY; without even the implied warranty of
+//    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//    GNU General Public License for more details.
+//
+//    The GNU General Public License is available in COPYING file
+//    along with Aladin Lite.
+//
+
+/******************************************************************************
+ * Aladin Lite project
+ *
+ * File RGBComposite
+ *
+ * Authors: Matthieu Baumann [CDS]
+ *
+ *****************************************************************************/
+import { ALEvent } from "./events/ALEvent.js";
+import { ColorCfg } from "./ColorCfg.js";
+import { HiPS } from "./HiPS.js";
+import { Utils } from "./Utils";
+
+/**
+ * @typedef {Object} RGBChannel
+ *
+ * @property {string|HiPS|Image} layer - The layer drawn in the channel, given by its name or by the image layer object.
+ * It must be a single channel (e.g. FITS) HiPS or image already added to the view.
+ * @property {number} [minCut] - The background level of the channel. The one of the layer if not given
+ * @property {number} [maxCut] - The value normalized to 1. The one of the layer if not given
+ */
+
+/**
+ * @typedef {Object} RGBCompositeOptions
+ *
+ * @property {string} [name] - A human-readable name for the composite
+ * @property {RGBChannel|string|HiPS|Image} red - The red channel
+ * @property {RGBChannel|string|HiPS|Image} green - The green channel
+ * @property {RGBChannel|string|HiPS|Image} blue - The blue channel
+ * @property {number} [Q=8] - The softening parameter of the Lupton et al. asinh stretch. It tends to be linear when Q tends to 0.
+ * @property {number} [alpha=1] - The linear stretch of the intensity
+ * @property {number} [opacity=1.0] - Opacity of the composite (value between 0 and 1).
+ * @property {boolean} [additive=false] - If true, additive blending is applied; otherwise, it is not applied.
+ * @property {number} [gamma=1.0] - The gamma correction value for the color configuration.
+ * @property {number} [saturation=0.0] - The saturation value for the color configuration.
+ * @property {number} [brightness=0.0] - The brightness value for the color configuration.
+ * @property {number} [contrast=0.0] - The contrast value for the color configuration.
+ *
+ * @example
+ *
+ *  let g = aladin.setOverlayImageLayer(A.HiPS('CDS/P/PanSTARRS/DR1/g', {opacity: 0.0}), 'g');
+ *  let r = aladin.setOverlayImageLayer(A.HiPS('CDS/P/PanSTARRS/DR1/r', {opacity: 0.0}), 'r');
+ *  let i = aladin.setOverlayImageLayer(A.HiPS('CDS/P/PanSTARRS/DR1/i', {opacity: 0.0}), 'i');
+ *
+ *  aladin.setOverlayImageLayer(A.rgbComposite({
+ *      red: {layer: i, minCut: 0, maxCut: 5000},
+ *      green: {layer: r, minCut: 0, maxCut: 5000},
+ *      blue: {layer: g, minCut: 0, maxCut: 5000},
+ *      Q: 8,
+ *      alpha: 1,
+ *  }), 'gri');
+ */
+
+export let RGBComposite = (function () {
+    /**
+     * An image layer combining three single channel layers as its red, green and blue channels.
+     * The channels are normalized with their cuts and combined with the asinh stretch of
+     * Lupton et al. (2004) so that the colors of the bright objects are preserved.
+     *
+     * The channel layers are drawn whether they are visible or not. One can set their opacity to 0
+     * to only see the composite.
+     *
+     * @class
+     * @constructs RGBComposite
+     *
+     * @param {RGBCompositeOptions} options - The channels and the stretch of the composite
+     */
+    let RGBComposite = function (options) {
+        this.layer = null;
+        this.added = false;
+        this.id = Utils.uuidv4();
+        this.name = (options && options.name) || "RGB composite";
+
+        this.red = RGBComposite._parseChannel(options.red);
+        this.green = RGBComposite._parseChannel(options.green);
+        this.blue = RGBComposite._parseChannel(options.blue);
+
+        this.Q = options.Q !== undefined ? options.Q : 8.0;
+        this.alpha = options.alpha !== undefined ? options.alpha : 1.0;
+
+        this.imgFormat = 'png';
+        this.longitudeReversed = false;
+
+        this.colorCfg = new ColorCfg(options);
+        this.options = options;
+
+        this.query = Promise.resolve(this);
+    };
+
+    RGBComposite._parseChannel = function (channel) {
+        if (!channel) {
+            throw "The red, green and blue channels of a RGB composite must be given";
+        }
+
+        // A layer name or an image layer object
+        if (typeof channel === "string" || channel.colorCfg) {
+            channel = {layer: channel};
+        }
+
+        return {...channel};
+    };
+
+    RGBComposite.prototype = {
+        _saveInCache: HiPS.prototype._saveInCache,
+
+        // @api
+        setOpacity: HiPS.prototype.setOpacity,
+        // @api
+        getOpacity: HiPS.prototype.getOpacity,
+        getAlpha: HiPS.prototype.getOpacity,
+        // @api
+        toggle: HiPS.prototype.toggle,
+
+        // @api
+        setOptions: HiPS.prototype.setOptions,
+        // @api
+        setBlendingConfig: HiPS.prototype.setBlendingConfig,
+
+        // @api
+        setGamma: HiPS.prototype.setGamma,
+        // @api
+        setSaturation: HiPS.prototype.setSaturation,
+        setBrightness: HiPS.prototype.setBrightness,
+        setContrast: HiPS.prototype.setContrast,
+
+        setColorCfg: HiPS.prototype.setColorCfg,
+        // @api
+        getColorCfg: HiPS.prototype.getColorCfg,
+
+        // Private method for updating the view with the new meta
+        _updateMetadata: HiPS.prototype._updateMetadata,
+
+        setView: function (view) {
+            this.view = view;
+        },
+
+        /**
+         * Sets the parameters of the Lupton et al. asinh stretch
+         *
+         * @memberof RGBComposite
+         *
+         * @param {number} [Q] - The softening parameter
+         * @param {number} [alpha] - The linear stretch of the intensity
+         */
+        setStretch: function (Q, alpha) {
+            if (Q !== undefined) {
+                this.Q = Q;
+            }
+            if (alpha !== undefined) {
+                this.alpha = alpha;
+            }
+
+            this._updateComposite();
+        },
+
+        /**
+         * Sets the cuts of a channel
+         *
+         * @memberof RGBComposite
+         *
+         * @param {string} channel - 'red', 'green' or 'blue'
+         * @param {number} minCut - The background level of the channel
+         * @param {number} maxCut - The value normalized to 1
+         */
+        setChannelCuts: function (channel, minCut, maxCut) {
+            if (!["red", "green", "blue"].includes(channel)) {
+                throw channel + " is not a channel. Possible values are 'red', 'green' or 'blue'";
+            }
+
+            this[channel] = {...this[channel], minCut, maxCut};
+
+            this._updateComposite();
+        },
+
+        _getConfig: function () {
+            const channel = (c) => {
+                return {
+                    layer: typeof c.layer === "string" ? c.layer : c.layer.layer,
+                    minCut: c.minCut,
+                    maxCut: c.maxCut,
+                };
+            };
+
+            return {
+                layer: this.layer,
+                red: channel(this.red),
+                green: channel(this.green),
+                blue: channel(this.blue),
+                stretch: {
+                    Q: this.Q,
+                    alpha: this.alpha,
+                },
+                meta: {
+                    ...this.colorCfg.get(),
+                    longitudeReversed: this.longitudeReversed,
+                    imgFormat: this.imgFormat,
+                },
+            };
+        },
+
+        _updateComposite: function () {
+            try {
+                if (this.added) {
+                    this.view.wasm.addRGBComposite(this._getConfig());
+                }
+            } catch (e) {
+                // Display the error message
+                console.error(e);
+            }
+        },
+
+        add: function (layer) {
+            this.layer = layer;
+            let self = this;
+
+            // The channel layers given as objects may not be added to the view yet
+            const channelsAdded = [this.red, this.green, this.blue]
+                .map((c) => c.layer)
+                .filter((l) => typeof l !== "string" && !l.added)
+                .map((l) => new Promise((resolve) => {
+                    const onAdded = (e) => {
+                        if (e.detail.layer === l) {
+                            ALEvent.HIPS_LAYER_ADDED.remove(self.view.aladinDiv, onAdded);
+                            resolve();
+                        }
+                    };
+
+                    ALEvent.HIPS_LAYER_ADDED.listenedBy(self.view.aladinDiv, onAdded);
+                }));
+
+            return Promise.all(channelsAdded)
+                .then(() => {
+                    self.view.wasm.addRGBComposite(self._getConfig());
+
+                    return self;
+                });
+        },
+    };
+
+    return RGBComposite;
+})();
@@ -1925,12 +1925,13 @@ export let View = (function () {
         this.cooFrame = cooFrame;
 
         // Set the new frame to the backend
-        if (this.cooFrame.system == CooFrameEnum.SYSTEMS.GAL) {
-            this.wasm.setCooSystem(Aladin.wasmLibs.core.CooSystem.GAL);
-        }
-        else if (this.cooFrame.system == CooFrameEnum.SYSTEMS.J2000) {
+        if (this.cooFrame.system == CooFrameEnum.SYSTEMS.J2000) {
             this.wasm.setCooSystem(Aladin.wasmLibs.core.CooSystem.ICRS);
         }
+        else {
+            // The other systems share the same name in the core
+            this.wasm.setCooSystem(Aladin.wasmLibs.core.CooSystem[this.cooFrame.system]);
+        }
 
         // Set the grid label format
         if (this.cooFrame.label == "ICRSd") {
@@ -186,7 +186,7 @@ import { ColorCfg } from "../../ColorCfg.js";
                 type: 'select',
                 name: 'stretch',
                 value: 'linear',
-                options: ['sqrt', 'linear', 'asinh', 'pow2', 'log'],
+                options: ['sqrt', 'linear', 'asinh', 'pow2', 'log', 'sinh', 'histeq'],
                 change(e) {
                     self.options.layer.setColormap(self.options.layer.getColorCfg().getColormap(), {stretch: e.target.value});
                 },